@@ -0,0 +1,37 @@
+use std::{process::Command, time::SystemTime};
+
+fn main() {
+    // Embedded migrations must be picked up when only a new SQL file is added
+    println!("cargo:rerun-if-changed=migrations");
+
+    // Reported by `/version`. Docker builds have no `.git`, they pass the
+    // commit through `TSUKIMI_GIT_COMMIT` instead.
+    println!("cargo:rerun-if-env-changed=TSUKIMI_GIT_COMMIT");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    let commit = std::env::var("TSUKIMI_GIT_COMMIT").ok().or_else(|| {
+        Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|commit| commit.trim().to_string())
+    });
+    println!(
+        "cargo:rustc-env=TSUKIMI_GIT_COMMIT={}",
+        commit.unwrap_or_else(|| "unknown".to_string())
+    );
+
+    let build_time = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=TSUKIMI_BUILD_TIMESTAMP={}", build_time);
+
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(str::to_string))
+        .map(|feature| feature.to_lowercase().replace('_', "-"))
+        .collect();
+    features.sort();
+    println!("cargo:rustc-env=TSUKIMI_FEATURES={}", features.join(","));
+}
@@ -0,0 +1,371 @@
+-- Extension pour générer des UUIDs
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+-- Extension pour la recherche approchée (fautes de frappe)
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Rôles globaux, par moteur et par projet
+CREATE TYPE global_role AS ENUM ('admin', 'user');
+CREATE TYPE engine_role AS ENUM ('owner', 'maintainer');
+CREATE TYPE project_role AS ENUM ('owner', 'translator', 'reviewer', 'viewer');
+CREATE TYPE project_visibility AS ENUM ('public', 'private');
+CREATE TYPE review_state AS ENUM ('translated', 'needs_work', 'approved');
+CREATE TYPE suggestion_status AS ENUM ('pending', 'approved', 'rejected');
+CREATE TYPE issue_flag AS ENUM ('needs_context', 'source_typo', 'glossary_question', 'other');
+
+-- Table des utilisateurs
+CREATE TABLE users (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    username VARCHAR(50) NOT NULL UNIQUE,
+    email VARCHAR(255) UNIQUE,
+    github_id BIGINT UNIQUE,
+    role global_role NOT NULL DEFAULT 'user',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Sessions émises par l'API (le token GitHub n'est jamais conservé)
+CREATE TABLE sessions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- SHA-256 du refresh token, remplacé à chaque rafraîchissement
+    refresh_token_hash CHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    -- Liste de révocation : une session révoquée invalide ses access tokens
+    revoked_at TIMESTAMPTZ,
+    refreshed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Tokens d'accès personnels (CI, scripts), stockés hachés
+CREATE TABLE personal_access_tokens (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    token_prefix VARCHAR(16) NOT NULL,
+    scopes TEXT[] NOT NULL,
+    expires_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- array_to_string n'est pas IMMUTABLE, requis pour une colonne générée
+CREATE FUNCTION immutable_array_to_string(TEXT[]) RETURNS TEXT AS $$
+    SELECT array_to_string($1, ' ')
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Table des moteurs/engines
+CREATE TABLE engines (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    current_version VARCHAR(20),
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    -- Langues sources prises en charge par l'extension
+    supported_languages TEXT[] NOT NULL DEFAULT '{}',
+    -- Version du monde WIT tsukimi:extension implémenté
+    wit_version VARCHAR(20),
+    downloads BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    -- Recherche plein texte, le nom pèse plus que les tags et la description
+    search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(name, '')), 'A') ||
+        setweight(to_tsvector('simple', immutable_array_to_string(tags)), 'B') ||
+        setweight(to_tsvector('simple', coalesce(description, '')), 'C')
+    ) STORED,
+
+    -- Index pour les recherches fréquentes
+    CONSTRAINT engines_name_unique UNIQUE(name)
+);
+
+-- Membres des moteurs (propriétaires et mainteneurs)
+CREATE TABLE engine_members (
+    engine_id UUID NOT NULL REFERENCES engines(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role engine_role NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (engine_id, user_id)
+);
+
+-- Catalogue des visual novels
+CREATE TABLE visual_novels (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    title VARCHAR(255) NOT NULL,
+    original_title VARCHAR(255),
+    developer VARCHAR(255),
+    release_date DATE,
+    original_language VARCHAR(35) NOT NULL,
+    engine_id UUID REFERENCES engines(id) ON DELETE SET NULL,
+    cover_image_url TEXT,
+    vndb_id VARCHAR(20) UNIQUE,
+    -- Identifiants sur d'autres bases (steam, egs...)
+    external_ids JSONB NOT NULL DEFAULT '{}',
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    -- Recherche plein texte, config 'simple' car les titres sont multilingues
+    search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(title, '')), 'A') ||
+        setweight(to_tsvector('simple', coalesce(original_title, '')), 'A') ||
+        setweight(to_tsvector('simple', coalesce(developer, '')), 'B')
+    ) STORED
+);
+
+-- Table des versions d'engines
+CREATE TABLE engine_versions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    engine_id UUID NOT NULL REFERENCES engines(id) ON DELETE CASCADE,
+    version VARCHAR(20) NOT NULL,
+    description TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    -- Contraintes
+    CONSTRAINT engine_versions_unique UNIQUE(engine_id, version)
+);
+
+-- Projets de traduction
+CREATE TABLE projects (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    visual_novel_id UUID NOT NULL REFERENCES visual_novels(id) ON DELETE RESTRICT,
+    engine_version_id UUID REFERENCES engine_versions(id) ON DELETE RESTRICT,
+    source_language VARCHAR(35) NOT NULL,
+    target_languages TEXT[] NOT NULL,
+    visibility project_visibility NOT NULL DEFAULT 'public',
+    archived_at TIMESTAMPTZ,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Membres des projets et leur rôle
+CREATE TABLE project_members (
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role project_role NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (project_id, user_id)
+);
+
+-- Unités de texte source extraites des fichiers du jeu
+CREATE TABLE source_units (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    file TEXT NOT NULL,
+    key TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    source_text TEXT NOT NULL,
+    context TEXT,
+    -- Nombre de mots du texte source, pour les statistiques
+    words INTEGER NOT NULL DEFAULT 0,
+    -- L'unité n'apparaît plus dans la dernière extraction
+    obsolete BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT source_units_unique UNIQUE(project_id, file, key)
+);
+
+-- Traduction courante d'une unité par langue
+CREATE TABLE translations (
+    unit_id UUID NOT NULL REFERENCES source_units(id) ON DELETE CASCADE,
+    language VARCHAR(35) NOT NULL,
+    text TEXT NOT NULL,
+    -- Numéro de révision pour la concurrence optimiste
+    revision INTEGER NOT NULL,
+    -- Le texte source a changé depuis la traduction
+    fuzzy BOOLEAN NOT NULL DEFAULT false,
+    review_state review_state NOT NULL DEFAULT 'translated',
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (unit_id, language)
+);
+
+-- Historique des révisions de traduction
+CREATE TABLE translation_history (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    unit_id UUID NOT NULL REFERENCES source_units(id) ON DELETE CASCADE,
+    language VARCHAR(35) NOT NULL,
+    revision INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    author_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT translation_history_unique UNIQUE(unit_id, language, revision)
+);
+
+-- Suggestions des traducteurs, validées ou rejetées par un relecteur
+CREATE TABLE suggestions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    unit_id UUID NOT NULL REFERENCES source_units(id) ON DELETE CASCADE,
+    language VARCHAR(35) NOT NULL,
+    text TEXT NOT NULL,
+    base_revision INTEGER NOT NULL,
+    author_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    status suggestion_status NOT NULL DEFAULT 'pending',
+    reviewer_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    review_comment TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    reviewed_at TIMESTAMPTZ
+);
+
+-- Fils de discussion attachés aux unités
+CREATE TABLE comment_threads (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    unit_id UUID NOT NULL REFERENCES source_units(id) ON DELETE CASCADE,
+    -- NULL si le fil concerne le texte source
+    language VARCHAR(35),
+    flag issue_flag,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    resolved_at TIMESTAMPTZ
+);
+
+CREATE TABLE comments (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    thread_id UUID NOT NULL REFERENCES comment_threads(id) ON DELETE CASCADE,
+    author_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Utilisateurs mentionnés (@username) dans un commentaire
+CREATE TABLE comment_mentions (
+    comment_id UUID NOT NULL REFERENCES comments(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    PRIMARY KEY (comment_id, user_id)
+);
+
+-- Avancement agrégé par fichier et par langue, recalculé à chaque écriture
+CREATE TABLE project_file_stats (
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    file TEXT NOT NULL,
+    language VARCHAR(35) NOT NULL,
+    units BIGINT NOT NULL,
+    words BIGINT NOT NULL,
+    translated_units BIGINT NOT NULL,
+    translated_words BIGINT NOT NULL,
+    reviewed_units BIGINT NOT NULL,
+    reviewed_words BIGINT NOT NULL,
+    fuzzy_units BIGINT NOT NULL,
+    fuzzy_words BIGINT NOT NULL,
+
+    PRIMARY KEY (project_id, file, language)
+);
+
+-- Contributions cumulées par utilisateur et par langue
+CREATE TABLE project_contributor_stats (
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    language VARCHAR(35) NOT NULL,
+    translations BIGINT NOT NULL DEFAULT 0,
+    translated_words BIGINT NOT NULL DEFAULT 0,
+    approvals BIGINT NOT NULL DEFAULT 0,
+
+    PRIMARY KEY (project_id, user_id, language)
+);
+
+-- Activité quotidienne par langue
+CREATE TABLE project_activity (
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    language VARCHAR(35) NOT NULL,
+    translations BIGINT NOT NULL DEFAULT 0,
+    approvals BIGINT NOT NULL DEFAULT 0,
+
+    PRIMARY KEY (project_id, day, language)
+);
+
+-- Index pour optimiser les requêtes
+CREATE INDEX idx_engine_versions_engine_id ON engine_versions(engine_id);
+CREATE INDEX idx_engine_versions_active ON engine_versions(is_active) WHERE is_active = true;
+CREATE INDEX idx_engines_search ON engines USING GIN(search_vector);
+CREATE INDEX idx_engines_name_trgm ON engines USING GIN(name gin_trgm_ops);
+CREATE INDEX idx_engines_supported_languages ON engines USING GIN(supported_languages);
+CREATE INDEX idx_users_email ON users(email);
+CREATE INDEX idx_users_username ON users(username);
+CREATE INDEX idx_sessions_user_id ON sessions(user_id);
+CREATE INDEX idx_personal_access_tokens_user_id ON personal_access_tokens(user_id);
+CREATE INDEX idx_engine_members_user_id ON engine_members(user_id);
+CREATE INDEX idx_visual_novels_engine_id ON visual_novels(engine_id);
+CREATE INDEX idx_visual_novels_search ON visual_novels USING GIN(search_vector);
+CREATE INDEX idx_projects_visual_novel_id ON projects(visual_novel_id);
+CREATE INDEX idx_project_members_user_id ON project_members(user_id);
+CREATE INDEX idx_source_units_project_file ON source_units(project_id, file, position);
+CREATE INDEX idx_comment_threads_unit_id ON comment_threads(unit_id);
+CREATE INDEX idx_comments_thread_id ON comments(thread_id);
+CREATE INDEX idx_comment_mentions_user_id ON comment_mentions(user_id);
+CREATE INDEX idx_project_contributor_stats_user_id ON project_contributor_stats(user_id);
+CREATE INDEX idx_suggestions_pending ON suggestions(unit_id, language) WHERE status = 'pending';
+
+-- Fonction pour mettre à jour automatiquement updated_at
+CREATE OR REPLACE FUNCTION update_updated_at_column()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = NOW();
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+-- Triggers pour updated_at
+CREATE TRIGGER update_users_updated_at
+    BEFORE UPDATE ON users
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TRIGGER update_engines_updated_at
+    BEFORE UPDATE ON engines
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TRIGGER update_visual_novels_updated_at
+    BEFORE UPDATE ON visual_novels
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TRIGGER update_projects_updated_at
+    BEFORE UPDATE ON projects
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Fonction pour maintenir current_version dans engines
+CREATE OR REPLACE FUNCTION update_engine_current_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.is_active = true THEN
+        -- Désactiver les autres versions actives pour ce moteur
+        UPDATE engine_versions
+        SET is_active = false
+        WHERE engine_id = NEW.engine_id AND id != NEW.id AND is_active = true;
+
+        -- Mettre à jour la version courante dans engines
+        UPDATE engines
+        SET current_version = NEW.version, updated_at = NOW()
+        WHERE id = NEW.engine_id;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+-- Trigger pour maintenir la cohérence
+CREATE TRIGGER update_engine_current_version_trigger
+    AFTER INSERT OR UPDATE ON engine_versions
+    FOR EACH ROW
+    WHEN (NEW.is_active = true)
+    EXECUTE FUNCTION update_engine_current_version();
+
+-- Contrainte pour s'assurer qu'il n'y a qu'une version active par engine
+CREATE UNIQUE INDEX idx_engine_versions_single_active
+ON engine_versions(engine_id)
+WHERE is_active = true;
@@ -0,0 +1,13 @@
+-- Artefact WebAssembly publié pour chaque version
+ALTER TABLE engine_versions
+    ADD COLUMN artifact_key TEXT,
+    ADD COLUMN artifact_size BIGINT,
+    ADD COLUMN artifact_sha256 CHAR(64);
+
+-- Les téléchargements ne doivent pas compter comme une mise à jour du moteur
+DROP TRIGGER update_engines_updated_at ON engines;
+CREATE TRIGGER update_engines_updated_at
+    BEFORE UPDATE OF name, description, current_version, tags, supported_languages, wit_version
+    ON engines
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
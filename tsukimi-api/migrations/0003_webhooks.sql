@@ -0,0 +1,62 @@
+CREATE TYPE webhook_event AS ENUM (
+    'engine_version_published',
+    'engine_version_yanked',
+    'project_milestone_reached',
+    'suggestion_approved'
+);
+CREATE TYPE delivery_status AS ENUM ('pending', 'succeeded', 'failed');
+
+-- Webhooks d'un moteur ou d'un projet. Le secret est gardé en clair, il sert
+-- à signer chaque envoi.
+CREATE TABLE webhooks (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    engine_id UUID REFERENCES engines(id) ON DELETE CASCADE,
+    project_id UUID REFERENCES projects(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events webhook_event[] NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT true,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK ((engine_id IS NULL) <> (project_id IS NULL))
+);
+
+-- File d'envoi et historique, une ligne par envoi (les renvois compris)
+CREATE TABLE webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    webhook_id UUID NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    event webhook_event NOT NULL,
+    payload JSONB NOT NULL,
+    status delivery_status NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    -- NULL une fois l'envoi terminé
+    next_attempt_at TIMESTAMPTZ DEFAULT NOW(),
+    last_attempt_at TIMESTAMPTZ,
+    response_status INTEGER,
+    response_body TEXT,
+    error TEXT,
+    redelivery_of UUID REFERENCES webhook_deliveries(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Paliers d'avancement déjà annoncés, pour ne les annoncer qu'une fois
+CREATE TABLE project_milestones (
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    language VARCHAR(35) NOT NULL,
+    milestone SMALLINT NOT NULL,
+    reached_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (project_id, language, milestone)
+);
+
+CREATE INDEX idx_webhooks_engine_id ON webhooks(engine_id);
+CREATE INDEX idx_webhooks_project_id ON webhooks(project_id);
+CREATE INDEX idx_webhook_deliveries_webhook_id ON webhook_deliveries(webhook_id, created_at DESC);
+CREATE INDEX idx_webhook_deliveries_due ON webhook_deliveries(next_attempt_at) WHERE status = 'pending';
+
+CREATE TRIGGER update_webhooks_updated_at
+    BEFORE UPDATE ON webhooks
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,38 @@
+-- Journal des actions qui modifient des données, en ajout seul. Pas de clé
+-- étrangère sur l'acteur ni le jeton : le journal survit à leur suppression.
+CREATE TABLE audit_log (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    actor_id UUID,
+    token_id UUID,
+    -- Méthode et modèle de route, ex. "PATCH /projects/{id}"
+    action TEXT NOT NULL,
+    target_type TEXT,
+    target_id TEXT,
+    -- Seuls les champs modifiés sont gardés
+    before JSONB,
+    after JSONB,
+    ip TEXT,
+    status SMALLINT NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_log_occurred_at ON audit_log(occurred_at DESC);
+CREATE INDEX idx_audit_log_actor_id ON audit_log(actor_id, occurred_at DESC);
+CREATE INDEX idx_audit_log_target ON audit_log(target_type, target_id, occurred_at DESC);
+
+CREATE FUNCTION reject_audit_log_change()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_append_only
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW
+    EXECUTE FUNCTION reject_audit_log_change();
+
+CREATE TRIGGER audit_log_no_truncate
+    BEFORE TRUNCATE ON audit_log
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION reject_audit_log_change();
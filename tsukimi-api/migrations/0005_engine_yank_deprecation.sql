@@ -0,0 +1,69 @@
+-- Une version retirée reste téléchargeable (fichiers de verrouillage existants)
+-- mais n'est plus proposée comme version courante
+ALTER TABLE engine_versions
+    ADD COLUMN yanked_at TIMESTAMPTZ,
+    ADD COLUMN yanked_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN yank_reason TEXT,
+    ADD CONSTRAINT engine_versions_yanked_inactive CHECK (yanked_at IS NULL OR NOT is_active);
+
+-- Moteur abandonné, avec un message et éventuellement un remplaçant
+ALTER TABLE engines
+    ADD COLUMN deprecated_at TIMESTAMPTZ,
+    ADD COLUMN deprecation_message TEXT,
+    ADD COLUMN replacement_engine_id UUID REFERENCES engines(id) ON DELETE SET NULL,
+    ADD CONSTRAINT engines_not_own_replacement CHECK (replacement_engine_id <> id);
+
+-- Retirer une version la désactive
+CREATE FUNCTION deactivate_yanked_engine_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.yanked_at IS NOT NULL THEN
+        NEW.is_active = false;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER deactivate_yanked_engine_version_trigger
+    BEFORE INSERT OR UPDATE ON engine_versions
+    FOR EACH ROW
+    EXECUTE FUNCTION deactivate_yanked_engine_version();
+
+-- Sans version active, la dernière version publiée non retirée devient la
+-- version courante, et sans elle le moteur n'a plus de version courante
+CREATE FUNCTION fallback_engine_current_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM engine_versions WHERE engine_id = NEW.engine_id AND is_active
+    ) THEN
+        UPDATE engine_versions
+        SET is_active = true
+        WHERE id = (
+            SELECT id
+            FROM engine_versions
+            WHERE engine_id = NEW.engine_id AND yanked_at IS NULL
+            ORDER BY created_at DESC
+            LIMIT 1
+        );
+        IF NOT FOUND THEN
+            UPDATE engines SET current_version = NULL WHERE id = NEW.engine_id;
+        END IF;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER fallback_engine_current_version_trigger
+    AFTER UPDATE OF yanked_at ON engine_versions
+    FOR EACH ROW
+    WHEN (OLD.yanked_at IS DISTINCT FROM NEW.yanked_at)
+    EXECUTE FUNCTION fallback_engine_current_version();
+
+DROP TRIGGER update_engines_updated_at ON engines;
+CREATE TRIGGER update_engines_updated_at
+    BEFORE UPDATE OF name, description, current_version, tags, supported_languages, wit_version,
+        deprecated_at, deprecation_message, replacement_engine_id
+    ON engines
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
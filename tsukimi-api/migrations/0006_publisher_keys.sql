@@ -0,0 +1,23 @@
+-- Clés publiques Ed25519 avec lesquelles les éditeurs signent leurs artefacts.
+-- Une clé est révoquée, jamais supprimée : les versions signées la référencent.
+CREATE TABLE publisher_keys (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    -- Clé brute de 32 octets, en base64
+    public_key TEXT NOT NULL,
+    -- SHA-256 de la clé brute, en hexadécimal
+    fingerprint CHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_publisher_keys_user_id ON publisher_keys(user_id);
+
+-- Signature détachée de l'artefact entier. Les versions publiées avant la
+-- signature n'en ont pas et sont refusées par le CLI.
+ALTER TABLE engine_versions
+    ADD COLUMN signature TEXT,
+    ADD COLUMN publisher_key_id UUID REFERENCES publisher_keys(id) ON DELETE RESTRICT,
+    ADD CONSTRAINT engine_versions_signed_with_key
+        CHECK ((signature IS NULL) = (publisher_key_id IS NULL));
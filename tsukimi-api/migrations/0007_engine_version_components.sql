@@ -0,0 +1,27 @@
+-- Ce que la validation du composant a détecté à la publication
+ALTER TABLE engine_versions
+    ADD COLUMN wit_version VARCHAR(20),
+    -- Fonctions exportées, ex. "iadd.decode"
+    ADD COLUMN capabilities TEXT[] NOT NULL DEFAULT '{}';
+
+-- Le moteur annonce le monde WIT de sa version courante
+CREATE OR REPLACE FUNCTION update_engine_current_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.is_active = true THEN
+        -- Désactiver les autres versions actives pour ce moteur
+        UPDATE engine_versions
+        SET is_active = false
+        WHERE engine_id = NEW.engine_id AND id != NEW.id AND is_active = true;
+
+        -- Mettre à jour la version courante dans engines
+        UPDATE engines
+        SET current_version = NEW.version,
+            wit_version = COALESCE(NEW.wit_version, wit_version),
+            updated_at = NOW()
+        WHERE id = NEW.engine_id;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
@@ -0,0 +1,59 @@
+CREATE TYPE job_status AS ENUM ('queued', 'running', 'succeeded', 'dead');
+
+-- File des tâches de fond. Une tâche en échec est réessayée avec un délai
+-- croissant, puis gardée en 'dead' pour être examinée ou relancée.
+CREATE TABLE jobs (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    kind TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    status job_status NOT NULL DEFAULT 'queued',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL,
+    -- Pas avant cette date, sert aussi aux tâches planifiées
+    run_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Tenue par un worker jusqu'à cette date, reprise ensuite s'il a disparu
+    locked_by TEXT,
+    locked_until TIMESTAMPTZ,
+    last_error TEXT,
+    -- Au plus une tâche en attente par clé
+    unique_key TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_jobs_due ON jobs(run_at) WHERE status = 'queued';
+CREATE INDEX idx_jobs_leased ON jobs(locked_until) WHERE status = 'running';
+CREATE INDEX idx_jobs_status ON jobs(status, created_at DESC);
+CREATE UNIQUE INDEX idx_jobs_unique_queued ON jobs(unique_key) WHERE status = 'queued';
+
+-- Les artefacts sont validés en tâche de fond : une version n'est proposée
+-- qu'une fois validée. Les versions existantes l'ont été à la publication.
+CREATE TYPE validation_status AS ENUM ('pending', 'valid', 'invalid');
+
+ALTER TABLE engine_versions
+    ADD COLUMN validation_status validation_status NOT NULL DEFAULT 'valid',
+    ADD COLUMN validation_report JSONB,
+    ADD CONSTRAINT engine_versions_active_valid CHECK (validation_status = 'valid' OR NOT is_active);
+
+CREATE OR REPLACE FUNCTION fallback_engine_current_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM engine_versions WHERE engine_id = NEW.engine_id AND is_active
+    ) THEN
+        UPDATE engine_versions
+        SET is_active = true
+        WHERE id = (
+            SELECT id
+            FROM engine_versions
+            WHERE engine_id = NEW.engine_id AND yanked_at IS NULL AND validation_status = 'valid'
+            ORDER BY created_at DESC
+            LIMIT 1
+        );
+        IF NOT FOUND THEN
+            UPDATE engines SET current_version = NULL WHERE id = NEW.engine_id;
+        END IF;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,10 @@
+-- Autorisations OAuth démarrées par l'API. Le vérificateur PKCE ne quitte
+-- jamais le serveur : le client ne renvoie que `state` avec le code.
+CREATE TABLE oauth_states (
+    state TEXT PRIMARY KEY,
+    pkce_verifier TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_oauth_states_expires_at ON oauth_states(expires_at);
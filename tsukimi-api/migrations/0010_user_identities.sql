@@ -0,0 +1,31 @@
+-- Comptes des fournisseurs d'identité (GitHub, GitLab, OpenID Connect) liés
+-- aux utilisateurs. Un utilisateur en lie au plus un par fournisseur, un compte
+-- n'appartient qu'à un utilisateur.
+CREATE TABLE user_identities (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Identifiant du fournisseur dans la configuration de l'API
+    provider VARCHAR(50) NOT NULL,
+    -- Identifiant stable du compte chez le fournisseur (`sub` en OpenID Connect)
+    subject VARCHAR(255) NOT NULL,
+    username VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_login_at TIMESTAMPTZ,
+    PRIMARY KEY (provider, subject),
+    UNIQUE (user_id, provider)
+);
+
+INSERT INTO user_identities (user_id, provider, subject, username, email, created_at)
+SELECT id, 'github', github_id::TEXT, username, email, created_at
+FROM users
+WHERE github_id IS NOT NULL;
+
+ALTER TABLE users DROP COLUMN github_id;
+
+-- Une autorisation n'est échangeable qu'auprès du fournisseur qui l'a émise.
+-- Le nonce lie le jeton d'identité OpenID Connect à l'autorisation.
+ALTER TABLE oauth_states
+    ADD COLUMN provider VARCHAR(50) NOT NULL DEFAULT 'github',
+    ADD COLUMN nonce TEXT;
+
+ALTER TABLE oauth_states ALTER COLUMN provider DROP DEFAULT;
@@ -0,0 +1,25 @@
+-- La version de repli est la plus grande version valide non retirée au sens
+-- de semver, et non la dernière publiée : publier un correctif d'une ancienne
+-- branche ne doit pas en faire la version courante.
+CREATE OR REPLACE FUNCTION fallback_engine_current_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM engine_versions WHERE engine_id = NEW.engine_id AND is_active
+    ) THEN
+        UPDATE engine_versions
+        SET is_active = true
+        WHERE id = (
+            SELECT id
+            FROM engine_versions
+            WHERE engine_id = NEW.engine_id AND yanked_at IS NULL AND validation_status = 'valid'
+            ORDER BY string_to_array(version, '.')::int[] DESC
+            LIMIT 1
+        );
+        IF NOT FOUND THEN
+            UPDATE engines SET current_version = NULL WHERE id = NEW.engine_id;
+        END IF;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
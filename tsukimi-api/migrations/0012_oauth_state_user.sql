@@ -0,0 +1,5 @@
+-- Une autorisation démarrée pour lier un compte appartient à l'utilisateur
+-- connecté qui l'a demandée : lui seul peut la terminer, et elle n'ouvre pas
+-- de session. Sans utilisateur, c'est une connexion.
+ALTER TABLE oauth_states
+    ADD COLUMN user_id UUID REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,33 @@
+-- Données d'exemple pour le développement, rejouables sans doublons
+INSERT INTO users (username, email, role) VALUES
+('admin', 'admin@example.com', 'admin'),
+('developer', 'dev@example.com', 'user')
+ON CONFLICT (username) DO NOTHING;
+
+INSERT INTO engines (name, description, tags) VALUES
+('PostgreSQL', 'Système de gestion de base de données relationnelle', '{sql,database}'),
+('Redis', 'Base de données en mémoire pour le cache et les messages', '{cache,database}')
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO engine_members (engine_id, user_id, role)
+SELECT e.id, u.id, 'owner'
+FROM engines e, users u WHERE u.username = 'developer'
+ON CONFLICT (engine_id, user_id) DO NOTHING;
+
+INSERT INTO engine_versions (engine_id, version, description, is_active)
+SELECT
+    e.id,
+    '15.4',
+    'Version stable de PostgreSQL avec améliorations de performance',
+    true
+FROM engines e WHERE e.name = 'PostgreSQL'
+ON CONFLICT (engine_id, version) DO NOTHING;
+
+INSERT INTO engine_versions (engine_id, version, description, is_active)
+SELECT
+    e.id,
+    '7.2',
+    'Version récente de Redis avec nouvelles fonctionnalités',
+    true
+FROM engines e WHERE e.name = 'Redis'
+ON CONFLICT (engine_id, version) DO NOTHING;
@@ -1,33 +1,70 @@
-use axum::http::Method;
-use std::net::{Ipv4Addr, SocketAddr};
+use clap::{Parser, Subcommand};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 use tokio::net::TcpListener;
-use tower_http::{
-    cors::{Any, CorsLayer},
-    trace::TraceLayer,
-};
 use tracing::{error, info};
+use tracing_subscriber::{filter::LevelFilter, prelude::*};
 
 pub mod config;
+pub mod error;
+pub mod middleware;
+pub mod policy;
 pub mod routes;
 pub mod services;
 
+#[derive(Parser)]
+#[command(version, about = "Tsukimi registry API")]
+struct Args {
+    /// JSON config file, defaults to `tsukimi.json` when it exists
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Apply the pending database migrations and exit
+    #[arg(long)]
+    migrate_only: bool,
+    /// Load the demo data, development environment only
+    #[arg(long)]
+    seed: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run background jobs and webhook deliveries without serving the API
+    Worker,
+}
+
 #[derive(Clone)]
 pub struct AppState {
+    pub components: services::components::ComponentService,
     pub database: services::database::DatabaseService,
+    pub engines: Arc<dyn services::repositories::EngineRepository>,
+    pub engine_versions: Arc<dyn services::repositories::EngineVersionRepository>,
+    pub users: Arc<dyn services::repositories::UserRepository>,
+    pub projects: Arc<dyn services::repositories::ProjectRepository>,
+    pub health: services::health::HealthService,
+    pub jobs: services::jobs::JobService,
+    pub metrics: services::metrics::MetricsService,
     pub oauth: services::oauth::OAuthService,
+    pub rate_limit: services::rate_limit::RateLimitService,
+    pub session: services::session::SessionService,
+    pub storage: services::storage::StorageService,
+    pub webhooks: services::webhooks::WebhookService,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::DEBUG)
+    let args = Args::parse();
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(LevelFilter::DEBUG))
+        .with(services::metrics::QueryTimingLayer)
         .init();
 
     let version = env!("CARGO_PKG_VERSION");
     let app_name = env!("CARGO_PKG_NAME");
 
     // Load configuration
-    let config = config::get_configuration().map_err(|e| {
+    let config = config::get_configuration(args.config.as_deref()).map_err(|e| {
         error!("Failed to load configuration: {}", e);
         e
     })?;
@@ -37,40 +74,80 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(debug_assertions)]
     info!("/!\\ Debug mode is enabled");
 
-    let (database_service, oauth_service) = services::get_services(&config).await.map_err(|e| {
+    let app_state = services::get_services(&config).await.map_err(|e| {
         error!("Failed to initialize services: {}", e);
         e
     })?;
 
-    let app_state = AppState {
-        database: database_service,
-        oauth: oauth_service,
-    };
-
-    let address = SocketAddr::from((Ipv4Addr::UNSPECIFIED, config.port()));
-    let listener = TcpListener::bind(address).await.map_err(|e| {
-        error!("Failed to bind to address {}: {}", address, e);
+    app_state.database.migrate().await.map_err(|e| {
+        error!("{}", e);
         e
     })?;
+    if args.migrate_only {
+        return Ok(());
+    }
 
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods([Method::GET, Method::POST]);
+    if args.seed {
+        if config.env() != config::Environment::Development {
+            error!("Seed data can only be loaded in development");
+            return Err("Seed data can only be loaded in development".into());
+        }
+        app_state.database.seed_dev_data().await.map_err(|e| {
+            error!("Failed to load seed data: {}", e);
+            e
+        })?;
+    }
 
-    let router = routes::get_router()
-        .with_state(app_state)
-        .layer(TraceLayer::new_for_http())
-        .layer(cors);
+    if let Some(Command::Worker) = args.command {
+        info!("Running as a worker");
+        app_state
+            .webhooks
+            .clone()
+            .spawn_dispatcher(app_state.database.clone(), app_state.health.clone());
+        let workers = app_state.jobs.clone().spawn_workers(app_state.clone());
+        shutdown_signal().await;
+        app_state.health.start_shutdown();
+        // Lets the running jobs finish, leases cover the ones cut short
+        workers.await?;
+        return Ok(());
+    }
+    if app_state.jobs.is_embedded() {
+        app_state
+            .webhooks
+            .clone()
+            .spawn_dispatcher(app_state.database.clone(), app_state.health.clone());
+        app_state.jobs.clone().spawn_workers(app_state.clone());
+    }
 
-    info!("Starting Tsukimi CDN on port: {}", config.port());
+    let address = SocketAddr::from((config.server().host(), config.server().port()));
+    let listener = TcpListener::bind(address).await.map_err(|e| {
+        error!("Failed to bind to address {}: {}", address, e);
+        e
+    })?;
 
-    axum::serve(listener, router)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .map_err(|e| {
-            error!("Failed to start server: {}", e);
-            e
-        })?;
+    let health = app_state.health.clone();
+    let shutdown_delay = config.server().shutdown_delay();
+    let router = routes::app(app_state, config.server().cors_origins());
+
+    info!("Starting Tsukimi CDN on {}", address);
+
+    axum::serve(
+        listener,
+        router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move {
+        shutdown_signal().await;
+        health.start_shutdown();
+        if !shutdown_delay.is_zero() {
+            info!("Draining for {:?} before shutting down", shutdown_delay);
+            tokio::time::sleep(shutdown_delay).await;
+        }
+    })
+    .await
+    .map_err(|e| {
+        error!("Failed to start server: {}", e);
+        e
+    })?;
 
     Ok(())
 }
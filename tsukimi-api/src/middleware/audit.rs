@@ -0,0 +1,165 @@
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::{MatchedPath, RawPathParams, Request, State, rejection::RawPathParamsRejection},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    middleware::{
+        auth::{AuthUser, Credential},
+        rate_limit::client_ip,
+    },
+    services::database::NewAuditEntry,
+};
+
+/// Filled by the `AuthUser` extractor, so the log knows who made the request
+/// without authenticating it a second time.
+#[derive(Clone, Default)]
+pub struct AuditActor(Arc<Mutex<Option<AuthUser>>>);
+
+impl AuditActor {
+    pub fn set(&self, auth: &AuthUser) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(auth.clone());
+    }
+
+    /// User and personal access token of the caller
+    fn ids(&self) -> (Option<Uuid>, Option<Uuid>) {
+        match &*self.0.lock().unwrap_or_else(|e| e.into_inner()) {
+            Some(auth) => match &auth.credential {
+                Credential::PersonalAccessToken { token_id, .. } => {
+                    (Some(auth.user_id), Some(*token_id))
+                }
+                Credential::Session { .. } => (Some(auth.user_id), None),
+            },
+            None => (None, None),
+        }
+    }
+}
+
+/// What a handler knows about the change it made, returned as a response
+/// extension: `(Extension(change), Json(project))`. Handlers that don't
+/// return one are logged against the first segment and `id` of their route.
+#[derive(Debug, Clone)]
+pub struct AuditChange {
+    target_type: &'static str,
+    target_id: String,
+    before: Option<Value>,
+    after: Option<Value>,
+}
+
+impl AuditChange {
+    pub fn new(target_type: &'static str, target_id: impl ToString) -> Self {
+        AuditChange {
+            target_type,
+            target_id: target_id.to_string(),
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn before(mut self, value: &impl Serialize) -> Self {
+        self.before = serde_json::to_value(value).ok();
+        self
+    }
+
+    pub fn after(mut self, value: &impl Serialize) -> Self {
+        self.after = serde_json::to_value(value).ok();
+        self
+    }
+
+    /// Drops the fields the change left untouched.
+    fn into_diff(self) -> (Option<Value>, Option<Value>) {
+        match (self.before, self.after) {
+            (Some(Value::Object(mut before)), Some(Value::Object(mut after))) => {
+                let unchanged: Vec<String> = before
+                    .iter()
+                    .filter(|(key, value)| after.get(key.as_str()) == Some(value))
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                for key in &unchanged {
+                    before.remove(key);
+                    after.remove(key);
+                }
+                (Some(Value::Object(before)), Some(Value::Object(after)))
+            }
+            (before, after) => (before, after),
+        }
+    }
+}
+
+/// Records every successful mutating request in the append-only audit log.
+/// Failing to write the entry is logged but does not fail the request, the
+/// change is already committed.
+pub async fn record_changes(
+    State(app_state): State<AppState>,
+    matched_path: MatchedPath,
+    params: Result<RawPathParams, RawPathParamsRejection>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    ) {
+        return next.run(request).await;
+    }
+
+    let actor = AuditActor::default();
+    request.extensions_mut().insert(actor.clone());
+    let action = format!("{} {}", request.method(), matched_path.as_str());
+    let ip = client_ip(&app_state, &request);
+
+    let mut response = next.run(request).await;
+    if !response.status().is_success() {
+        return response;
+    }
+
+    let (target_type, target_id, before, after) =
+        match response.extensions_mut().remove::<AuditChange>() {
+            Some(change) => {
+                let target_type = change.target_type.to_string();
+                let target_id = change.target_id.clone();
+                let (before, after) = change.into_diff();
+                (Some(target_type), Some(target_id), before, after)
+            }
+            None => {
+                let target_type = matched_path
+                    .as_str()
+                    .split('/')
+                    .find(|segment| !segment.is_empty())
+                    .map(str::to_string);
+                let target_id = params.ok().and_then(|params| {
+                    params
+                        .iter()
+                        .find(|(name, _)| *name == "id")
+                        .map(|(_, value)| value.to_string())
+                });
+                (target_type, target_id, None, None)
+            }
+        };
+    let (actor_id, token_id) = actor.ids();
+
+    let entry = NewAuditEntry {
+        actor_id,
+        token_id,
+        action,
+        target_type,
+        target_id,
+        before,
+        after,
+        ip,
+        status: response.status().as_u16() as i16,
+    };
+    if let Err(e) = app_state.database.record_audit_entry(&entry).await {
+        error!("Failed to record audit entry for {}: {}", entry.action, e);
+    }
+    response
+}
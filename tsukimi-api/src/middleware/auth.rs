@@ -0,0 +1,136 @@
+use axum::{
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    http::{HeaderMap, header, request::Parts},
+};
+use tsukimi_core::auth::TokenScope;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::ApiError,
+    middleware::audit::AuditActor,
+    services::session::{PERSONAL_ACCESS_TOKEN_PREFIX, SessionService},
+};
+
+/// How the caller proved its identity.
+#[derive(Debug, Clone)]
+pub enum Credential {
+    /// Interactive login, holds every scope.
+    Session { session_id: Uuid },
+    /// Personal access token, restricted to its scopes.
+    PersonalAccessToken {
+        token_id: Uuid,
+        scopes: Vec<TokenScope>,
+    },
+}
+
+/// Authenticated caller, extracted from the `Authorization: Bearer` header.
+///
+/// Adding it to a handler's arguments is enough to make the route require
+/// either a valid, non-revoked session or a live personal access token.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user_id: Uuid,
+    pub credential: Credential,
+}
+
+impl AuthUser {
+    pub fn require_scope(&self, scope: TokenScope) -> Result<(), ApiError> {
+        match &self.credential {
+            Credential::Session { .. } => Ok(()),
+            Credential::PersonalAccessToken { scopes, .. } if scopes.contains(&scope) => Ok(()),
+            Credential::PersonalAccessToken { .. } => Err(ApiError::Forbidden(format!(
+                "Token is missing the `{}` scope",
+                scope
+            ))),
+        }
+    }
+
+    /// Some actions, such as managing tokens, are only allowed from an
+    /// interactive session.
+    pub fn require_session(&self) -> Result<Uuid, ApiError> {
+        match &self.credential {
+            Credential::Session { session_id } => Ok(*session_id),
+            Credential::PersonalAccessToken { .. } => Err(ApiError::Forbidden(
+                "This action requires an interactive session".into(),
+            )),
+        }
+    }
+}
+
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        app_state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        // The rate limiter may have looked the token up already
+        let auth = match parts.extensions.get::<AuthUser>() {
+            Some(auth) => auth.clone(),
+            None => authenticate(&parts.headers, app_state).await?,
+        };
+        if let Some(actor) = parts.extensions.get::<AuditActor>() {
+            actor.set(&auth);
+        }
+        Ok(auth)
+    }
+}
+
+pub(crate) async fn authenticate(
+    headers: &HeaderMap,
+    app_state: &AppState,
+) -> Result<AuthUser, ApiError> {
+    let token = bearer_token(headers)?;
+
+    if token.starts_with(PERSONAL_ACCESS_TOKEN_PREFIX) {
+        let (token_id, user_id, scopes) = app_state
+            .users
+            .use_personal_access_token(&SessionService::hash_secret(token))
+            .await?
+            .ok_or_else(|| ApiError::Unauthorized("Invalid, expired or revoked token".into()))?;
+        return Ok(AuthUser {
+            user_id,
+            credential: Credential::PersonalAccessToken { token_id, scopes },
+        });
+    }
+
+    let claims = app_state.session.verify_access_token(token)?;
+
+    if !app_state.users.is_session_active(claims.sid).await? {
+        return Err(ApiError::Unauthorized("Session has been revoked".into()));
+    }
+
+    Ok(AuthUser {
+        user_id: claims.sub,
+        credential: Credential::Session {
+            session_id: claims.sid,
+        },
+    })
+}
+
+/// Routes readable anonymously take an `Option<AuthUser>`: no `Authorization`
+/// header yields `None`, an invalid one is still rejected.
+impl OptionalFromRequestParts<AppState> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        app_state: &AppState,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        if !parts.headers.contains_key(header::AUTHORIZATION) {
+            return Ok(None);
+        }
+        <AuthUser as FromRequestParts<AppState>>::from_request_parts(parts, app_state)
+            .await
+            .map(Some)
+    }
+}
+
+pub(crate) fn bearer_token(headers: &HeaderMap) -> Result<&str, ApiError> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| ApiError::Unauthorized("Missing bearer token".into()))
+}
@@ -0,0 +1,26 @@
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::services::metrics;
+
+/// Counts and times every request, labelled by route template so ids don't
+/// blow up the number of series.
+pub async fn track_requests(matched_path: MatchedPath, request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let start = Instant::now();
+
+    let response = next.run(request).await;
+
+    metrics::record_request(
+        method.as_str(),
+        matched_path.as_str(),
+        response.status().as_u16(),
+        start.elapsed(),
+    );
+    response
+}
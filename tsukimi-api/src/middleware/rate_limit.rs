@@ -0,0 +1,185 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{HeaderMap, HeaderValue, Method, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    AppState,
+    error::ApiError,
+    middleware::auth::{authenticate, bearer_token},
+    services::{
+        rate_limit::{RateLimitClass, RateLimitDecision},
+        session::PERSONAL_ACCESS_TOKEN_PREFIX,
+    },
+};
+
+/// Applies the budget of the route, if any, and reports it in the
+/// `RateLimit-*` headers.
+pub async fn limit_requests(
+    State(app_state): State<AppState>,
+    matched_path: MatchedPath,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(class) = classify(request.method(), matched_path.as_str()) else {
+        return next.run(request).await;
+    };
+    if !app_state.rate_limit.is_enabled() {
+        return next.run(request).await;
+    }
+
+    let client = client_key(&app_state, &mut request).await;
+    let decision = app_state.rate_limit.check(class, &client);
+
+    let mut response = match decision.allowed {
+        true => next.run(request).await,
+        false => ApiError::TooManyRequests(format!(
+            "Rate limit exceeded, retry in {} seconds",
+            ceil_secs(&decision.retry_after)
+        ))
+        .into_response(),
+    };
+    set_headers(response.headers_mut(), &decision);
+    response
+}
+
+fn classify(method: &Method, path: &str) -> Option<RateLimitClass> {
+    match (method, path) {
+        (&Method::GET, "/engines" | "/visual-novels") => Some(RateLimitClass::Search),
+        (&Method::GET, "/engines/{id}/versions/{version}/download") => {
+            Some(RateLimitClass::Download)
+        }
+        (&Method::POST, "/auth/session" | "/auth/device-session") => Some(RateLimitClass::OAuth),
+        (_, path) if path.starts_with("/oauth/") => Some(RateLimitClass::OAuth),
+        (&Method::POST | &Method::PUT, path) if path.starts_with("/engines/{id}/versions") => {
+            Some(RateLimitClass::Publish)
+        }
+        _ => None,
+    }
+}
+
+/// Authenticated callers get their own budget wherever they connect from.
+/// Sessions are checked from the access token alone, personal access tokens
+/// are looked up and the handler reuses the result. Anything else, made up
+/// tokens included, counts against the address.
+async fn client_key(app_state: &AppState, request: &mut Request) -> String {
+    match bearer_token(request.headers()) {
+        Ok(token) if token.starts_with(PERSONAL_ACCESS_TOKEN_PREFIX) => {
+            if let Ok(auth) = authenticate(request.headers(), app_state).await {
+                let key = format!("user:{}", auth.user_id);
+                request.extensions_mut().insert(auth);
+                return key;
+            }
+        }
+        Ok(token) => {
+            if let Ok(claims) = app_state.session.verify_access_token(token) {
+                return format!("user:{}", claims.sub);
+            }
+        }
+        Err(_) => {}
+    }
+
+    format!("ip:{}", client_ip(app_state, request).unwrap_or_default())
+}
+
+/// Address of the client, taken from `X-Forwarded-For` when the proxies in
+/// front of the API are trusted.
+pub(crate) fn client_ip(app_state: &AppState, request: &Request) -> Option<String> {
+    let forwarded = app_state
+        .rate_limit
+        .trust_forwarded_for()
+        .then(|| {
+            request
+                .headers()
+                .get("x-forwarded-for")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| forwarded_ip(value, app_state.rate_limit.trusted_proxies()))
+        })
+        .flatten();
+    forwarded
+        .or_else(|| {
+            request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(address)| address.ip())
+        })
+        .map(|ip| ip.to_string())
+}
+
+/// Proxies append to `X-Forwarded-For`, only the entries they added can be
+/// trusted, the leftmost ones come from the client. `None` when the chain is
+/// shorter than the proxies or the entry is not an address.
+fn forwarded_ip(forwarded_for: &str, trusted_proxies: usize) -> Option<IpAddr> {
+    forwarded_for
+        .rsplit(',')
+        .nth(trusted_proxies.checked_sub(1)?)
+        .and_then(|ip| ip.trim().parse().ok())
+}
+
+fn set_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    let mut set = |name: &'static str, value: u64| {
+        headers.insert(name, HeaderValue::from(value));
+    };
+    set("ratelimit-limit", decision.limit as u64);
+    set("ratelimit-remaining", decision.remaining as u64);
+    set("ratelimit-reset", ceil_secs(&decision.reset));
+    if !decision.allowed {
+        headers.insert(
+            header::RETRY_AFTER,
+            HeaderValue::from(ceil_secs(&decision.retry_after)),
+        );
+    }
+}
+
+fn ceil_secs(duration: &std::time::Duration) -> u64 {
+    duration.as_secs_f64().ceil() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forwarded_ip_skips_the_entries_of_the_client() {
+        let ip = |value: &str| value.parse::<IpAddr>().ok();
+        assert_eq!(forwarded_ip("203.0.113.7", 1), ip("203.0.113.7"));
+        // The client may send its own header, the proxy appends to it
+        assert_eq!(forwarded_ip("10.0.0.1, 203.0.113.7", 1), ip("203.0.113.7"));
+        assert_eq!(
+            forwarded_ip("10.0.0.1,203.0.113.7, 192.0.2.1", 2),
+            ip("203.0.113.7")
+        );
+        assert_eq!(forwarded_ip(" 2001:db8::1 ", 1), ip("2001:db8::1"));
+    }
+
+    #[test]
+    fn forwarded_ip_rejects_short_chains_and_garbage() {
+        assert_eq!(forwarded_ip("203.0.113.7", 2), None);
+        assert_eq!(forwarded_ip("", 1), None);
+        assert_eq!(forwarded_ip("203.0.113.7, ", 1), None);
+        assert_eq!(forwarded_ip("203.0.113.7, unknown", 1), None);
+        assert_eq!(forwarded_ip("203.0.113.7", 0), None);
+    }
+
+    #[test]
+    fn publishing_routes_share_a_budget() {
+        let publish = Some(RateLimitClass::Publish);
+        assert_eq!(
+            classify(&Method::PUT, "/engines/{id}/versions/{version}"),
+            publish
+        );
+        assert_eq!(
+            classify(&Method::POST, "/engines/{id}/versions/{version}/yank"),
+            publish
+        );
+        assert_eq!(classify(&Method::POST, "/engines"), None);
+        assert_eq!(
+            classify(&Method::GET, "/engines"),
+            Some(RateLimitClass::Search)
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use tsukimi_core::{
+    auth::TokenScope,
+    models::{EngineRole, GlobalRole, ProjectRole, ProjectVisibility},
+};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{ApiError, ApiResult},
+    middleware::auth::AuthUser,
+    services::database::ProjectAccess,
+};
+
+/// Actions on an engine guarded by the engine roles.
+#[derive(Debug, Clone, Copy)]
+pub enum EngineAction {
+    Publish,
+    Update,
+    Yank,
+    Deprecate,
+    ManageMembers,
+    ManageWebhooks,
+}
+
+impl EngineAction {
+    fn is_allowed(&self, role: EngineRole) -> bool {
+        match self {
+            EngineAction::Publish | EngineAction::Update | EngineAction::Yank => true,
+            EngineAction::Deprecate
+            | EngineAction::ManageMembers
+            | EngineAction::ManageWebhooks => role == EngineRole::Owner,
+        }
+    }
+}
+
+/// Actions on a translation project guarded by the project roles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectAction {
+    View,
+    Translate,
+    Review,
+    Update,
+    ManageMembers,
+    ManageWebhooks,
+}
+
+impl ProjectAction {
+    fn is_allowed(&self, role: ProjectRole) -> bool {
+        match self {
+            ProjectAction::View => true,
+            ProjectAction::Translate => matches!(
+                role,
+                ProjectRole::Owner | ProjectRole::Translator | ProjectRole::Reviewer
+            ),
+            ProjectAction::Review => matches!(role, ProjectRole::Owner | ProjectRole::Reviewer),
+            ProjectAction::Update
+            | ProjectAction::ManageMembers
+            | ProjectAction::ManageWebhooks => role == ProjectRole::Owner,
+        }
+    }
+
+    /// Archived projects are read-only, only their settings can still change
+    /// (e.g. to unarchive them). Webhooks stay manageable so they can be
+    /// switched off.
+    fn is_allowed_when_archived(&self) -> bool {
+        matches!(
+            self,
+            ProjectAction::View | ProjectAction::Update | ProjectAction::ManageWebhooks
+        )
+    }
+}
+
+pub async fn is_admin(app_state: &AppState, auth: &AuthUser) -> ApiResult<bool> {
+    Ok(app_state.users.get_user_role(auth.user_id).await? == Some(GlobalRole::Admin))
+}
+
+pub async fn require_admin(app_state: &AppState, auth: &AuthUser) -> ApiResult<()> {
+    match is_admin(app_state, auth).await? {
+        true => Ok(()),
+        false => Err(ApiError::Forbidden("Administrator role required".into())),
+    }
+}
+
+/// Catalog entries can be edited by their creator or by an administrator.
+pub async fn require_creator_or_admin(
+    app_state: &AppState,
+    auth: &AuthUser,
+    creator_id: Option<Uuid>,
+) -> ApiResult<()> {
+    if creator_id == Some(auth.user_id) || is_admin(app_state, auth).await? {
+        return Ok(());
+    }
+    Err(ApiError::Forbidden(
+        "Only the creator or an administrator can do this".into(),
+    ))
+}
+
+/// Checks that the caller may perform `action` on the engine.
+/// Administrators are allowed everything, tokens additionally need the
+/// `engines:publish` scope.
+pub async fn authorize_engine(
+    app_state: &AppState,
+    auth: &AuthUser,
+    engine_id: Uuid,
+    action: EngineAction,
+) -> ApiResult<()> {
+    auth.require_scope(TokenScope::EnginesPublish)?;
+    if is_admin(app_state, auth).await? {
+        return Ok(());
+    }
+    match app_state
+        .engines
+        .get_engine_role(engine_id, auth.user_id)
+        .await?
+    {
+        Some(role) if action.is_allowed(role) => Ok(()),
+        _ => Err(ApiError::Forbidden(format!(
+            "Not allowed to {:?} this engine",
+            action
+        ))),
+    }
+}
+
+/// Checks that the caller may perform `action` on the project and returns what
+/// was learnt about it.
+///
+/// Anonymous callers may only view public projects. Private projects are
+/// reported as not found to non-members so their existence does not leak.
+pub async fn authorize_project(
+    app_state: &AppState,
+    auth: Option<&AuthUser>,
+    project_id: Uuid,
+    action: ProjectAction,
+) -> ApiResult<ProjectAccess> {
+    let not_found = || ApiError::NotFound("Project".into());
+    let access = app_state
+        .projects
+        .get_project_access(project_id, auth.map(|auth| auth.user_id))
+        .await?
+        .ok_or_else(not_found)?;
+    let admin = match auth {
+        Some(auth) => is_admin(app_state, auth).await?,
+        None => false,
+    };
+    let visible = admin || access.visibility == ProjectVisibility::Public || access.role.is_some();
+
+    if !visible {
+        return Err(not_found());
+    }
+    if action == ProjectAction::View {
+        return Ok(access);
+    }
+
+    let auth = auth.ok_or_else(|| ApiError::Unauthorized("Authentication required".into()))?;
+    auth.require_scope(TokenScope::ProjectsWrite)?;
+    if access.archived && !action.is_allowed_when_archived() {
+        return Err(ApiError::Conflict("Project is archived".into()));
+    }
+
+    match access.role {
+        _ if admin => Ok(access),
+        Some(role) if action.is_allowed(role) => Ok(access),
+        _ => Err(ApiError::Forbidden(format!(
+            "Not allowed to {:?} this project",
+            action
+        ))),
+    }
+}
@@ -0,0 +1,155 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde_json::json;
+use tsukimi_core::models::{AuditEntry, BackgroundJob, UpdateUserRoleRequest};
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{ApiError, ApiResult, ErrorBody},
+    middleware::{audit::AuditChange, auth::AuthUser},
+    policy,
+    services::database::{ApiPagination, AuditLogFilter, JobFilter},
+};
+
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(set_user_role))
+        .routes(routes!(get_audit_log))
+        .routes(routes!(get_jobs))
+        .routes(routes!(retry_job))
+}
+
+#[utoipa::path(
+    put,
+    path = "/users/{id}/role",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "User id")),
+    request_body = UpdateUserRoleRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 204, description = "Role updated"),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+        (status = 404, description = "Unknown user", body = ErrorBody),
+    )
+)]
+async fn set_user_role(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateUserRoleRequest>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>)> {
+    auth.require_session()?;
+    policy::require_admin(&app_state, &auth).await?;
+
+    let previous = app_state.users.get_user_role(id).await?;
+    match app_state.users.set_user_role(id, payload.role).await? {
+        true => {
+            let change = AuditChange::new("users", id)
+                .before(&json!({ "role": previous }))
+                .after(&json!({ "role": payload.role }));
+            Ok((StatusCode::NO_CONTENT, Extension(change)))
+        }
+        false => Err(ApiError::NotFound("User".into())),
+    }
+}
+
+/// Mutating requests that succeeded, most recent first. `query` is ignored.
+#[utoipa::path(
+    get,
+    path = "/audit-log",
+    tag = "admin",
+    params(ApiPagination, AuditLogFilter),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, body = Vec<AuditEntry>),
+        (status = 400, body = ErrorBody),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+    )
+)]
+async fn get_audit_log(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Query(pagination): Query<ApiPagination>,
+    Query(filter): Query<AuditLogFilter>,
+) -> ApiResult<Json<Vec<AuditEntry>>> {
+    auth.require_session()?;
+    policy::require_admin(&app_state, &auth).await?;
+
+    if let (Some(since), Some(until)) = (filter.since, filter.until)
+        && since > until
+    {
+        return Err(ApiError::BadRequest("`since` is after `until`".into()));
+    }
+    let entries = app_state.database.get_audit_log(pagination, filter).await?;
+    Ok(Json(entries))
+}
+
+/// Jobs of the background queue, most recent first.
+#[utoipa::path(
+    get,
+    path = "/jobs",
+    tag = "admin",
+    params(ApiPagination, JobFilter),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, body = Vec<BackgroundJob>),
+        (status = 400, body = ErrorBody),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+    )
+)]
+async fn get_jobs(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Query(pagination): Query<ApiPagination>,
+    Query(filter): Query<JobFilter>,
+) -> ApiResult<Json<Vec<BackgroundJob>>> {
+    auth.require_session()?;
+    policy::require_admin(&app_state, &auth).await?;
+
+    let jobs = app_state.database.get_jobs(pagination, filter).await?;
+    Ok(Json(jobs))
+}
+
+/// Queues a dead job again with fresh attempts.
+#[utoipa::path(
+    post,
+    path = "/jobs/{id}/retry",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Job id")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, body = BackgroundJob),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+        (status = 404, description = "Unknown job, or not dead", body = ErrorBody),
+        (status = 409, description = "The same job is already queued", body = ErrorBody),
+    )
+)]
+async fn retry_job(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<(Extension<AuditChange>, Json<BackgroundJob>)> {
+    auth.require_session()?;
+    policy::require_admin(&app_state, &auth).await?;
+
+    let job = app_state
+        .database
+        .retry_job(id)
+        .await
+        .map_err(|e| match &e {
+            sqlx::Error::Database(db) if db.is_unique_violation() => {
+                ApiError::Conflict("The same job is already queued".into())
+            }
+            _ => e.into(),
+        })?
+        .ok_or_else(|| ApiError::NotFound("Dead job".into()))?;
+    let change = AuditChange::new("jobs", id)
+        .before(&json!({ "status": "dead" }))
+        .after(&json!({ "status": job.status }));
+    Ok((Extension(change), Json(job)))
+}
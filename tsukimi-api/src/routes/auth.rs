@@ -0,0 +1,322 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde_json::json;
+use tsukimi_core::{
+    auth::{
+        DeviceSessionRequest, OauthExchangeCodeRequest, RefreshSessionRequest, SessionResponse,
+        UserIdentity,
+    },
+    models::User,
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{ApiError, ApiResult, ErrorBody},
+    middleware::{audit::AuditChange, auth::AuthUser},
+    routes::oauth,
+    services::{database::NewIdentity, session::SessionService},
+};
+
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(create_session, revoke_session))
+        .routes(routes!(create_device_session))
+        .routes(routes!(revoke_all_sessions))
+        .routes(routes!(refresh_session))
+        .routes(routes!(me))
+        .routes(routes!(list_identities, link_identity))
+        .routes(routes!(unlink_identity))
+}
+
+/// Exchanges the authorization code of any identity provider, then drops the
+/// provider token and returns a Tsukimi session instead. The first login with
+/// an identity creates the user.
+#[utoipa::path(
+    post,
+    path = "/session",
+    tag = "auth",
+    request_body = OauthExchangeCodeRequest,
+    responses(
+        (status = 200, body = SessionResponse),
+        (status = 400, description = "Unknown, expired or spent OAuth state, or started to link an account", body = ErrorBody),
+        (status = 401, description = "The provider refused the code", body = ErrorBody),
+        (status = 502, description = "The provider is unreachable", body = ErrorBody),
+    )
+)]
+async fn create_session(
+    State(app_state): State<AppState>,
+    Json(payload): Json<OauthExchangeCodeRequest>,
+) -> ApiResult<(Extension<AuditChange>, Json<SessionResponse>)> {
+    let identity = oauth::identify(&app_state, None, payload).await?;
+    open_session(&app_state, &identity).await
+}
+
+/// Trades an access token the client obtained through the device flow of a
+/// provider for a Tsukimi session. The provider token is not kept.
+#[utoipa::path(
+    post,
+    path = "/device-session",
+    tag = "auth",
+    request_body = DeviceSessionRequest,
+    responses(
+        (status = 200, body = SessionResponse),
+        (status = 401, description = "The provider refused the token, or issued it to another application", body = ErrorBody),
+        (status = 404, description = "Unknown identity provider", body = ErrorBody),
+    )
+)]
+async fn create_device_session(
+    State(app_state): State<AppState>,
+    Json(payload): Json<DeviceSessionRequest>,
+) -> ApiResult<(Extension<AuditChange>, Json<SessionResponse>)> {
+    let provider = app_state
+        .oauth
+        .provider(&payload.provider)
+        .ok_or_else(|| ApiError::NotFound("Identity provider".into()))?;
+    let identity = provider
+        .identify_device_token(&payload.access_token)
+        .await
+        .map_err(|_| ApiError::Unauthorized("Error identifying the token".into()))?;
+    open_session(&app_state, &identity).await
+}
+
+/// Logs in the user owning the identity, creating it on first login.
+async fn open_session(
+    app_state: &AppState,
+    identity: &NewIdentity,
+) -> ApiResult<(Extension<AuditChange>, Json<SessionResponse>)> {
+    let user = app_state.users.upsert_identity_user(identity).await?;
+
+    let refresh_token = SessionService::generate_refresh_token();
+    let session_id = app_state
+        .users
+        .create_session(
+            user.id,
+            &SessionService::hash_secret(refresh_token.secret()),
+            app_state.session.refresh_token_expiration(),
+        )
+        .await?;
+
+    // Logged in requests carry no actor yet, the user is part of the change
+    let change = AuditChange::new("sessions", session_id).after(&json!({
+        "user_id": user.id,
+        "provider": identity.provider,
+        "subject": identity.subject,
+    }));
+    Ok((
+        Extension(change),
+        Json(SessionResponse {
+            access_token: app_state.session.sign_access_token(user.id, session_id)?,
+            refresh_token,
+            expires_in: app_state.session.access_token_ttl(),
+        }),
+    ))
+}
+
+/// Trades a refresh token for a new pair, the old refresh token is spent.
+#[utoipa::path(
+    post,
+    path = "/refresh",
+    tag = "auth",
+    request_body = RefreshSessionRequest,
+    responses(
+        (status = 200, body = SessionResponse),
+        (status = 401, description = "Invalid or expired refresh token", body = ErrorBody),
+    )
+)]
+async fn refresh_session(
+    State(app_state): State<AppState>,
+    Json(payload): Json<RefreshSessionRequest>,
+) -> ApiResult<(Extension<AuditChange>, Json<SessionResponse>)> {
+    let refresh_token = SessionService::generate_refresh_token();
+    let (session_id, user_id): (Uuid, Uuid) = app_state
+        .users
+        .rotate_session(
+            &SessionService::hash_secret(payload.refresh_token.secret()),
+            &SessionService::hash_secret(refresh_token.secret()),
+            app_state.session.refresh_token_expiration(),
+        )
+        .await?
+        .ok_or_else(|| ApiError::Unauthorized("Invalid or expired refresh token".into()))?;
+
+    let change = AuditChange::new("sessions", session_id).after(&json!({ "user_id": user_id }));
+    Ok((
+        Extension(change),
+        Json(SessionResponse {
+            access_token: app_state.session.sign_access_token(user_id, session_id)?,
+            refresh_token,
+            expires_in: app_state.session.access_token_ttl(),
+        }),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/session",
+    tag = "auth",
+    security(("bearer" = [])),
+    responses(
+        (status = 204, description = "Session revoked"),
+        (status = 401, body = ErrorBody),
+        (status = 403, description = "Called with a personal access token", body = ErrorBody),
+    )
+)]
+async fn revoke_session(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+) -> ApiResult<(StatusCode, Extension<AuditChange>)> {
+    let session_id = auth.require_session()?;
+    app_state.users.revoke_session(session_id).await?;
+    let change = AuditChange::new("sessions", session_id);
+    Ok((StatusCode::NO_CONTENT, Extension(change)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/sessions",
+    tag = "auth",
+    security(("bearer" = [])),
+    responses(
+        (status = 204, description = "Every session of the caller revoked"),
+        (status = 401, body = ErrorBody),
+        (status = 403, description = "Called with a personal access token", body = ErrorBody),
+    )
+)]
+async fn revoke_all_sessions(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+) -> ApiResult<(StatusCode, Extension<AuditChange>)> {
+    auth.require_session()?;
+    app_state.users.revoke_user_sessions(auth.user_id).await?;
+    let change = AuditChange::new("users", auth.user_id);
+    Ok((StatusCode::NO_CONTENT, Extension(change)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/me",
+    tag = "auth",
+    security(("bearer" = [])),
+    responses((status = 200, body = User), (status = 401, body = ErrorBody))
+)]
+async fn me(State(app_state): State<AppState>, auth: AuthUser) -> ApiResult<Json<User>> {
+    app_state
+        .users
+        .get_user(auth.user_id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound("User".into()))
+}
+
+/// Accounts the caller can log in with.
+#[utoipa::path(
+    get,
+    path = "/identities",
+    tag = "auth",
+    security(("bearer" = [])),
+    responses((status = 200, body = Vec<UserIdentity>), (status = 401, body = ErrorBody))
+)]
+async fn list_identities(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+) -> ApiResult<Json<Vec<UserIdentity>>> {
+    Ok(Json(
+        app_state.users.get_user_identities(auth.user_id).await?,
+    ))
+}
+
+/// Links the account of another provider to the caller. The authorization must
+/// have been started by the caller with `purpose=link`.
+#[utoipa::path(
+    post,
+    path = "/identities",
+    tag = "auth",
+    security(("bearer" = [])),
+    request_body = OauthExchangeCodeRequest,
+    responses(
+        (status = 201, body = UserIdentity),
+        (status = 400, description = "Unknown, expired or spent OAuth state, or not started by the caller to link an account", body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, description = "Called with a personal access token", body = ErrorBody),
+        (status = 409, description = "Account already linked to a user, or provider already linked", body = ErrorBody),
+        (status = 502, description = "The provider is unreachable", body = ErrorBody),
+    )
+)]
+async fn link_identity(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Json(payload): Json<OauthExchangeCodeRequest>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>, Json<UserIdentity>)> {
+    auth.require_session()?;
+    let identity = oauth::identify(&app_state, Some(auth.user_id), payload).await?;
+    let linked = app_state
+        .users
+        .link_identity(auth.user_id, &identity)
+        .await
+        .map_err(|error| match &error {
+            sqlx::Error::Database(e) if e.is_unique_violation() => {
+                ApiError::Conflict("Another account of this provider is already linked".into())
+            }
+            _ => error.into(),
+        })?
+        .ok_or_else(|| ApiError::Conflict("This account is linked to another user".into()))?;
+    let change = AuditChange::new(
+        "user_identities",
+        format!("{}/{}", auth.user_id, linked.provider),
+    )
+    .after(&linked);
+    Ok((StatusCode::CREATED, Extension(change), Json(linked)))
+}
+
+/// Unlinks the account of a provider. The last identity of a user can't be
+/// unlinked, they couldn't log in anymore.
+#[utoipa::path(
+    delete,
+    path = "/identities/{provider}",
+    tag = "auth",
+    security(("bearer" = [])),
+    params(("provider" = String, Path, description = "Identity provider id")),
+    responses(
+        (status = 204, description = "Identity unlinked"),
+        (status = 401, body = ErrorBody),
+        (status = 403, description = "Called with a personal access token", body = ErrorBody),
+        (status = 404, description = "No account of this provider is linked", body = ErrorBody),
+        (status = 409, description = "Last identity of the user", body = ErrorBody),
+    )
+)]
+async fn unlink_identity(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(provider): Path<String>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>)> {
+    auth.require_session()?;
+    let identities = app_state.users.get_user_identities(auth.user_id).await?;
+    let Some(identity) = identities
+        .iter()
+        .find(|identity| identity.provider == provider)
+    else {
+        return Err(ApiError::NotFound("Identity".into()));
+    };
+    if identities.len() == 1 {
+        return Err(ApiError::Conflict(
+            "The last identity of a user can't be unlinked".into(),
+        ));
+    }
+    match app_state
+        .users
+        .unlink_identity(auth.user_id, &provider)
+        .await?
+    {
+        true => {
+            let change =
+                AuditChange::new("user_identities", format!("{}/{}", auth.user_id, provider))
+                    .before(identity);
+            Ok((StatusCode::NO_CONTENT, Extension(change)))
+        }
+        false => Err(ApiError::NotFound("Identity".into())),
+    }
+}
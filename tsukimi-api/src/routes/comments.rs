@@ -0,0 +1,311 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use tsukimi_core::models::{CommentThread, CreateCommentRequest, CreateThreadRequest};
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{ApiError, ApiResult, ErrorBody},
+    middleware::{audit::AuditChange, auth::AuthUser},
+    policy::{self, ProjectAction},
+    routes::units::{ensure_target_language, ensure_unit},
+    services::database::{ApiPagination, ThreadFilter},
+};
+
+static MAX_COMMENT_LENGTH: usize = 10_000;
+
+/// Routes nested under `/projects`.
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_threads))
+        .routes(routes!(get_unit_threads, create_thread))
+        .routes(routes!(get_thread))
+        .routes(routes!(create_comment))
+        .routes(routes!(resolve_thread))
+        .routes(routes!(reopen_thread))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/threads",
+    tag = "comments",
+    params(("id" = Uuid, Path, description = "Project id"), ApiPagination, ThreadFilter),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, body = Vec<CommentThread>),
+        (status = 404, description = "Unknown or hidden project", body = ErrorBody),
+    )
+)]
+async fn get_threads(
+    State(app_state): State<AppState>,
+    auth: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(pagination): Query<ApiPagination>,
+    Query(filter): Query<ThreadFilter>,
+) -> ApiResult<Json<Vec<CommentThread>>> {
+    policy::authorize_project(&app_state, auth.as_ref(), id, ProjectAction::View).await?;
+    let threads = app_state
+        .database
+        .get_threads(id, pagination, filter)
+        .await?;
+    Ok(Json(threads))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/units/{unit_id}/threads",
+    tag = "comments",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("unit_id" = Uuid, Path, description = "Unit id"),
+        ApiPagination,
+        ThreadFilter,
+    ),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, body = Vec<CommentThread>),
+        (status = 404, description = "Unknown unit", body = ErrorBody),
+    )
+)]
+async fn get_unit_threads(
+    State(app_state): State<AppState>,
+    auth: Option<AuthUser>,
+    Path((id, unit_id)): Path<(Uuid, Uuid)>,
+    Query(pagination): Query<ApiPagination>,
+    Query(mut filter): Query<ThreadFilter>,
+) -> ApiResult<Json<Vec<CommentThread>>> {
+    policy::authorize_project(&app_state, auth.as_ref(), id, ProjectAction::View).await?;
+    ensure_unit(&app_state, id, unit_id).await?;
+    filter.unit_id = Some(unit_id);
+    let threads = app_state
+        .database
+        .get_threads(id, pagination, filter)
+        .await?;
+    Ok(Json(threads))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/threads/{thread_id}",
+    tag = "comments",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("thread_id" = Uuid, Path, description = "Thread id"),
+    ),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, body = CommentThread),
+        (status = 404, description = "Unknown thread", body = ErrorBody),
+    )
+)]
+async fn get_thread(
+    State(app_state): State<AppState>,
+    auth: Option<AuthUser>,
+    Path((id, thread_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<Json<CommentThread>> {
+    policy::authorize_project(&app_state, auth.as_ref(), id, ProjectAction::View).await?;
+    find_thread(&app_state, id, thread_id).await.map(Json)
+}
+
+/// Opens a thread on a unit, on its source text or on one of its translations.
+#[utoipa::path(
+    post,
+    path = "/{id}/units/{unit_id}/threads",
+    tag = "comments",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("unit_id" = Uuid, Path, description = "Unit id"),
+    ),
+    request_body = CreateThreadRequest,
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 201, body = CommentThread),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown unit", body = ErrorBody),
+    )
+)]
+async fn create_thread(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, unit_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<CreateThreadRequest>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>, Json<CommentThread>)> {
+    policy::authorize_project(&app_state, Some(&auth), id, ProjectAction::Translate).await?;
+    ensure_unit(&app_state, id, unit_id).await?;
+    if let Some(language) = &payload.language {
+        ensure_target_language(&app_state, id, language).await?;
+    }
+    validate_body(&payload.body)?;
+
+    let thread_id = app_state
+        .database
+        .create_thread(
+            unit_id,
+            payload.language.as_deref(),
+            payload.flag,
+            auth.user_id,
+            &payload.body,
+            &parse_mentions(&payload.body),
+        )
+        .await?;
+    let thread = find_thread(&app_state, id, thread_id).await?;
+    let change = AuditChange::new("comment_threads", thread_id).after(&thread);
+    Ok((StatusCode::CREATED, Extension(change), Json(thread)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/threads/{thread_id}/comments",
+    tag = "comments",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("thread_id" = Uuid, Path, description = "Thread id"),
+    ),
+    request_body = CreateCommentRequest,
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 201, body = CommentThread),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown thread", body = ErrorBody),
+    )
+)]
+async fn create_comment(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, thread_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<CreateCommentRequest>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>, Json<CommentThread>)> {
+    policy::authorize_project(&app_state, Some(&auth), id, ProjectAction::Translate).await?;
+    find_thread(&app_state, id, thread_id).await?;
+    validate_body(&payload.body)?;
+
+    let comment_id = app_state
+        .database
+        .create_comment(
+            thread_id,
+            auth.user_id,
+            &payload.body,
+            &parse_mentions(&payload.body),
+        )
+        .await?;
+    let thread = find_thread(&app_state, id, thread_id).await?;
+    let comment = thread
+        .comments
+        .iter()
+        .find(|comment| comment.id == comment_id);
+    let change = AuditChange::new("comments", comment_id).after(&comment);
+    Ok((StatusCode::CREATED, Extension(change), Json(thread)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/threads/{thread_id}/resolve",
+    tag = "comments",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("thread_id" = Uuid, Path, description = "Thread id"),
+    ),
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = CommentThread),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown thread", body = ErrorBody),
+    )
+)]
+async fn resolve_thread(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, thread_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<(Extension<AuditChange>, Json<CommentThread>)> {
+    set_resolved(app_state, auth, id, thread_id, true).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/threads/{thread_id}/reopen",
+    tag = "comments",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("thread_id" = Uuid, Path, description = "Thread id"),
+    ),
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = CommentThread),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown thread", body = ErrorBody),
+    )
+)]
+async fn reopen_thread(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, thread_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<(Extension<AuditChange>, Json<CommentThread>)> {
+    set_resolved(app_state, auth, id, thread_id, false).await
+}
+
+async fn set_resolved(
+    app_state: AppState,
+    auth: AuthUser,
+    id: Uuid,
+    thread_id: Uuid,
+    resolved: bool,
+) -> ApiResult<(Extension<AuditChange>, Json<CommentThread>)> {
+    policy::authorize_project(&app_state, Some(&auth), id, ProjectAction::Translate).await?;
+    let previous = find_thread(&app_state, id, thread_id).await?;
+
+    app_state
+        .database
+        .set_thread_resolved(thread_id, resolved.then_some(auth.user_id))
+        .await?;
+    let thread = find_thread(&app_state, id, thread_id).await?;
+    let change = AuditChange::new("comment_threads", thread_id)
+        .before(&previous)
+        .after(&thread);
+    Ok((Extension(change), Json(thread)))
+}
+
+async fn find_thread(
+    app_state: &AppState,
+    project_id: Uuid,
+    thread_id: Uuid,
+) -> ApiResult<CommentThread> {
+    app_state
+        .database
+        .get_project_thread(project_id, thread_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Thread".into()))
+}
+
+fn validate_body(body: &str) -> ApiResult<()> {
+    let length = body.trim().len();
+    if length == 0 || length > MAX_COMMENT_LENGTH {
+        return Err(ApiError::BadRequest(format!(
+            "Comment must be between 1 and {} characters",
+            MAX_COMMENT_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+/// Extracts the `@username` mentions of a comment, without duplicates.
+fn parse_mentions(body: &str) -> Vec<String> {
+    let mut mentions: Vec<String> = body
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '@' || c == '_' || c == '-'))
+        .filter_map(|word| word.strip_prefix('@'))
+        .filter(|name| !name.is_empty() && !name.contains('@'))
+        .map(str::to_string)
+        .collect();
+    mentions.sort_unstable();
+    mentions.dedup();
+    mentions
+}
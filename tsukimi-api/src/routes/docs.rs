@@ -0,0 +1,73 @@
+use axum::{Json, Router, routing::get};
+use utoipa::{
+    Modify, OpenApi,
+    openapi::{
+        self,
+        security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    },
+};
+use utoipa_scalar::{Scalar, Servable};
+
+use crate::{AppState, error::ErrorBody, services::database::EngineSort};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Tsukimi API",
+        description = "Registry of Tsukimi engine extensions and visual novel translation projects."
+    ),
+    components(schemas(ErrorBody, EngineSort)),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "meta", description = "Probes, build information and metrics"),
+        (name = "auth", description = "Tsukimi sessions opened with an identity provider login"),
+        (name = "oauth", description = "Identity providers, raw OAuth authorization, exchange, refresh and revocation"),
+        (name = "tokens", description = "Personal access tokens"),
+        (name = "admin", description = "Administration, admins only"),
+        (name = "engines", description = "Engine extensions and their releases"),
+        (name = "publisher-keys", description = "Ed25519 keys engine releases are signed with"),
+        (name = "visual-novels", description = "Visual novel catalogue"),
+        (name = "projects", description = "Translation projects and their members"),
+        (name = "units", description = "Extracted source text and its translations"),
+        (name = "reviews", description = "Suggestions and review workflow"),
+        (name = "comments", description = "Discussion threads on units"),
+        (name = "stats", description = "Project progress and activity"),
+        (name = "webhooks", description = "Signed notifications of engine and project events"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Declares the `bearer` scheme referenced by the authenticated routes.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, api: &mut openapi::OpenApi) {
+        let components = api.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some(
+                        "Session access token, or personal access token starting with `tsk_pat_`",
+                    ))
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// Serves the document at `/openapi.json` and an interactive reference at
+/// `/docs`, the page loads its script from a CDN.
+pub fn get_router(api: openapi::OpenApi) -> Router<AppState> {
+    let document = api.clone();
+    Router::new()
+        .route(
+            "/openapi.json",
+            get(move || {
+                let document = document.clone();
+                async move { Json(document) }
+            }),
+        )
+        .merge(Scalar::with_url("/docs", api))
+}
@@ -1,20 +1,611 @@
 use crate::AppState;
-use crate::services::database::ApiPagination;
-use axum::extract::{Query, State};
-use tsukimi_core::models::Engine;
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::middleware::audit::AuditChange;
+use crate::middleware::auth::AuthUser;
+use crate::policy::{self, EngineAction};
+use crate::services::database::{ApiPagination, EngineFilter, MemberUpdate, NewEngineVersion};
+use crate::services::metrics;
+use axum::Extension;
+use axum::extract::{DefaultBodyLimit, Path, Query, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::IntoResponse;
+use bytes::Bytes;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+use tsukimi_core::models::{
+    DeprecateEngineRequest, Engine, EngineMember, EngineVersion, UpdateEngineMemberRequest,
+    ValidationStatus, Version, YankVersionRequest,
+};
+use tsukimi_core::signing;
+use utoipa::IntoParams;
+use utoipa_axum::router::UtoipaMethodRouterExt;
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
 
-pub fn get_router() -> axum::Router<AppState> {
-    axum::Router::new().route("/", axum::routing::get(get_engines))
+/// Hex encoded SHA-256 of the artifact, checked by the CLI after download
+static CHECKSUM_HEADER: &str = "x-checksum-sha256";
+/// Set on downloads of a yanked version, lockfiles pinning it still work
+static YANKED_HEADER: &str = "x-yanked";
+/// Base64 encoded Ed25519 signature of the artifact, sent on upload and download
+static SIGNATURE_HEADER: &str = "x-signature";
+/// Id of the publisher key the artifact is signed with
+static PUBLISHER_KEY_HEADER: &str = "x-publisher-key";
+/// Largest artifact accepted on upload
+static MAX_ARTIFACT_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Deserialize, Debug, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct PublishParams {
+    /// Release notes
+    description: Option<String>,
+}
+
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_engines))
+        .routes(routes!(get_engine))
+        .routes(routes!(deprecate_engine))
+        .routes(routes!(undeprecate_engine))
+        .routes(routes!(get_versions))
+        .routes(routes!(publish_version).layer(DefaultBodyLimit::max(MAX_ARTIFACT_SIZE)))
+        .routes(routes!(yank_version))
+        .routes(routes!(unyank_version))
+        .routes(routes!(get_members))
+        .routes(routes!(download_version))
+        .routes(routes!(set_member, remove_member))
 }
 
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "engines",
+    params(ApiPagination, EngineFilter),
+    responses((status = 200, body = Vec<Engine>))
+)]
 async fn get_engines(
     Query(pagination): Query<ApiPagination>,
+    Query(filter): Query<EngineFilter>,
     State(app_state): State<AppState>,
-) -> Result<axum::Json<Vec<Engine>>, &'static str> {
-    let list = app_state
-        .database
-        .get_engines(pagination)
-        .await
-        .map_err(|_| "Error fetching engines")?;
+) -> ApiResult<axum::Json<Vec<Engine>>> {
+    let list = app_state.engines.get_engines(pagination, filter).await?;
     Ok(axum::Json(list))
 }
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "engines",
+    params(("id" = Uuid, Path, description = "Engine id")),
+    responses(
+        (status = 200, body = Engine),
+        (status = 404, body = ErrorBody),
+    )
+)]
+async fn get_engine(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<axum::Json<Engine>> {
+    app_state
+        .engines
+        .get_engine(id)
+        .await?
+        .map(axum::Json)
+        .ok_or_else(|| ApiError::NotFound("Engine".into()))
+}
+
+/// Marks the whole engine as abandoned, optionally pointing to a replacement.
+/// Its versions stay installable.
+#[utoipa::path(
+    post,
+    path = "/{id}/deprecate",
+    tag = "engines",
+    params(("id" = Uuid, Path, description = "Engine id")),
+    request_body = DeprecateEngineRequest,
+    security(("bearer" = ["engines:publish"])),
+    responses(
+        (status = 200, body = Engine),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    )
+)]
+async fn deprecate_engine(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    axum::Json(payload): axum::Json<DeprecateEngineRequest>,
+) -> ApiResult<(Extension<AuditChange>, axum::Json<Engine>)> {
+    policy::authorize_engine(&app_state, &auth, id, EngineAction::Deprecate).await?;
+
+    let message = payload.message.trim();
+    if message.is_empty() || message.len() > 1000 {
+        return Err(ApiError::BadRequest(
+            "Deprecation message must be between 1 and 1000 characters".into(),
+        ));
+    }
+    if let Some(replacement) = payload.replacement_engine_id {
+        if replacement == id {
+            return Err(ApiError::BadRequest(
+                "An engine cannot replace itself".into(),
+            ));
+        }
+        if app_state.engines.get_engine(replacement).await?.is_none() {
+            return Err(ApiError::BadRequest("Unknown replacement engine".into()));
+        }
+    }
+
+    let not_found = || ApiError::NotFound("Engine".into());
+    let engine = app_state
+        .engines
+        .get_engine(id)
+        .await?
+        .ok_or_else(not_found)?;
+    let deprecated = app_state
+        .engines
+        .deprecate_engine(id, message, payload.replacement_engine_id)
+        .await?
+        .ok_or_else(not_found)?;
+    let change = AuditChange::new("engines", id)
+        .before(&engine)
+        .after(&deprecated);
+    Ok((Extension(change), axum::Json(deprecated)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/undeprecate",
+    tag = "engines",
+    params(("id" = Uuid, Path, description = "Engine id")),
+    security(("bearer" = ["engines:publish"])),
+    responses(
+        (status = 200, body = Engine),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    )
+)]
+async fn undeprecate_engine(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<(Extension<AuditChange>, axum::Json<Engine>)> {
+    policy::authorize_engine(&app_state, &auth, id, EngineAction::Deprecate).await?;
+
+    let not_found = || ApiError::NotFound("Engine".into());
+    let engine = app_state
+        .engines
+        .get_engine(id)
+        .await?
+        .ok_or_else(not_found)?;
+    let undeprecated = app_state
+        .engines
+        .undeprecate_engine(id)
+        .await?
+        .ok_or_else(not_found)?;
+    let change = AuditChange::new("engines", id)
+        .before(&engine)
+        .after(&undeprecated);
+    Ok((Extension(change), axum::Json(undeprecated)))
+}
+
+/// Every published version, yanked ones included, most recent first.
+#[utoipa::path(
+    get,
+    path = "/{id}/versions",
+    tag = "engines",
+    params(("id" = Uuid, Path, description = "Engine id")),
+    responses(
+        (status = 200, body = Vec<EngineVersion>),
+        (status = 404, body = ErrorBody),
+    )
+)]
+async fn get_versions(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<axum::Json<Vec<EngineVersion>>> {
+    if app_state.engines.get_engine(id).await?.is_none() {
+        return Err(ApiError::NotFound("Engine".into()));
+    }
+    let versions = app_state.engine_versions.get_engine_versions(id).await?;
+    Ok(axum::Json(versions))
+}
+
+/// Uploads a release. The body is the WebAssembly component, signed with a
+/// key the caller registered. The release stays pending until a background
+/// job checks it implements the `tsukimi:extension` world, it then becomes
+/// the current version unless a greater one already is.
+#[utoipa::path(
+    put,
+    path = "/{id}/versions/{version}",
+    tag = "engines",
+    params(
+        ("id" = Uuid, Path, description = "Engine id"),
+        ("version" = String, Path, description = "Released version, e.g. `1.2.0`"),
+        ("x-signature" = String, Header, description = "Base64 encoded Ed25519 signature of the body"),
+        ("x-publisher-key" = Uuid, Header, description = "Publisher key the body is signed with"),
+        PublishParams,
+    ),
+    request_body(content = Vec<u8>, content_type = "application/wasm"),
+    security(("bearer" = ["engines:publish"])),
+    responses(
+        (status = 202, description = "Uploaded, pending validation", body = EngineVersion),
+        (status = 400, description = "Invalid version, key or signature", body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+        (status = 409, description = "Version already published", body = ErrorBody),
+        (status = 413, description = "Artifact too large"),
+    )
+)]
+async fn publish_version(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, version)): Path<(Uuid, String)>,
+    Query(params): Query<PublishParams>,
+    headers: HeaderMap,
+    artifact: Bytes,
+) -> ApiResult<(
+    StatusCode,
+    Extension<AuditChange>,
+    axum::Json<EngineVersion>,
+)> {
+    policy::authorize_engine(&app_state, &auth, id, EngineAction::Publish).await?;
+
+    let parsed: Version = version
+        .parse()
+        .map_err(|e| ApiError::BadRequest(format!("{}: {}", e, version)))?;
+    let version = parsed.to_string();
+    if artifact.is_empty() {
+        return Err(ApiError::BadRequest("Artifact is empty".into()));
+    }
+    let description = params
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|description| !description.is_empty());
+
+    let signature = header_value(&headers, SIGNATURE_HEADER)?;
+    let key_id: Uuid = header_value(&headers, PUBLISHER_KEY_HEADER)?
+        .parse()
+        .map_err(|_| ApiError::BadRequest(format!("{} must be a key id", PUBLISHER_KEY_HEADER)))?;
+    let key = app_state
+        .engine_versions
+        .get_publisher_key(key_id)
+        .await?
+        .filter(|key| key.user_id == auth.user_id && key.revoked_at.is_none())
+        .ok_or_else(|| ApiError::BadRequest("Unknown or revoked publisher key".into()))?;
+    signing::verify_artifact(&key.public_key, signature, &artifact)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid signature: {}", e)))?;
+
+    app_state
+        .engines
+        .get_engine(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Engine".into()))?;
+    let conflict = || ApiError::Conflict(format!("Version {} is already published", version));
+    // A version whose artifact was rejected can be uploaded again
+    if app_state
+        .engine_versions
+        .get_engine_version(id, &version)
+        .await?
+        .is_some_and(|existing| existing.validation_status != ValidationStatus::Invalid)
+    {
+        return Err(conflict());
+    }
+
+    // Keyed by upload, a concurrent upload of the same version never
+    // overwrites the artifact of the one that wins, and a failed one can
+    // remove its artifact without touching the other's
+    let sha256 = hex::encode(Sha256::digest(&artifact));
+    let artifact_key = format!("engines/{}/{}/{}.wasm", id, version, Uuid::new_v4());
+    let artifact_size = artifact.len() as i64;
+    app_state.storage.put(&artifact_key, artifact).await?;
+
+    let published = match app_state
+        .engine_versions
+        .publish_version(&NewEngineVersion {
+            engine_id: id,
+            version: &version,
+            description,
+            artifact_key: &artifact_key,
+            artifact_size,
+            artifact_sha256: &sha256,
+            signature: signature.trim(),
+            publisher_key_id: key.id,
+        })
+        .await
+    {
+        Ok(published) => published,
+        Err(e) => {
+            // No version refers to the artifact, don't leave it behind
+            if let Err(delete_error) = app_state.storage.delete(&artifact_key).await {
+                warn!("Orphaned artifact {}: {}", artifact_key, delete_error);
+            }
+            return Err(match &e {
+                sqlx::Error::Database(db) if db.is_unique_violation() => conflict(),
+                _ => e.into(),
+            });
+        }
+    };
+    let change = AuditChange::new("engine_versions", published.id).after(&published);
+    Ok((
+        StatusCode::ACCEPTED,
+        Extension(change),
+        axum::Json(published),
+    ))
+}
+
+fn header_value<'a>(headers: &'a HeaderMap, name: &str) -> ApiResult<&'a str> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| ApiError::BadRequest(format!("Missing {} header", name)))
+}
+
+/// Pulls a broken release: it is no longer offered as the current version but
+/// stays downloadable for lockfiles that pin it. Yanking the current version
+/// makes the latest remaining one current.
+#[utoipa::path(
+    post,
+    path = "/{id}/versions/{version}/yank",
+    tag = "engines",
+    params(
+        ("id" = Uuid, Path, description = "Engine id"),
+        ("version" = String, Path, description = "Released version, e.g. `1.2.0`"),
+    ),
+    request_body = YankVersionRequest,
+    security(("bearer" = ["engines:publish"])),
+    responses(
+        (status = 200, body = EngineVersion),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown engine or version", body = ErrorBody),
+    )
+)]
+async fn yank_version(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, version)): Path<(Uuid, String)>,
+    axum::Json(payload): axum::Json<YankVersionRequest>,
+) -> ApiResult<(Extension<AuditChange>, axum::Json<EngineVersion>)> {
+    policy::authorize_engine(&app_state, &auth, id, EngineAction::Yank).await?;
+
+    let reason = payload
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|reason| !reason.is_empty());
+    if reason.is_some_and(|reason| reason.len() > 1000) {
+        return Err(ApiError::BadRequest(
+            "Yank reason must be at most 1000 characters".into(),
+        ));
+    }
+
+    let yanked = app_state
+        .engine_versions
+        .yank_version(id, &version, reason, auth.user_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Engine version".into()))?;
+    let change = AuditChange::new("engine_versions", yanked.id).after(&yanked);
+    Ok((Extension(change), axum::Json(yanked)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/versions/{version}/unyank",
+    tag = "engines",
+    params(
+        ("id" = Uuid, Path, description = "Engine id"),
+        ("version" = String, Path, description = "Released version, e.g. `1.2.0`"),
+    ),
+    security(("bearer" = ["engines:publish"])),
+    responses(
+        (status = 200, body = EngineVersion),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown engine or version", body = ErrorBody),
+    )
+)]
+async fn unyank_version(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, version)): Path<(Uuid, String)>,
+) -> ApiResult<(Extension<AuditChange>, axum::Json<EngineVersion>)> {
+    policy::authorize_engine(&app_state, &auth, id, EngineAction::Yank).await?;
+
+    let unyanked = app_state
+        .engine_versions
+        .unyank_version(id, &version)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Engine version".into()))?;
+    let change = AuditChange::new("engine_versions", unyanked.id).after(&unyanked);
+    Ok((Extension(change), axum::Json(unyanked)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/versions/{version}/download",
+    tag = "engines",
+    params(
+        ("id" = Uuid, Path, description = "Engine id"),
+        ("version" = String, Path, description = "Released version, e.g. `1.2.0`"),
+    ),
+    responses(
+        (
+            status = 200,
+            description = "The WebAssembly component",
+            body = Vec<u8>,
+            content_type = "application/wasm",
+            headers(
+                ("x-checksum-sha256" = String, description = "Hex encoded SHA-256 of the artifact"),
+                ("x-yanked" = String, description = "`true` when the version is yanked"),
+                ("x-signature" = String, description = "Base64 encoded Ed25519 signature of the artifact"),
+                ("x-publisher-key" = String, description = "Id of the publisher key the artifact is signed with"),
+            )
+        ),
+        (status = 404, description = "Unknown engine or version", body = ErrorBody),
+    )
+)]
+async fn download_version(
+    State(app_state): State<AppState>,
+    Path((id, version)): Path<(Uuid, String)>,
+) -> ApiResult<impl IntoResponse> {
+    let artifact = app_state
+        .engine_versions
+        .get_version_artifact(id, &version)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Engine version".into()))?;
+    let bytes = app_state
+        .storage
+        .get(&artifact.artifact_key)
+        .await?
+        .ok_or_else(|| {
+            ApiError::Internal(format!("Artifact {} is missing", artifact.artifact_key))
+        })?;
+
+    app_state.engines.increment_downloads(id).await?;
+    metrics::record_download(&artifact.engine_name, bytes.len());
+
+    let disposition = format!(
+        "attachment; filename=\"{}-{}.wasm\"",
+        artifact.engine_name, version
+    );
+    let mut headers = vec![(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/wasm"),
+    )];
+    if let Ok(value) = HeaderValue::from_str(&disposition) {
+        headers.push((header::CONTENT_DISPOSITION, value));
+    }
+    if let Some(value) = artifact
+        .artifact_sha256
+        .and_then(|sha256| HeaderValue::from_str(&sha256).ok())
+    {
+        headers.push((header::HeaderName::from_static(CHECKSUM_HEADER), value));
+    }
+    if let Some(value) = artifact
+        .signature
+        .and_then(|signature| HeaderValue::from_str(&signature).ok())
+    {
+        headers.push((header::HeaderName::from_static(SIGNATURE_HEADER), value));
+    }
+    if let Some(value) = artifact
+        .publisher_key_id
+        .and_then(|key_id| HeaderValue::from_str(&key_id.to_string()).ok())
+    {
+        headers.push((header::HeaderName::from_static(PUBLISHER_KEY_HEADER), value));
+    }
+    if artifact.yanked {
+        headers.push((
+            header::HeaderName::from_static(YANKED_HEADER),
+            HeaderValue::from_static("true"),
+        ));
+    }
+    Ok((axum::response::AppendHeaders(headers), bytes))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/members",
+    tag = "engines",
+    params(("id" = Uuid, Path, description = "Engine id")),
+    responses((status = 200, body = Vec<EngineMember>))
+)]
+async fn get_members(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<axum::Json<Vec<EngineMember>>> {
+    let members = app_state.engines.get_engine_members(id).await?;
+    Ok(axum::Json(members))
+}
+
+#[utoipa::path(
+    put,
+    path = "/{id}/members/{user_id}",
+    tag = "engines",
+    params(
+        ("id" = Uuid, Path, description = "Engine id"),
+        ("user_id" = Uuid, Path, description = "Member user id"),
+    ),
+    request_body = UpdateEngineMemberRequest,
+    security(("bearer" = ["engines:publish"])),
+    responses(
+        (status = 204, description = "Role set"),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown engine or user", body = ErrorBody),
+        (status = 409, description = "Would leave the engine without owner", body = ErrorBody),
+    )
+)]
+async fn set_member(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+    axum::Json(payload): axum::Json<UpdateEngineMemberRequest>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>)> {
+    policy::authorize_engine(&app_state, &auth, id, EngineAction::ManageMembers).await?;
+
+    let previous = match app_state
+        .engines
+        .set_engine_member(id, user_id, payload.role)
+        .await
+        .map_err(|e| match &e {
+            sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+                ApiError::NotFound("User".into())
+            }
+            _ => e.into(),
+        })? {
+        MemberUpdate::Applied(previous) => previous,
+        MemberUpdate::LastOwner => return Err(last_owner()),
+    };
+    let change = AuditChange::new("engines", id)
+        .before(&json!({ user_id.to_string(): previous }))
+        .after(&json!({ user_id.to_string(): payload.role }));
+    Ok((StatusCode::NO_CONTENT, Extension(change)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}/members/{user_id}",
+    tag = "engines",
+    params(
+        ("id" = Uuid, Path, description = "Engine id"),
+        ("user_id" = Uuid, Path, description = "Member user id"),
+    ),
+    security(("bearer" = ["engines:publish"])),
+    responses(
+        (status = 204, description = "Member removed"),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Not a member", body = ErrorBody),
+        (status = 409, description = "Would leave the engine without owner", body = ErrorBody),
+    )
+)]
+async fn remove_member(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>)> {
+    policy::authorize_engine(&app_state, &auth, id, EngineAction::ManageMembers).await?;
+
+    match app_state.engines.remove_engine_member(id, user_id).await? {
+        MemberUpdate::Applied(Some(previous)) => {
+            let change = AuditChange::new("engines", id)
+                .before(&json!({ user_id.to_string(): previous }))
+                .after(&json!({ user_id.to_string(): null }));
+            Ok((StatusCode::NO_CONTENT, Extension(change)))
+        }
+        MemberUpdate::Applied(None) => Err(ApiError::NotFound("Engine member".into())),
+        MemberUpdate::LastOwner => Err(last_owner()),
+    }
+}
+
+/// An engine must always keep at least one owner, its last one can't be
+/// demoted or removed.
+fn last_owner() -> ApiError {
+    ApiError::Conflict("An engine must keep at least one owner".into())
+}
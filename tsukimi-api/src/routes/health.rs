@@ -0,0 +1,144 @@
+use std::collections::BTreeMap;
+
+use axum::{Json, extract::State, http::StatusCode};
+use serde::Serialize;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::AppState;
+
+/// Probes for the load balancer, mounted at the root.
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(healthz))
+        .routes(routes!(readyz))
+        .routes(routes!(version))
+}
+
+#[derive(Serialize, ToSchema)]
+struct Check {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl From<Result<(), String>> for Check {
+    fn from(result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => Check {
+                ok: true,
+                error: None,
+            },
+            Err(e) => Check {
+                ok: false,
+                error: Some(e),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+struct Readiness {
+    ready: bool,
+    checks: BTreeMap<&'static str, Check>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct Version {
+    name: &'static str,
+    version: &'static str,
+    commit: &'static str,
+    #[serde(with = "time::serde::rfc3339::option")]
+    built_at: Option<OffsetDateTime>,
+    profile: &'static str,
+    features: Vec<&'static str>,
+}
+
+/// The process is up, nothing else is checked so a database outage doesn't
+/// get every instance restarted.
+#[utoipa::path(get, path = "/healthz", tag = "meta", responses((status = 200, body = String)))]
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "meta",
+    responses(
+        (status = 200, description = "Every check passed", body = Readiness),
+        (status = 503, description = "At least one check failed", body = Readiness),
+    )
+)]
+async fn readyz(State(app_state): State<AppState>) -> (StatusCode, Json<Readiness>) {
+    let mut checks: BTreeMap<&'static str, Check> = BTreeMap::new();
+
+    checks.insert(
+        "shutdown",
+        match app_state.health.is_shutting_down() {
+            true => Err("Instance is shutting down".to_string()),
+            false => Ok(()),
+        }
+        .into(),
+    );
+
+    let database = app_state
+        .database
+        .ping()
+        .await
+        .map_err(|e| format!("Database unreachable: {}", e));
+    let reachable = database.is_ok();
+    checks.insert("database", database.into());
+
+    if reachable {
+        checks.insert(
+            "migrations",
+            match app_state.database.is_schema_current().await {
+                Ok(true) => Ok(()),
+                Ok(false) => Err("Database schema is not at the latest migration".to_string()),
+                Err(e) => Err(format!("Failed to read the applied migrations: {}", e)),
+            }
+            .into(),
+        );
+    }
+
+    checks.insert("storage", app_state.storage.check_writable().await.into());
+
+    checks.insert(
+        "oauth",
+        match app_state.oauth.is_configured() {
+            true => Ok(()),
+            false => Err("An identity provider has no OAuth client configured".to_string()),
+        }
+        .into(),
+    );
+
+    let ready = checks.values().all(|check| check.ok);
+    let status = match ready {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    (status, Json(Readiness { ready, checks }))
+}
+
+#[utoipa::path(get, path = "/version", tag = "meta", responses((status = 200, body = Version)))]
+async fn version() -> Json<Version> {
+    Json(Version {
+        name: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        commit: env!("TSUKIMI_GIT_COMMIT"),
+        built_at: env!("TSUKIMI_BUILD_TIMESTAMP")
+            .parse()
+            .ok()
+            .and_then(|timestamp| OffsetDateTime::from_unix_timestamp(timestamp).ok()),
+        profile: match cfg!(debug_assertions) {
+            true => "debug",
+            false => "release",
+        },
+        features: env!("TSUKIMI_FEATURES")
+            .split(',')
+            .filter(|feature| !feature.is_empty())
+            .collect(),
+    })
+}
@@ -0,0 +1,701 @@
+//! Requests through the whole router, with the engines, users and projects
+//! kept in memory.
+
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Method, Request, StatusCode, header},
+};
+use serde_json::{Value, json};
+use tower::ServiceExt;
+use tsukimi_core::{
+    auth::TokenScope,
+    models::{GlobalRole, ProjectVisibility, User},
+};
+use uuid::Uuid;
+
+use super::{
+    app,
+    tests::{offline_state, offline_state_with},
+};
+use crate::{
+    config::CorsOrigins,
+    services::{
+        repositories::memory::MemoryRepository,
+        session::{PERSONAL_ACCESS_TOKEN_PREFIX, SessionService},
+    },
+};
+
+struct TestApp {
+    router: Router,
+    repository: Arc<MemoryRepository>,
+    state: crate::AppState,
+}
+
+impl TestApp {
+    async fn new() -> Self {
+        TestApp::with_state(offline_state().await)
+    }
+
+    fn with_state(mut state: crate::AppState) -> Self {
+        let repository = Arc::new(MemoryRepository::default());
+        state.engines = repository.clone();
+        state.engine_versions = repository.clone();
+        state.users = repository.clone();
+        state.projects = repository.clone();
+        TestApp {
+            router: app(state.clone(), &CorsOrigins::Any),
+            repository,
+            state,
+        }
+    }
+
+    /// Opens a session for the user and returns its access token.
+    async fn login(&self, user: &User) -> String {
+        let session_id = self
+            .state
+            .users
+            .create_session(
+                user.id,
+                &SessionService::hash_secret(&user.username),
+                self.state.session.refresh_token_expiration(),
+            )
+            .await
+            .unwrap();
+        self.state
+            .session
+            .sign_access_token(user.id, session_id)
+            .unwrap()
+            .secret()
+            .to_string()
+    }
+
+    async fn request(
+        &self,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        (status, body)
+    }
+
+    async fn get(&self, uri: &str, token: Option<&str>) -> (StatusCode, Value) {
+        self.request(Method::GET, uri, token, None).await
+    }
+}
+
+#[tokio::test]
+async fn engines_are_searchable() {
+    let app = TestApp::new().await;
+    let owner = app.repository.insert_user("owner", GlobalRole::User);
+    app.repository
+        .insert_engine("kirikiri", "KiriKiri Z scenarios", owner.id);
+    app.repository
+        .insert_engine("renpy", "Ren'Py scripts", owner.id);
+
+    let (status, body) = app.get("/engines", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 2);
+
+    let (status, body) = app.get("/engines?query=scripts", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body[0]["name"], "renpy");
+    assert_eq!(body.as_array().unwrap().len(), 1);
+
+    let (status, _) = app
+        .get("/engines/00000000-0000-0000-0000-000000000000", None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn pages_are_validated() {
+    let app = TestApp::new().await;
+    let owner = app.repository.insert_user("owner", GlobalRole::User);
+    app.repository.insert_engine("kirikiri", "", owner.id);
+    app.repository.insert_engine("renpy", "", owner.id);
+
+    let (status, _) = app.get("/engines?page=0", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = app.get("/engines?per_page=0", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, first) = app.get("/engines?per_page=1", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, second) = app.get("/engines?per_page=1&page=2", None).await;
+    assert_eq!(first.as_array().unwrap().len(), 1);
+    assert_ne!(first[0]["name"], second[0]["name"]);
+
+    let (status, body) = app.get("/engines?per_page=100000", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn yanking_moves_the_current_version() {
+    let app = TestApp::new().await;
+    let owner = app.repository.insert_user("owner", GlobalRole::User);
+    let stranger = app.repository.insert_user("stranger", GlobalRole::User);
+    let engine = app.repository.insert_engine("kirikiri", "", owner.id);
+    app.repository.insert_version(engine.id, "1.0.0", "a");
+    app.repository.insert_version(engine.id, "1.1.0", "b");
+
+    let (status, body) = app
+        .get(&format!("/engines/{}/versions", engine.id), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 2);
+    // A fix of an older line, published last, is not the greatest version
+    app.repository.insert_version(engine.id, "0.9.1", "c");
+
+    let yank = format!("/engines/{}/versions/1.1.0/yank", engine.id);
+    let stranger_token = app.login(&stranger).await;
+    let (status, _) = app
+        .request(Method::POST, &yank, Some(&stranger_token), Some(json!({})))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let token = app.login(&owner).await;
+    let (status, body) = app
+        .request(
+            Method::POST,
+            &yank,
+            Some(&token),
+            Some(json!({ "reason": "Corrupts saves" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["yank_reason"], "Corrupts saves");
+    let (_, body) = app.get(&format!("/engines/{}", engine.id), None).await;
+    assert_eq!(body["current_version"], "1.0.0");
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/engines/{}/versions/1.1.0/unyank", engine.id),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    // Only an engine without current version gets the unyanked one back
+    let (_, body) = app.get(&format!("/engines/{}", engine.id), None).await;
+    assert_eq!(body["current_version"], "1.0.0");
+}
+
+#[tokio::test]
+async fn engines_keep_an_owner() {
+    let app = TestApp::new().await;
+    let owner = app.repository.insert_user("owner", GlobalRole::User);
+    let engine = app.repository.insert_engine("kirikiri", "", owner.id);
+    let token = app.login(&owner).await;
+
+    let member = format!("/engines/{}/members/{}", engine.id, owner.id);
+    let (status, _) = app
+        .request(Method::DELETE, &member, Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = app
+        .request(
+            Method::PUT,
+            &member,
+            Some(&token),
+            Some(json!({ "role": "maintainer" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let unknown = format!("/engines/{}/members/{}", engine.id, Uuid::new_v4());
+    let (status, _) = app
+        .request(
+            Method::PUT,
+            &unknown,
+            Some(&token),
+            Some(json!({ "role": "maintainer" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Once another owner is in, the first one may step down
+    let other = app.repository.insert_user("other", GlobalRole::User);
+    let other_member = format!("/engines/{}/members/{}", engine.id, other.id);
+    let (status, _) = app
+        .request(
+            Method::PUT,
+            &other_member,
+            Some(&token),
+            Some(json!({ "role": "owner" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = app
+        .request(
+            Method::PUT,
+            &member,
+            Some(&token),
+            Some(json!({ "role": "maintainer" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let other_token = app.login(&other).await;
+    let (status, _) = app
+        .request(Method::DELETE, &other_member, Some(&other_token), None)
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (_, body) = app
+        .get(&format!("/engines/{}/members", engine.id), None)
+        .await;
+    assert_eq!(body[0]["role"], "owner");
+    assert_eq!(body[0]["user_id"], other.id.to_string());
+}
+
+#[tokio::test]
+async fn private_projects_are_hidden() {
+    let app = TestApp::new().await;
+    let owner = app.repository.insert_user("owner", GlobalRole::User);
+    let stranger = app.repository.insert_user("stranger", GlobalRole::User);
+    let admin = app.repository.insert_user("admin", GlobalRole::Admin);
+    let public = app
+        .repository
+        .insert_project("Public", ProjectVisibility::Public, owner.id);
+    let private = app
+        .repository
+        .insert_project("Private", ProjectVisibility::Private, owner.id);
+
+    let (status, _) = app.get(&format!("/projects/{}", public.id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app.get(&format!("/projects/{}", private.id), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let stranger_token = app.login(&stranger).await;
+    let (status, _) = app
+        .get(&format!("/projects/{}", private.id), Some(&stranger_token))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, body) = app.get("/projects", Some(&stranger_token)).await;
+    assert_eq!(body.as_array().unwrap().len(), 1);
+
+    for user in [&owner, &admin] {
+        let token = app.login(user).await;
+        let (status, _) = app
+            .get(&format!("/projects/{}", private.id), Some(&token))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, body) = app.get("/projects", Some(&token)).await;
+        assert_eq!(body.as_array().unwrap().len(), 2);
+    }
+}
+
+#[tokio::test]
+async fn archived_projects_refuse_member_changes() {
+    let app = TestApp::new().await;
+    let owner = app.repository.insert_user("owner", GlobalRole::User);
+    let translator = app.repository.insert_user("translator", GlobalRole::User);
+    let project = app
+        .repository
+        .insert_project("Project", ProjectVisibility::Public, owner.id);
+    let token = app.login(&owner).await;
+
+    let (status, body) = app
+        .request(
+            Method::PATCH,
+            &format!("/projects/{}", project.id),
+            Some(&token),
+            Some(json!({ "name": "Renamed" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["name"], "Renamed");
+
+    let member = format!("/projects/{}/members/{}", project.id, translator.id);
+    let role = json!({ "role": "translator" });
+    let (status, _) = app
+        .request(Method::PUT, &member, Some(&token), Some(role.clone()))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/projects/{}/archive", project.id),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app
+        .request(Method::DELETE, &member, Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = app
+        .request(Method::PUT, &member, Some(&token), Some(role))
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn revoked_sessions_are_rejected() {
+    let app = TestApp::new().await;
+    let user = app.repository.insert_user("reader", GlobalRole::User);
+    let token = app.login(&user).await;
+
+    let (status, body) = app.get("/auth/me", Some(&token)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["username"], "reader");
+
+    let (status, _) = app
+        .request(Method::DELETE, "/auth/session", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = app.get("/auth/me", Some(&token)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn personal_access_tokens_are_limited_to_their_scopes() {
+    let app = TestApp::new().await;
+    let owner = app.repository.insert_user("owner", GlobalRole::User);
+    let project = app
+        .repository
+        .insert_project("Project", ProjectVisibility::Public, owner.id);
+    let uri = format!("/projects/{}", project.id);
+    let rename = json!({ "name": "Renamed" });
+
+    let token = app
+        .repository
+        .insert_personal_access_token(owner.id, &[TokenScope::ProjectsWrite]);
+    let (status, body) = app
+        .request(Method::PATCH, &uri, Some(&token), Some(rename.clone()))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["name"], "Renamed");
+    // Managing tokens needs an interactive session
+    let (status, _) = app.get("/tokens", Some(&token)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let publish_only = app
+        .repository
+        .insert_personal_access_token(owner.id, &[TokenScope::EnginesPublish]);
+    let (status, _) = app
+        .request(
+            Method::PATCH,
+            &uri,
+            Some(&publish_only),
+            Some(rename.clone()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = app
+        .request(Method::PATCH, &uri, Some("tsk_pat_unknown"), Some(rename))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn refresh_tokens_are_single_use() {
+    let app = TestApp::new().await;
+    let user = app.repository.insert_user("reader", GlobalRole::User);
+    // `login` stores the hash of the username as refresh token
+    app.login(&user).await;
+
+    let refresh = json!({ "refresh_token": "reader" });
+    let (status, body) = app
+        .request(Method::POST, "/auth/refresh", None, Some(refresh.clone()))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let token = body["access_token"].as_str().unwrap();
+    let (status, _) = app.get("/auth/me", Some(token)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .request(Method::POST, "/auth/refresh", None, Some(refresh))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/auth/refresh",
+            None,
+            Some(json!({ "refresh_token": body["refresh_token"] })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn identities_keep_a_login() {
+    let app = TestApp::new().await;
+    let user = app.repository.insert_user("reader", GlobalRole::User);
+    app.repository.insert_identity(user.id, "github", "1");
+    app.repository.insert_identity(user.id, "gitlab", "2");
+    let token = app.login(&user).await;
+
+    let (status, body) = app.get("/auth/identities", Some(&token)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 2);
+
+    let (status, _) = app
+        .request(
+            Method::DELETE,
+            "/auth/identities/gitlab",
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = app
+        .request(
+            Method::DELETE,
+            "/auth/identities/gitlab",
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = app
+        .request(
+            Method::DELETE,
+            "/auth/identities/github",
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn only_sessions_start_a_link() {
+    let app = TestApp::new().await;
+    let user = app.repository.insert_user("reader", GlobalRole::User);
+    let token = app
+        .repository
+        .insert_personal_access_token(user.id, &[TokenScope::ProjectsWrite]);
+
+    let uri = "/oauth/github/authorize-url?purpose=link";
+    let (status, _) = app.get(uri, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = app.get(uri, Some(&token)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn device_sessions_need_a_known_provider() {
+    let app = TestApp::new().await;
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/auth/device-session",
+            None,
+            Some(json!({ "provider": "unknown", "access_token": "gho_token" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn personal_access_tokens_are_revocable() {
+    let app = TestApp::new().await;
+    let user = app.repository.insert_user("reader", GlobalRole::User);
+    let session = app.login(&user).await;
+
+    let (status, created) = app
+        .request(
+            Method::POST,
+            "/tokens",
+            Some(&session),
+            Some(json!({ "name": "ci", "scopes": ["projects:write"] })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let secret = created["secret"].as_str().unwrap();
+    let (status, _) = app.get("/auth/me", Some(secret)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, tokens) = app.get("/tokens", Some(&session)).await;
+    assert_eq!(tokens.as_array().unwrap().len(), 1);
+
+    let uri = format!("/tokens/{}", created["id"].as_str().unwrap());
+    let (status, _) = app
+        .request(Method::DELETE, &uri, Some(&session), None)
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = app.get("/auth/me", Some(secret)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (_, tokens) = app.get("/tokens", Some(&session)).await;
+    assert!(tokens.as_array().unwrap().is_empty());
+    let (status, _) = app
+        .request(Method::DELETE, &uri, Some(&session), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn oauth_states_are_single_use() {
+    let app = TestApp::new().await;
+    let user = app.repository.insert_user("reader", GlobalRole::User);
+    let session = app.login(&user).await;
+
+    let (status, body) = app.get("/oauth/github/authorize-url", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let exchange = json!({ "code": "code", "state": body["state"] });
+
+    // A login authorization can't link an account, and is spent trying
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/auth/identities",
+            Some(&session),
+            Some(exchange.clone()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, body) = app
+        .request(Method::POST, "/auth/session", None, Some(exchange))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Bad request: Unknown or expired OAuth state");
+}
+
+#[tokio::test]
+async fn versions_are_signed_with_an_own_publisher_key() {
+    let app = TestApp::new().await;
+    let owner = app.repository.insert_user("owner", GlobalRole::User);
+    let stranger = app.repository.insert_user("stranger", GlobalRole::User);
+    let engine = app.repository.insert_engine("kirikiri", "", owner.id);
+    let own_key = app.repository.insert_publisher_key(owner.id);
+    let other_key = app.repository.insert_publisher_key(stranger.id);
+    let token = app.login(&owner).await;
+
+    let publish = |key_id: Uuid| {
+        let request = Request::builder()
+            .method(Method::PUT)
+            .uri(format!("/engines/{}/versions/1.0.0", engine.id))
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header(header::CONTENT_TYPE, "application/wasm")
+            .header("x-signature", "c2lnbmF0dXJl")
+            .header("x-publisher-key", key_id.to_string())
+            .body(Body::from("\0asm"))
+            .unwrap();
+        let router = app.router.clone();
+        async move {
+            let response = router.oneshot(request).await.unwrap();
+            let status = response.status();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body: Value = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+            (
+                status,
+                body["error"].as_str().unwrap_or_default().to_string(),
+            )
+        }
+    };
+
+    for key_id in [other_key.id, Uuid::new_v4()] {
+        let (status, error) = publish(key_id).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(error.contains("publisher key"), "{}", error);
+    }
+    // The key is accepted, the test signature is not
+    let (status, error) = publish(own_key.id).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(error.contains("Invalid signature"), "{}", error);
+}
+
+#[tokio::test]
+async fn projects_translate_a_known_visual_novel() {
+    let app = TestApp::new().await;
+    let user = app.repository.insert_user("translator", GlobalRole::User);
+    let visual_novel = app.repository.insert_visual_novel("Tsukihime", "ja");
+    let token = app.login(&user).await;
+    let project = |visual_novel_id: Uuid| {
+        json!({
+            "name": "Tsukihime EN",
+            "visual_novel_id": visual_novel_id,
+            "target_languages": ["en"],
+            "visibility": "public",
+        })
+    };
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/projects",
+            Some(&token),
+            Some(project(Uuid::new_v4())),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, body) = app
+        .request(
+            Method::POST,
+            "/projects",
+            Some(&token),
+            Some(project(visual_novel.id)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["source_language"], "ja");
+}
+
+#[tokio::test]
+async fn tokens_share_the_budget_of_their_user() {
+    let app =
+        TestApp::with_state(offline_state_with(json!({ "ratelimit": { "enabled": true } })).await);
+    let user = app.repository.insert_user("user", GlobalRole::User);
+    let first = app.repository.insert_personal_access_token(user.id, &[]);
+    let second = app.repository.insert_personal_access_token(user.id, &[]);
+    let other = app.repository.insert_user("other", GlobalRole::User);
+    let other = app.repository.insert_personal_access_token(other.id, &[]);
+
+    let remaining = |token: String| {
+        let request = Request::builder()
+            .uri("/engines")
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+        let router = app.router.clone();
+        async move {
+            let response = router.oneshot(request).await.unwrap();
+            response.headers()["ratelimit-remaining"]
+                .to_str()
+                .unwrap()
+                .parse::<u32>()
+                .unwrap()
+        }
+    };
+    let budget = remaining(first).await;
+    assert_eq!(remaining(second).await, budget - 1);
+    assert_eq!(remaining(other).await, budget);
+    // A made up token counts against the address, not a bucket of its own
+    assert_eq!(
+        remaining(format!("{}unknown", PERSONAL_ACCESS_TOKEN_PREFIX)).await,
+        budget
+    );
+    assert_eq!(
+        remaining(format!("{}other", PERSONAL_ACCESS_TOKEN_PREFIX)).await,
+        budget - 1
+    );
+}
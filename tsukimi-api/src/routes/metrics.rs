@@ -0,0 +1,26 @@
+use axum::{extract::State, http::header, response::IntoResponse};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{AppState, services::metrics};
+
+static PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(get_metrics))
+}
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "meta",
+    responses((status = 200, description = "Prometheus text exposition", body = String, content_type = "text/plain"))
+)]
+async fn get_metrics(State(app_state): State<AppState>) -> impl IntoResponse {
+    let (size, idle, max) = app_state.database.pool_usage();
+    metrics::record_pool(size, idle, max);
+
+    (
+        [(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+        app_state.metrics.render(),
+    )
+}
@@ -1,26 +1,260 @@
-use axum::{Router, extract::State};
+use std::sync::Arc;
 
-use crate::AppState;
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use oauth2::PkceCodeVerifier;
+use serde::Deserialize;
+use time::OffsetDateTime;
+use tsukimi_core::auth::{
+    IdentityProviderInfo, OauthAuthorizeUrlResponse, OauthExchangeCodeRequest,
+    OauthExchangeCodeResponse, OauthRefreshRequest, OauthRevokeRequest,
+};
+use utoipa::{IntoParams, ToSchema};
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
 
-pub fn get_router() -> Router<AppState> {
-    Router::new().route(
-        "/github/exchange-code",
-        axum::routing::post(exchange_code_handler),
+use crate::{
+    AppState,
+    error::{ApiError, ApiResult, ErrorBody},
+    middleware::auth::AuthUser,
+    services::{
+        database::{NewIdentity, OauthState},
+        oauth::{IdentityProvider, ProviderTokens},
+    },
+};
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+enum AuthorizationPurpose {
+    /// Log in, or sign up, with the account
+    #[default]
+    Login,
+    /// Link the account to the caller, who must have a session
+    Link,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct AuthorizeUrlParams {
+    /// `link` to link the account to the caller instead of logging in
+    #[serde(default)]
+    #[param(inline)]
+    purpose: AuthorizationPurpose,
+}
+
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(providers_handler))
+        .routes(routes!(authorize_url_handler))
+        .routes(routes!(exchange_code_handler))
+        .routes(routes!(refresh_handler))
+        .routes(routes!(revoke_handler))
+}
+
+/// Identity providers users can log in with, in the configured order.
+#[utoipa::path(
+    get,
+    path = "/providers",
+    tag = "oauth",
+    responses((status = 200, body = Vec<IdentityProviderInfo>))
+)]
+async fn providers_handler(State(app_state): State<AppState>) -> Json<Vec<IdentityProviderInfo>> {
+    Json(app_state.oauth.providers())
+}
+
+/// Starts an authorization. The PKCE verifier is kept server side, the client
+/// only sends back the `state` along the code the provider redirected it with.
+/// An authorization to link an account is bound to the caller, only they can
+/// complete it and it can't open a session.
+#[utoipa::path(
+    get,
+    path = "/{provider}/authorize-url",
+    tag = "oauth",
+    params(
+        ("provider" = String, Path, description = "Identity provider id"),
+        AuthorizeUrlParams,
+    ),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, body = OauthAuthorizeUrlResponse),
+        (status = 401, description = "Linking an account without a session", body = ErrorBody),
+        (status = 403, description = "Linking an account with a personal access token", body = ErrorBody),
+        (status = 404, description = "Unknown identity provider", body = ErrorBody),
+        (status = 502, description = "The provider is unreachable", body = ErrorBody),
     )
+)]
+async fn authorize_url_handler(
+    State(app_state): State<AppState>,
+    auth: Option<AuthUser>,
+    Path(provider_id): Path<String>,
+    Query(params): Query<AuthorizeUrlParams>,
+) -> ApiResult<Json<OauthAuthorizeUrlResponse>> {
+    let user_id = match params.purpose {
+        AuthorizationPurpose::Login => None,
+        AuthorizationPurpose::Link => {
+            let auth = auth.ok_or_else(|| {
+                ApiError::Unauthorized("Linking an account requires a session".into())
+            })?;
+            auth.require_session()?;
+            Some(auth.user_id)
+        }
+    };
+    let provider = find_provider(&app_state, &provider_id)?;
+    let authorization = provider.authorize().await.map_err(ApiError::Upstream)?;
+    app_state
+        .users
+        .create_oauth_state(
+            authorization.state.secret(),
+            &OauthState {
+                provider: provider_id,
+                pkce_verifier: authorization.pkce_verifier.secret().clone(),
+                nonce: authorization.nonce,
+                user_id,
+            },
+            OffsetDateTime::now_utc() + app_state.oauth.state_ttl(),
+        )
+        .await?;
+    Ok(Json(OauthAuthorizeUrlResponse {
+        authorize_url: authorization.url,
+        state: authorization.state,
+        client_id: provider.client_id().to_string(),
+    }))
 }
 
+/// Exchanges an authorization code and returns the provider token as is.
+#[utoipa::path(
+    post,
+    path = "/{provider}/exchange-code",
+    tag = "oauth",
+    params(("provider" = String, Path, description = "Identity provider id")),
+    request_body = OauthExchangeCodeRequest,
+    responses(
+        (status = 200, body = OauthExchangeCodeResponse),
+        (status = 400, description = "Unknown, expired or spent state, or started to link an account", body = ErrorBody),
+        (status = 401, description = "The provider refused the code", body = ErrorBody),
+        (status = 404, description = "Unknown identity provider", body = ErrorBody),
+    )
+)]
 async fn exchange_code_handler(
     State(app_state): State<AppState>,
-    axum::extract::Json(payload): axum::extract::Json<tsukimi_core::auth::OauthExchangeCodeRequest>,
-) -> Result<axum::Json<tsukimi_core::auth::OauthExchangeCodeResponse>, &'static str> {
-    let tsukimi_core::auth::OauthExchangeCodeRequest {
-        code,
-        pkce_code_verifier,
-    } = payload;
-    let response = app_state
+    Path(provider_id): Path<String>,
+    Json(payload): Json<OauthExchangeCodeRequest>,
+) -> ApiResult<Json<OauthExchangeCodeResponse>> {
+    find_provider(&app_state, &provider_id)?;
+    let (_, tokens, _) = exchange_code(&app_state, Some(&provider_id), None, payload).await?;
+    Ok(Json(tokens.tokens))
+}
+
+/// Trades a provider refresh token, as returned by the code exchange, for a
+/// new token pair of the same provider.
+#[utoipa::path(
+    post,
+    path = "/{provider}/refresh",
+    tag = "oauth",
+    params(("provider" = String, Path, description = "Identity provider id")),
+    request_body = OauthRefreshRequest,
+    responses(
+        (status = 200, body = OauthExchangeCodeResponse),
+        (status = 401, description = "The provider refused the refresh token", body = ErrorBody),
+        (status = 404, description = "Unknown identity provider", body = ErrorBody),
+    )
+)]
+async fn refresh_handler(
+    State(app_state): State<AppState>,
+    Path(provider_id): Path<String>,
+    Json(payload): Json<OauthRefreshRequest>,
+) -> ApiResult<Json<OauthExchangeCodeResponse>> {
+    find_provider(&app_state, &provider_id)?
+        .refresh(&payload.refresh_token)
+        .await
+        .map(Json)
+        .map_err(|_| ApiError::Unauthorized("Error refreshing token".into()))
+}
+
+/// Revokes a provider token returned by the code exchange or a refresh.
+#[utoipa::path(
+    post,
+    path = "/{provider}/revoke",
+    tag = "oauth",
+    params(("provider" = String, Path, description = "Identity provider id")),
+    request_body = OauthRevokeRequest,
+    responses(
+        (status = 204, description = "Token revoked"),
+        (status = 404, description = "Unknown identity provider", body = ErrorBody),
+        (status = 502, description = "The provider is unreachable", body = ErrorBody),
+    )
+)]
+async fn revoke_handler(
+    State(app_state): State<AppState>,
+    Path(provider_id): Path<String>,
+    Json(payload): Json<OauthRevokeRequest>,
+) -> ApiResult<StatusCode> {
+    find_provider(&app_state, &provider_id)?
+        .revoke(&payload.access_token)
+        .await
+        .map_err(ApiError::Upstream)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn find_provider(app_state: &AppState, provider_id: &str) -> ApiResult<Arc<dyn IdentityProvider>> {
+    app_state
         .oauth
-        .exchange_code(code, pkce_code_verifier)
+        .provider(provider_id)
+        .ok_or_else(|| ApiError::NotFound("Identity provider".into()))
+}
+
+/// Spends the state of an authorization started by `authorize-url` and
+/// exchanges the code with its PKCE verifier. The provider is the one the
+/// authorization was started with, `expected_provider` only checks it.
+/// `user_id` is the user linking an account, `None` for a login, and must be
+/// the one the authorization was started for.
+async fn exchange_code(
+    app_state: &AppState,
+    expected_provider: Option<&str>,
+    user_id: Option<Uuid>,
+    payload: OauthExchangeCodeRequest,
+) -> ApiResult<(Arc<dyn IdentityProvider>, ProviderTokens, OauthState)> {
+    let OauthExchangeCodeRequest { code, state } = payload;
+    let oauth_state = app_state
+        .users
+        .take_oauth_state(state.secret())
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Unknown or expired OAuth state".into()))?;
+    if expected_provider.is_some_and(|provider| provider != oauth_state.provider) {
+        return Err(ApiError::BadRequest(
+            "OAuth state was issued for another provider".into(),
+        ));
+    }
+    if oauth_state.user_id != user_id {
+        return Err(ApiError::BadRequest(
+            "OAuth state was issued for another purpose".into(),
+        ));
+    }
+    let provider = find_provider(app_state, &oauth_state.provider)?;
+    let tokens = provider
+        .exchange_code(
+            code,
+            PkceCodeVerifier::new(oauth_state.pkce_verifier.clone()),
+        )
+        .await
+        .map_err(|_| ApiError::Unauthorized("Error exchanging code".into()))?;
+    Ok((provider, tokens, oauth_state))
+}
+
+/// Exchanges the code of an authorization and reads the account it was
+/// granted by. The provider token is dropped. `user_id` is the user linking
+/// the account, `None` for a login.
+pub(crate) async fn identify(
+    app_state: &AppState,
+    user_id: Option<Uuid>,
+    payload: OauthExchangeCodeRequest,
+) -> ApiResult<NewIdentity> {
+    let (provider, tokens, oauth_state) = exchange_code(app_state, None, user_id, payload).await?;
+    provider
+        .identify(&tokens, oauth_state.nonce.as_deref())
         .await
-        .map_err(|_| "Error exchanging code")?;
-    Ok(axum::Json(response))
+        .map_err(ApiError::Upstream)
 }
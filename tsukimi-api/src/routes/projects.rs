@@ -0,0 +1,392 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde_json::json;
+use tsukimi_core::{
+    auth::TokenScope,
+    models::{
+        CreateProjectRequest, Project, ProjectMember, UpdateProjectMemberRequest,
+        UpdateProjectRequest,
+    },
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{ApiError, ApiResult, ErrorBody},
+    middleware::{audit::AuditChange, auth::AuthUser},
+    policy::{self, ProjectAction},
+    services::database::{ApiPagination, MemberUpdate, ProjectFilter},
+};
+
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_projects, create_project))
+        .routes(routes!(get_project, update_project))
+        .routes(routes!(archive_project))
+        .routes(routes!(unarchive_project))
+        .routes(routes!(get_members))
+        .routes(routes!(set_member, remove_member))
+}
+
+/// Public projects, plus the private ones the caller is a member of.
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "projects",
+    params(ApiPagination, ProjectFilter),
+    security((), ("bearer" = [])),
+    responses((status = 200, body = Vec<Project>))
+)]
+async fn get_projects(
+    State(app_state): State<AppState>,
+    auth: Option<AuthUser>,
+    Query(pagination): Query<ApiPagination>,
+    Query(filter): Query<ProjectFilter>,
+) -> ApiResult<Json<Vec<Project>>> {
+    let is_admin = match &auth {
+        Some(auth) => policy::is_admin(&app_state, auth).await?,
+        None => false,
+    };
+    let list = app_state
+        .projects
+        .get_projects(pagination, filter, auth.map(|auth| auth.user_id), is_admin)
+        .await?;
+    Ok(Json(list))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "projects",
+    params(("id" = Uuid, Path, description = "Project id")),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, body = Project),
+        (status = 404, description = "Unknown or hidden project", body = ErrorBody),
+    )
+)]
+async fn get_project(
+    State(app_state): State<AppState>,
+    auth: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Project>> {
+    policy::authorize_project(&app_state, auth.as_ref(), id, ProjectAction::View).await?;
+    app_state
+        .projects
+        .get_project(id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound("Project".into()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "projects",
+    request_body = CreateProjectRequest,
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 201, body = Project),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+    )
+)]
+async fn create_project(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Json(payload): Json<CreateProjectRequest>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>, Json<Project>)> {
+    auth.require_scope(TokenScope::ProjectsWrite)?;
+
+    let visual_novel = app_state
+        .projects
+        .get_visual_novel(payload.visual_novel_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Unknown visual novel".into()))?;
+    let source_language = payload
+        .source_language
+        .clone()
+        .unwrap_or(visual_novel.original_language);
+
+    validate_name(&payload.name)?;
+    validate_languages(&source_language, &payload.target_languages)?;
+
+    let project = app_state
+        .projects
+        .create_project(&payload, &source_language, auth.user_id)
+        .await
+        .map_err(bad_engine_version)?;
+    let change = AuditChange::new("projects", project.id).after(&project);
+    Ok((StatusCode::CREATED, Extension(change), Json(project)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    tag = "projects",
+    params(("id" = Uuid, Path, description = "Project id")),
+    request_body = UpdateProjectRequest,
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = Project),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown or hidden project", body = ErrorBody),
+        (status = 409, description = "Project is archived", body = ErrorBody),
+    )
+)]
+async fn update_project(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateProjectRequest>,
+) -> ApiResult<(Extension<AuditChange>, Json<Project>)> {
+    policy::authorize_project(&app_state, Some(&auth), id, ProjectAction::Update).await?;
+
+    let project = app_state
+        .projects
+        .get_project(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Project".into()))?;
+    if let Some(name) = &payload.name {
+        validate_name(name)?;
+    }
+    if let Some(target_languages) = &payload.target_languages {
+        validate_languages(&project.source_language, target_languages)?;
+    }
+
+    let updated = app_state
+        .projects
+        .update_project(id, &payload)
+        .await
+        .map_err(bad_engine_version)?
+        .ok_or_else(|| ApiError::NotFound("Project".into()))?;
+    let change = AuditChange::new("projects", id)
+        .before(&project)
+        .after(&updated);
+    Ok((Extension(change), Json(updated)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/archive",
+    tag = "projects",
+    params(("id" = Uuid, Path, description = "Project id")),
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = Project),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown or hidden project", body = ErrorBody),
+    )
+)]
+async fn archive_project(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Project>> {
+    set_archived(app_state, auth, id, true).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/unarchive",
+    tag = "projects",
+    params(("id" = Uuid, Path, description = "Project id")),
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = Project),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown or hidden project", body = ErrorBody),
+    )
+)]
+async fn unarchive_project(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Project>> {
+    set_archived(app_state, auth, id, false).await
+}
+
+async fn set_archived(
+    app_state: AppState,
+    auth: AuthUser,
+    id: Uuid,
+    archived: bool,
+) -> ApiResult<Json<Project>> {
+    policy::authorize_project(&app_state, Some(&auth), id, ProjectAction::Update).await?;
+    app_state
+        .projects
+        .set_project_archived(id, archived)
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound("Project".into()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/members",
+    tag = "projects",
+    params(("id" = Uuid, Path, description = "Project id")),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, body = Vec<ProjectMember>),
+        (status = 404, description = "Unknown or hidden project", body = ErrorBody),
+    )
+)]
+async fn get_members(
+    State(app_state): State<AppState>,
+    auth: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Vec<ProjectMember>>> {
+    policy::authorize_project(&app_state, auth.as_ref(), id, ProjectAction::View).await?;
+    let members = app_state.projects.get_project_members(id).await?;
+    Ok(Json(members))
+}
+
+#[utoipa::path(
+    put,
+    path = "/{id}/members/{user_id}",
+    tag = "projects",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("user_id" = Uuid, Path, description = "Member user id"),
+    ),
+    request_body = UpdateProjectMemberRequest,
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 204, description = "Role set"),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown project or user", body = ErrorBody),
+        (status = 409, description = "Would leave the project without owner", body = ErrorBody),
+    )
+)]
+async fn set_member(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateProjectMemberRequest>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>)> {
+    policy::authorize_project(&app_state, Some(&auth), id, ProjectAction::ManageMembers).await?;
+
+    let previous = match app_state
+        .projects
+        .set_project_member(id, user_id, payload.role)
+        .await
+        .map_err(|e| match &e {
+            sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+                ApiError::NotFound("User".into())
+            }
+            _ => e.into(),
+        })? {
+        MemberUpdate::Applied(previous) => previous,
+        MemberUpdate::LastOwner => return Err(last_owner()),
+    };
+    let change = AuditChange::new("projects", id)
+        .before(&json!({ user_id.to_string(): previous }))
+        .after(&json!({ user_id.to_string(): payload.role }));
+    Ok((StatusCode::NO_CONTENT, Extension(change)))
+}
+
+/// Members can always remove themselves, managers can remove anyone.
+#[utoipa::path(
+    delete,
+    path = "/{id}/members/{user_id}",
+    tag = "projects",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("user_id" = Uuid, Path, description = "Member user id"),
+    ),
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 204, description = "Member removed"),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Not a member", body = ErrorBody),
+        (status = 409, description = "Would leave the project without owner", body = ErrorBody),
+    )
+)]
+async fn remove_member(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>)> {
+    // Members may always leave a project on their own
+    if user_id != auth.user_id {
+        policy::authorize_project(&app_state, Some(&auth), id, ProjectAction::ManageMembers)
+            .await?;
+    }
+
+    match app_state
+        .projects
+        .remove_project_member(id, user_id)
+        .await?
+    {
+        MemberUpdate::Applied(Some(previous)) => {
+            let change = AuditChange::new("projects", id)
+                .before(&json!({ user_id.to_string(): previous }))
+                .after(&json!({ user_id.to_string(): null }));
+            Ok((StatusCode::NO_CONTENT, Extension(change)))
+        }
+        MemberUpdate::Applied(None) => Err(ApiError::NotFound("Project member".into())),
+        MemberUpdate::LastOwner => Err(last_owner()),
+    }
+}
+
+/// A project must always keep at least one owner, its last one can't be
+/// demoted or removed.
+fn last_owner() -> ApiError {
+    ApiError::Conflict("A project must keep at least one owner".into())
+}
+
+fn validate_name(name: &str) -> ApiResult<()> {
+    match name.trim().len() {
+        1..=100 => Ok(()),
+        _ => Err(ApiError::BadRequest(
+            "Project name must be between 1 and 100 characters".into(),
+        )),
+    }
+}
+
+fn validate_languages(source_language: &str, target_languages: &[String]) -> ApiResult<()> {
+    if target_languages.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one target language is required".into(),
+        ));
+    }
+    if let Some(language) = target_languages
+        .iter()
+        .find(|language| language.is_empty() || language.len() > 35)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Invalid language tag `{}`",
+            language
+        )));
+    }
+    if target_languages
+        .iter()
+        .any(|language| language == source_language)
+    {
+        return Err(ApiError::BadRequest(
+            "Target languages must differ from the source language".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn bad_engine_version(error: sqlx::Error) -> ApiError {
+    match &error {
+        sqlx::Error::Database(e) if e.is_foreign_key_violation() => {
+            ApiError::BadRequest("Unknown engine version".into())
+        }
+        _ => error.into(),
+    }
+}
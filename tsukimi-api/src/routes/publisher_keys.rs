@@ -0,0 +1,148 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use tsukimi_core::{
+    models::{PublisherKey, RegisterPublisherKeyRequest},
+    signing,
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{ApiError, ApiResult, ErrorBody},
+    middleware::{audit::AuditChange, auth::AuthUser},
+};
+
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(list_keys, register_key))
+        .routes(routes!(get_key, revoke_key))
+}
+
+/// Keys of the caller, revoked ones included.
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "publisher-keys",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, body = Vec<PublisherKey>),
+        (status = 401, body = ErrorBody),
+        (status = 403, description = "Called with a personal access token", body = ErrorBody),
+    )
+)]
+async fn list_keys(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+) -> ApiResult<Json<Vec<PublisherKey>>> {
+    auth.require_session()?;
+    let keys = app_state.database.get_publisher_keys(auth.user_id).await?;
+    Ok(Json(keys))
+}
+
+/// Registers a public key the caller will sign their releases with. The
+/// private key never leaves the publisher.
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "publisher-keys",
+    request_body = RegisterPublisherKeyRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 201, body = PublisherKey),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, description = "Called with a personal access token", body = ErrorBody),
+        (status = 409, description = "Key already registered", body = ErrorBody),
+    )
+)]
+async fn register_key(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Json(payload): Json<RegisterPublisherKeyRequest>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>, Json<PublisherKey>)> {
+    auth.require_session()?;
+
+    let name = payload.name.trim();
+    if name.is_empty() || name.len() > 100 {
+        return Err(ApiError::BadRequest(
+            "Key name must be between 1 and 100 characters".into(),
+        ));
+    }
+    let raw_key = signing::decode_public_key(&payload.public_key)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid key: {}", e)))?;
+
+    let key = app_state
+        .database
+        .create_publisher_key(
+            auth.user_id,
+            name,
+            payload.public_key.trim(),
+            &signing::fingerprint(&raw_key),
+        )
+        .await
+        .map_err(|e| match &e {
+            sqlx::Error::Database(db) if db.is_unique_violation() => {
+                ApiError::Conflict("This key is already registered".into())
+            }
+            _ => e.into(),
+        })?;
+    let change = AuditChange::new("publisher_keys", key.id).after(&key);
+    Ok((StatusCode::CREATED, Extension(change), Json(key)))
+}
+
+/// Public, anyone can fetch the key a release was signed with.
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "publisher-keys",
+    params(("id" = Uuid, Path, description = "Key id")),
+    responses(
+        (status = 200, body = PublisherKey),
+        (status = 404, body = ErrorBody),
+    )
+)]
+async fn get_key(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<PublisherKey>> {
+    app_state
+        .database
+        .get_publisher_key(id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound("Publisher key".into()))
+}
+
+/// Revoking a key refuses new releases signed with it, and makes the CLI
+/// refuse to install the ones already signed with it.
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "publisher-keys",
+    params(("id" = Uuid, Path, description = "Key id")),
+    security(("bearer" = [])),
+    responses(
+        (status = 204, description = "Key revoked"),
+        (status = 401, body = ErrorBody),
+        (status = 403, description = "Called with a personal access token", body = ErrorBody),
+        (status = 404, description = "Unknown or already revoked key", body = ErrorBody),
+    )
+)]
+async fn revoke_key(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>)> {
+    auth.require_session()?;
+    let key = app_state
+        .database
+        .revoke_publisher_key(auth.user_id, id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Publisher key".into()))?;
+    let change = AuditChange::new("publisher_keys", id).after(&key);
+    Ok((StatusCode::NO_CONTENT, Extension(change)))
+}
@@ -0,0 +1,278 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde_json::json;
+use tsukimi_core::models::{
+    CreateSuggestionRequest, ReviewDecisionRequest, ReviewQueueItem, ReviewTranslationRequest,
+    Suggestion, SuggestionStatus, Translation,
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{ApiError, ApiResult, ErrorBody},
+    middleware::{audit::AuditChange, auth::AuthUser},
+    policy::{self, ProjectAction},
+    routes::units::{ensure_target_language, ensure_unit},
+    services::database::{ApiPagination, ReviewQueueFilter, TranslationUpdate},
+};
+
+/// Routes nested under `/projects`.
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_suggestions, create_suggestion))
+        .routes(routes!(review_translation))
+        .routes(routes!(approve_suggestion))
+        .routes(routes!(reject_suggestion))
+        .routes(routes!(get_review_queue))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/units/{unit_id}/translations/{language}/suggestions",
+    tag = "reviews",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("unit_id" = Uuid, Path, description = "Unit id"),
+        ("language" = String, Path, description = "Target language, BCP 47 tag"),
+    ),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, body = Vec<Suggestion>),
+        (status = 404, description = "Unknown unit", body = ErrorBody),
+    )
+)]
+async fn get_suggestions(
+    State(app_state): State<AppState>,
+    auth: Option<AuthUser>,
+    Path((id, unit_id, language)): Path<(Uuid, Uuid, String)>,
+) -> ApiResult<Json<Vec<Suggestion>>> {
+    policy::authorize_project(&app_state, auth.as_ref(), id, ProjectAction::View).await?;
+    ensure_unit(&app_state, id, unit_id).await?;
+    let suggestions = app_state
+        .database
+        .get_suggestions(unit_id, &language)
+        .await?;
+    Ok(Json(suggestions))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/units/{unit_id}/translations/{language}/suggestions",
+    tag = "reviews",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("unit_id" = Uuid, Path, description = "Unit id"),
+        ("language" = String, Path, description = "Target language, BCP 47 tag"),
+    ),
+    request_body = CreateSuggestionRequest,
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 201, body = Suggestion),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown unit", body = ErrorBody),
+    )
+)]
+async fn create_suggestion(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, unit_id, language)): Path<(Uuid, Uuid, String)>,
+    Json(payload): Json<CreateSuggestionRequest>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>, Json<Suggestion>)> {
+    policy::authorize_project(&app_state, Some(&auth), id, ProjectAction::Translate).await?;
+    ensure_unit(&app_state, id, unit_id).await?;
+    ensure_target_language(&app_state, id, &language).await?;
+    if payload.text.trim().is_empty() {
+        return Err(ApiError::BadRequest("Suggestion text is required".into()));
+    }
+
+    let suggestion = app_state
+        .database
+        .create_suggestion(unit_id, &language, &payload.text, auth.user_id)
+        .await?;
+    let change = AuditChange::new("suggestions", suggestion.id).after(&suggestion);
+    Ok((StatusCode::CREATED, Extension(change), Json(suggestion)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/{id}/units/{unit_id}/translations/{language}/review",
+    tag = "reviews",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("unit_id" = Uuid, Path, description = "Unit id"),
+        ("language" = String, Path, description = "Target language, BCP 47 tag"),
+    ),
+    request_body = ReviewTranslationRequest,
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = Translation),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown unit or not translated yet", body = ErrorBody),
+    )
+)]
+async fn review_translation(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, unit_id, language)): Path<(Uuid, Uuid, String)>,
+    Json(payload): Json<ReviewTranslationRequest>,
+) -> ApiResult<(Extension<AuditChange>, Json<Translation>)> {
+    policy::authorize_project(&app_state, Some(&auth), id, ProjectAction::Review).await?;
+    ensure_unit(&app_state, id, unit_id).await?;
+    let previous = app_state
+        .database
+        .get_translation(unit_id, &language)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Translation".into()))?;
+    let translation = app_state
+        .database
+        .set_review_state(unit_id, &language, payload.state, auth.user_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Translation".into()))?;
+    let change = AuditChange::new("translations", format!("{}/{}", unit_id, language))
+        .before(&previous)
+        .after(&translation);
+    Ok((Extension(change), Json(translation)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/suggestions/{suggestion_id}/approve",
+    tag = "reviews",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("suggestion_id" = Uuid, Path, description = "Suggestion id"),
+    ),
+    request_body(content = Option<ReviewDecisionRequest>, description = "Optional reviewer comment"),
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = Translation),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown suggestion", body = ErrorBody),
+        (status = 409, description = "Suggestion has already been reviewed, or the translation changed since it was made", body = ErrorBody),
+    )
+)]
+async fn approve_suggestion(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, suggestion_id)): Path<(Uuid, Uuid)>,
+    payload: Option<Json<ReviewDecisionRequest>>,
+) -> ApiResult<(Extension<AuditChange>, Json<Translation>)> {
+    policy::authorize_project(&app_state, Some(&auth), id, ProjectAction::Review).await?;
+    let suggestion = ensure_suggestion(&app_state, id, suggestion_id).await?;
+    let Json(payload) = payload.unwrap_or_default();
+    let previous = app_state
+        .database
+        .get_translation(suggestion.unit_id, &suggestion.language)
+        .await?;
+
+    match app_state
+        .database
+        .approve_suggestion(suggestion_id, auth.user_id, payload.comment.as_deref())
+        .await?
+    {
+        Some(TranslationUpdate::Saved(translation)) => {
+            let change = AuditChange::new("suggestions", suggestion_id)
+                .before(&json!({ "status": suggestion.status, "translation": previous }))
+                .after(&json!({
+                    "status": SuggestionStatus::Approved,
+                    "reviewer_id": auth.user_id,
+                    "review_comment": payload.comment,
+                    "translation": translation,
+                }));
+            Ok((Extension(change), Json(translation)))
+        }
+        Some(TranslationUpdate::Conflict(_)) => Err(ApiError::Conflict(
+            "The translation changed since the suggestion was made".into(),
+        )),
+        None => Err(ApiError::Conflict(
+            "Suggestion has already been reviewed".into(),
+        )),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/suggestions/{suggestion_id}/reject",
+    tag = "reviews",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("suggestion_id" = Uuid, Path, description = "Suggestion id"),
+    ),
+    request_body(content = Option<ReviewDecisionRequest>, description = "Optional reviewer comment"),
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = Suggestion),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown suggestion", body = ErrorBody),
+        (status = 409, description = "Suggestion has already been reviewed", body = ErrorBody),
+    )
+)]
+async fn reject_suggestion(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, suggestion_id)): Path<(Uuid, Uuid)>,
+    payload: Option<Json<ReviewDecisionRequest>>,
+) -> ApiResult<(Extension<AuditChange>, Json<Suggestion>)> {
+    policy::authorize_project(&app_state, Some(&auth), id, ProjectAction::Review).await?;
+    let suggestion = ensure_suggestion(&app_state, id, suggestion_id).await?;
+    let Json(payload) = payload.unwrap_or_default();
+
+    let rejected = app_state
+        .database
+        .reject_suggestion(suggestion_id, auth.user_id, payload.comment.as_deref())
+        .await?
+        .ok_or_else(|| ApiError::Conflict("Suggestion has already been reviewed".into()))?;
+    let change = AuditChange::new("suggestions", suggestion_id)
+        .before(&suggestion)
+        .after(&rejected);
+    Ok((Extension(change), Json(rejected)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/review-queue",
+    tag = "reviews",
+    params(("id" = Uuid, Path, description = "Project id"), ApiPagination, ReviewQueueFilter),
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = Vec<ReviewQueueItem>),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown or hidden project", body = ErrorBody),
+    )
+)]
+async fn get_review_queue(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Query(pagination): Query<ApiPagination>,
+    Query(filter): Query<ReviewQueueFilter>,
+) -> ApiResult<Json<Vec<ReviewQueueItem>>> {
+    policy::authorize_project(&app_state, Some(&auth), id, ProjectAction::Review).await?;
+    let queue = app_state
+        .database
+        .get_review_queue(id, pagination, filter)
+        .await?;
+    Ok(Json(queue))
+}
+
+async fn ensure_suggestion(
+    app_state: &AppState,
+    project_id: Uuid,
+    suggestion_id: Uuid,
+) -> ApiResult<Suggestion> {
+    app_state
+        .database
+        .get_project_suggestion(project_id, suggestion_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Suggestion".into()))
+}
@@ -0,0 +1,53 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use tsukimi_core::models::ProjectStats;
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{ApiError, ApiResult, ErrorBody},
+    middleware::auth::AuthUser,
+    policy::{self, ProjectAction},
+    services::database::StatsFilter,
+};
+
+/// Longest activity history that can be requested, in days
+static MAX_ACTIVITY_DAYS: u32 = 366;
+
+/// Routes nested under `/projects`.
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(get_project_stats))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/stats",
+    tag = "stats",
+    params(("id" = Uuid, Path, description = "Project id"), StatsFilter),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, body = ProjectStats),
+        (status = 400, description = "Too many days of activity requested", body = ErrorBody),
+        (status = 404, description = "Unknown or hidden project", body = ErrorBody),
+    )
+)]
+async fn get_project_stats(
+    State(app_state): State<AppState>,
+    auth: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(filter): Query<StatsFilter>,
+) -> ApiResult<Json<ProjectStats>> {
+    policy::authorize_project(&app_state, auth.as_ref(), id, ProjectAction::View).await?;
+    if filter.days > MAX_ACTIVITY_DAYS {
+        return Err(ApiError::BadRequest(format!(
+            "Activity is limited to the last {} days",
+            MAX_ACTIVITY_DAYS
+        )));
+    }
+
+    let stats = app_state.database.get_project_stats(id, filter).await?;
+    Ok(Json(stats))
+}
@@ -0,0 +1,155 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use time::{Duration, OffsetDateTime};
+use tsukimi_core::auth::{
+    CreatePersonalAccessTokenRequest, CreatedPersonalAccessToken, PersonalAccessToken,
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{ApiError, ApiResult, ErrorBody},
+    middleware::{audit::AuditChange, auth::AuthUser},
+    services::session::SessionService,
+};
+
+/// Length of the token kept in clear to identify it, `tsk_pat_` plus 4 characters
+const TOKEN_PREFIX_LENGTH: usize = 12;
+/// Longest lifetime of an expiring token, longer lived ones have no expiry
+const MAX_EXPIRES_IN_DAYS: u32 = 365;
+
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(list_tokens, create_token))
+        .routes(routes!(revoke_token))
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "tokens",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, body = Vec<PersonalAccessToken>),
+        (status = 401, body = ErrorBody),
+        (status = 403, description = "Called with a personal access token", body = ErrorBody),
+    )
+)]
+async fn list_tokens(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+) -> ApiResult<Json<Vec<PersonalAccessToken>>> {
+    auth.require_session()?;
+    let tokens = app_state
+        .users
+        .get_personal_access_tokens(auth.user_id)
+        .await?;
+    Ok(Json(tokens))
+}
+
+/// The secret is only returned in this response.
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "tokens",
+    request_body = CreatePersonalAccessTokenRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 201, body = CreatedPersonalAccessToken),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, description = "Called with a personal access token", body = ErrorBody),
+    )
+)]
+async fn create_token(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Json(payload): Json<CreatePersonalAccessTokenRequest>,
+) -> ApiResult<(
+    StatusCode,
+    Extension<AuditChange>,
+    Json<CreatedPersonalAccessToken>,
+)> {
+    auth.require_session()?;
+
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().count() > 100 {
+        return Err(ApiError::BadRequest(
+            "Token name must be between 1 and 100 characters".into(),
+        ));
+    }
+    if payload.scopes.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one scope is required".into(),
+        ));
+    }
+
+    if payload
+        .expires_in_days
+        .is_some_and(|days| days == 0 || days > MAX_EXPIRES_IN_DAYS)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Token expiry must be between 1 and {} days",
+            MAX_EXPIRES_IN_DAYS
+        )));
+    }
+
+    let mut scopes = payload.scopes;
+    scopes.sort_unstable();
+    scopes.dedup();
+    let expires_at = payload
+        .expires_in_days
+        .map(|days| OffsetDateTime::now_utc() + Duration::days(days.into()));
+
+    let secret = SessionService::generate_personal_access_token();
+    let token = app_state
+        .users
+        .create_personal_access_token(
+            auth.user_id,
+            name,
+            &SessionService::hash_secret(secret.secret()),
+            &secret.secret()[..TOKEN_PREFIX_LENGTH],
+            &scopes,
+            expires_at,
+        )
+        .await?;
+
+    let change = AuditChange::new("personal_access_tokens", token.id).after(&token);
+    Ok((
+        StatusCode::CREATED,
+        Extension(change),
+        Json(CreatedPersonalAccessToken { token, secret }),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "tokens",
+    params(("id" = Uuid, Path, description = "Token id")),
+    security(("bearer" = [])),
+    responses(
+        (status = 204, description = "Token revoked"),
+        (status = 401, body = ErrorBody),
+        (status = 403, description = "Called with a personal access token", body = ErrorBody),
+        (status = 404, description = "Unknown token", body = ErrorBody),
+    )
+)]
+async fn revoke_token(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>)> {
+    auth.require_session()?;
+    let token = app_state
+        .users
+        .revoke_personal_access_token(auth.user_id, id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Token".into()))?;
+    let change = AuditChange::new("personal_access_tokens", id).before(&token);
+    Ok((StatusCode::NO_CONTENT, Extension(change)))
+}
@@ -0,0 +1,297 @@
+use std::collections::HashSet;
+
+use axum::{
+    Extension, Json,
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+use tsukimi_core::models::{
+    ProjectFile, Translation, TranslationRevision, TranslationUnit, UpdateTranslationRequest,
+    UploadExtractionRequest, UploadExtractionResponse,
+};
+use utoipa_axum::{
+    router::{OpenApiRouter, UtoipaMethodRouterExt},
+    routes,
+};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{ApiError, ApiResult, ErrorBody},
+    middleware::{audit::AuditChange, auth::AuthUser},
+    policy::{self, ProjectAction},
+    services::database::{ApiPagination, TranslationUpdate, UnitFilter},
+};
+
+/// Upper bound of units accepted by a single extraction upload
+const MAX_UPLOAD_UNITS: usize = 200_000;
+/// Size budgeted for each unit of an extraction, JSON overhead included
+const MAX_UNIT_SIZE: usize = 1024;
+/// Largest extraction body accepted, rejected before it is buffered
+const MAX_EXTRACTION_SIZE: usize = MAX_UPLOAD_UNITS * MAX_UNIT_SIZE;
+
+/// Routes nested under `/projects`.
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_files))
+        .routes(routes!(get_units))
+        .routes(routes!(upload_extraction).layer(DefaultBodyLimit::max(MAX_EXTRACTION_SIZE)))
+        .routes(routes!(get_translation, update_translation))
+        .routes(routes!(get_history))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/files",
+    tag = "units",
+    params(("id" = Uuid, Path, description = "Project id")),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, body = Vec<ProjectFile>),
+        (status = 404, description = "Unknown or hidden project", body = ErrorBody),
+    )
+)]
+async fn get_files(
+    State(app_state): State<AppState>,
+    auth: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Vec<ProjectFile>>> {
+    policy::authorize_project(&app_state, auth.as_ref(), id, ProjectAction::View).await?;
+    let files = app_state.database.get_project_files(id).await?;
+    Ok(Json(files))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/units",
+    tag = "units",
+    params(("id" = Uuid, Path, description = "Project id"), ApiPagination, UnitFilter),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, body = Vec<TranslationUnit>),
+        (status = 404, description = "Unknown or hidden project", body = ErrorBody),
+    )
+)]
+async fn get_units(
+    State(app_state): State<AppState>,
+    auth: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(pagination): Query<ApiPagination>,
+    Query(filter): Query<UnitFilter>,
+) -> ApiResult<Json<Vec<TranslationUnit>>> {
+    policy::authorize_project(&app_state, auth.as_ref(), id, ProjectAction::View).await?;
+    let units = app_state.database.get_units(id, pagination, filter).await?;
+    Ok(Json(units))
+}
+
+/// Replaces the source text of the project with a new extraction, units
+/// missing from it become obsolete.
+#[utoipa::path(
+    post,
+    path = "/{id}/units",
+    tag = "units",
+    params(("id" = Uuid, Path, description = "Project id")),
+    request_body = UploadExtractionRequest,
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = UploadExtractionResponse),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown or hidden project", body = ErrorBody),
+        (status = 413, description = "Extraction larger than the upload limit"),
+    )
+)]
+async fn upload_extraction(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UploadExtractionRequest>,
+) -> ApiResult<(Extension<AuditChange>, Json<UploadExtractionResponse>)> {
+    policy::authorize_project(&app_state, Some(&auth), id, ProjectAction::Update).await?;
+
+    if payload.units.is_empty() || payload.units.len() > MAX_UPLOAD_UNITS {
+        return Err(ApiError::BadRequest(format!(
+            "An extraction must contain between 1 and {} units",
+            MAX_UPLOAD_UNITS
+        )));
+    }
+    let mut seen = HashSet::new();
+    if let Some(duplicate) = payload
+        .units
+        .iter()
+        .find(|unit| !seen.insert((unit.file.as_str(), unit.key.as_str())))
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Duplicate unit `{}` in file `{}`",
+            duplicate.key, duplicate.file
+        )));
+    }
+
+    let (response, obsoleted) = app_state
+        .database
+        .upload_extraction(id, &payload.units)
+        .await?;
+    let change = AuditChange::new("projects", id).after(&json!({
+        "inserted": response.inserted,
+        "updated": response.updated,
+        "obsoleted": obsoleted.len(),
+        "obsoleted_units": obsoleted,
+    }));
+    Ok((Extension(change), Json(response)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/units/{unit_id}/translations/{language}",
+    tag = "units",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("unit_id" = Uuid, Path, description = "Unit id"),
+        ("language" = String, Path, description = "Target language, BCP 47 tag"),
+    ),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, body = Translation),
+        (status = 404, description = "Unknown unit or not translated yet", body = ErrorBody),
+    )
+)]
+async fn get_translation(
+    State(app_state): State<AppState>,
+    auth: Option<AuthUser>,
+    Path((id, unit_id, language)): Path<(Uuid, Uuid, String)>,
+) -> ApiResult<Json<Translation>> {
+    policy::authorize_project(&app_state, auth.as_ref(), id, ProjectAction::View).await?;
+    ensure_unit(&app_state, id, unit_id).await?;
+    app_state
+        .database
+        .get_translation(unit_id, &language)
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound("Translation".into()))
+}
+
+/// `revision` must be the revision the edit is based on, a stale one returns
+/// the current translation with a 409 so the client can merge.
+#[utoipa::path(
+    put,
+    path = "/{id}/units/{unit_id}/translations/{language}",
+    tag = "units",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("unit_id" = Uuid, Path, description = "Unit id"),
+        ("language" = String, Path, description = "Target language, BCP 47 tag"),
+    ),
+    request_body = UpdateTranslationRequest,
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = Translation),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown unit", body = ErrorBody),
+        (status = 409, description = "Edited meanwhile, holds the current translation", body = Option<Translation>),
+    )
+)]
+async fn update_translation(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, unit_id, language)): Path<(Uuid, Uuid, String)>,
+    Json(payload): Json<UpdateTranslationRequest>,
+) -> ApiResult<Response> {
+    policy::authorize_project(&app_state, Some(&auth), id, ProjectAction::Translate).await?;
+    ensure_unit(&app_state, id, unit_id).await?;
+    ensure_target_language(&app_state, id, &language).await?;
+
+    let previous = app_state
+        .database
+        .get_translation(unit_id, &language)
+        .await?;
+    match app_state
+        .database
+        .update_translation(
+            unit_id,
+            &language,
+            &payload.text,
+            payload.revision,
+            auth.user_id,
+        )
+        .await?
+    {
+        TranslationUpdate::Saved(translation) => {
+            let change = AuditChange::new("translations", format!("{}/{}", unit_id, language))
+                .before(&previous)
+                .after(&translation);
+            Ok((Extension(change), Json(translation)).into_response())
+        }
+        // Hand back the current revision so the client can merge
+        TranslationUpdate::Conflict(current) => {
+            Ok((StatusCode::CONFLICT, Json(current)).into_response())
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/units/{unit_id}/translations/{language}/history",
+    tag = "units",
+    params(
+        ("id" = Uuid, Path, description = "Project id"),
+        ("unit_id" = Uuid, Path, description = "Unit id"),
+        ("language" = String, Path, description = "Target language, BCP 47 tag"),
+    ),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, body = Vec<TranslationRevision>),
+        (status = 404, description = "Unknown unit", body = ErrorBody),
+    )
+)]
+async fn get_history(
+    State(app_state): State<AppState>,
+    auth: Option<AuthUser>,
+    Path((id, unit_id, language)): Path<(Uuid, Uuid, String)>,
+) -> ApiResult<Json<Vec<TranslationRevision>>> {
+    policy::authorize_project(&app_state, auth.as_ref(), id, ProjectAction::View).await?;
+    ensure_unit(&app_state, id, unit_id).await?;
+    let history = app_state
+        .database
+        .get_translation_history(unit_id, &language)
+        .await?;
+    Ok(Json(history))
+}
+
+pub(crate) async fn ensure_unit(
+    app_state: &AppState,
+    project_id: Uuid,
+    unit_id: Uuid,
+) -> ApiResult<()> {
+    match app_state
+        .database
+        .unit_belongs_to_project(unit_id, project_id)
+        .await?
+    {
+        true => Ok(()),
+        false => Err(ApiError::NotFound("Unit".into())),
+    }
+}
+
+pub(crate) async fn ensure_target_language(
+    app_state: &AppState,
+    project_id: Uuid,
+    language: &str,
+) -> ApiResult<()> {
+    let project = app_state
+        .projects
+        .get_project(project_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Project".into()))?;
+    match project.target_languages.iter().any(|l| l == language) {
+        true => Ok(()),
+        false => Err(ApiError::BadRequest(format!(
+            "`{}` is not a target language of this project",
+            language
+        ))),
+    }
+}
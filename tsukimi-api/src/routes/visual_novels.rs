@@ -0,0 +1,205 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use tsukimi_core::{
+    auth::TokenScope,
+    models::{VisualNovel, VisualNovelRequest},
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{ApiError, ApiResult, ErrorBody},
+    middleware::{audit::AuditChange, auth::AuthUser},
+    policy,
+    services::database::{ApiPagination, VisualNovelFilter},
+};
+
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_visual_novels, create_visual_novel))
+        .routes(routes!(
+            get_visual_novel,
+            update_visual_novel,
+            delete_visual_novel
+        ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "visual-novels",
+    params(ApiPagination, VisualNovelFilter),
+    responses((status = 200, body = Vec<VisualNovel>))
+)]
+async fn get_visual_novels(
+    State(app_state): State<AppState>,
+    Query(pagination): Query<ApiPagination>,
+    Query(filter): Query<VisualNovelFilter>,
+) -> ApiResult<Json<Vec<VisualNovel>>> {
+    let list = app_state
+        .database
+        .get_visual_novels(pagination, filter)
+        .await?;
+    Ok(Json(list))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "visual-novels",
+    params(("id" = Uuid, Path, description = "Visual novel id")),
+    responses(
+        (status = 200, body = VisualNovel),
+        (status = 404, description = "Unknown visual novel", body = ErrorBody),
+    )
+)]
+async fn get_visual_novel(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<VisualNovel>> {
+    app_state
+        .database
+        .get_visual_novel(id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound("Visual novel".into()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "visual-novels",
+    request_body = VisualNovelRequest,
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 201, body = VisualNovel),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 409, description = "VNDB id already registered", body = ErrorBody),
+    )
+)]
+async fn create_visual_novel(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Json(payload): Json<VisualNovelRequest>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>, Json<VisualNovel>)> {
+    auth.require_scope(TokenScope::ProjectsWrite)?;
+    validate(&payload)?;
+
+    let visual_novel = app_state
+        .database
+        .create_visual_novel(&payload, auth.user_id)
+        .await
+        .map_err(conflict_on_duplicate)?;
+    let change = AuditChange::new("visual-novels", visual_novel.id).after(&visual_novel);
+    Ok((StatusCode::CREATED, Extension(change), Json(visual_novel)))
+}
+
+/// Only its creator or an admin can edit a visual novel.
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = "visual-novels",
+    params(("id" = Uuid, Path, description = "Visual novel id")),
+    request_body = VisualNovelRequest,
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = VisualNovel),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown visual novel", body = ErrorBody),
+        (status = 409, description = "VNDB id already registered", body = ErrorBody),
+    )
+)]
+async fn update_visual_novel(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<VisualNovelRequest>,
+) -> ApiResult<(Extension<AuditChange>, Json<VisualNovel>)> {
+    auth.require_scope(TokenScope::ProjectsWrite)?;
+    let creator = app_state.database.get_visual_novel_creator(id).await?;
+    policy::require_creator_or_admin(&app_state, &auth, creator).await?;
+    validate(&payload)?;
+
+    let not_found = || ApiError::NotFound("Visual novel".into());
+    let visual_novel = app_state
+        .database
+        .get_visual_novel(id)
+        .await?
+        .ok_or_else(not_found)?;
+    let updated = app_state
+        .database
+        .update_visual_novel(id, &payload)
+        .await
+        .map_err(conflict_on_duplicate)?
+        .ok_or_else(not_found)?;
+    let change = AuditChange::new("visual-novels", id)
+        .before(&visual_novel)
+        .after(&updated);
+    Ok((Extension(change), Json(updated)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "visual-novels",
+    params(("id" = Uuid, Path, description = "Visual novel id")),
+    security(("bearer" = ["projects:write"])),
+    responses(
+        (status = 204, description = "Visual novel deleted"),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown visual novel", body = ErrorBody),
+    )
+)]
+async fn delete_visual_novel(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>)> {
+    auth.require_scope(TokenScope::ProjectsWrite)?;
+    let creator = app_state.database.get_visual_novel_creator(id).await?;
+    policy::require_creator_or_admin(&app_state, &auth, creator).await?;
+
+    let not_found = || ApiError::NotFound("Visual novel".into());
+    let visual_novel = app_state
+        .database
+        .get_visual_novel(id)
+        .await?
+        .ok_or_else(not_found)?;
+    match app_state.database.delete_visual_novel(id).await? {
+        true => {
+            let change = AuditChange::new("visual-novels", id).before(&visual_novel);
+            Ok((StatusCode::NO_CONTENT, Extension(change)))
+        }
+        false => Err(not_found()),
+    }
+}
+
+fn validate(payload: &VisualNovelRequest) -> ApiResult<()> {
+    if payload.title.trim().is_empty() {
+        return Err(ApiError::BadRequest("Title is required".into()));
+    }
+    if payload.original_language.trim().is_empty() {
+        return Err(ApiError::BadRequest("Original language is required".into()));
+    }
+    Ok(())
+}
+
+fn conflict_on_duplicate(error: sqlx::Error) -> ApiError {
+    match &error {
+        sqlx::Error::Database(e) if e.is_unique_violation() => {
+            ApiError::Conflict("A visual novel with this VNDB id already exists".into())
+        }
+        sqlx::Error::Database(e) if e.is_foreign_key_violation() => {
+            ApiError::BadRequest("Unknown engine".into())
+        }
+        _ => error.into(),
+    }
+}
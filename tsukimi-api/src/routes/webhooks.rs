@@ -0,0 +1,320 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use tsukimi_core::models::{
+    CreateWebhookRequest, CreatedWebhook, UpdateWebhookRequest, Webhook, WebhookDelivery,
+    WebhookEvent,
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{ApiError, ApiResult, ErrorBody},
+    middleware::{audit::AuditChange, auth::AuthUser},
+    policy::{self, EngineAction, ProjectAction},
+    services::{
+        database::{ApiPagination, WebhookFilter, WebhookScope},
+        webhooks::WebhookService,
+    },
+};
+
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_webhooks, create_webhook))
+        .routes(routes!(get_webhook, update_webhook, delete_webhook))
+        .routes(routes!(get_deliveries))
+        .routes(routes!(redeliver))
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "webhooks",
+    params(WebhookFilter),
+    security(("bearer" = ["engines:publish"]), ("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = Vec<Webhook>),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown or hidden project", body = ErrorBody),
+    )
+)]
+async fn get_webhooks(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Query(filter): Query<WebhookFilter>,
+) -> ApiResult<Json<Vec<Webhook>>> {
+    let scope = scope_of(filter.engine_id, filter.project_id)?;
+    authorize(&app_state, &auth, scope).await?;
+    let webhooks = app_state.database.get_webhooks(scope).await?;
+    Ok(Json(webhooks))
+}
+
+/// The signing secret is only returned in this response.
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "webhooks",
+    request_body = CreateWebhookRequest,
+    security(("bearer" = ["engines:publish"]), ("bearer" = ["projects:write"])),
+    responses(
+        (status = 201, body = CreatedWebhook),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown or hidden project", body = ErrorBody),
+    )
+)]
+async fn create_webhook(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>, Json<CreatedWebhook>)> {
+    let scope = scope_of(payload.engine_id, payload.project_id)?;
+    authorize(&app_state, &auth, scope).await?;
+    validate(
+        &app_state.webhooks,
+        scope,
+        Some(&payload.url),
+        Some(&payload.events),
+    )
+    .await?;
+
+    let secret = WebhookService::generate_secret();
+    let webhook = app_state
+        .database
+        .create_webhook(scope, &payload.url, &secret, &payload.events, auth.user_id)
+        .await?;
+    // The secret stays out of the log
+    let change = AuditChange::new("webhooks", webhook.id).after(&webhook);
+    Ok((
+        StatusCode::CREATED,
+        Extension(change),
+        Json(CreatedWebhook { webhook, secret }),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook id")),
+    security(("bearer" = ["engines:publish"]), ("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = Webhook),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    )
+)]
+async fn get_webhook(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Webhook>> {
+    let webhook = ensure_webhook(&app_state, &auth, id).await?;
+    Ok(Json(webhook))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook id")),
+    request_body = UpdateWebhookRequest,
+    security(("bearer" = ["engines:publish"]), ("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = Webhook),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    )
+)]
+async fn update_webhook(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateWebhookRequest>,
+) -> ApiResult<(Extension<AuditChange>, Json<Webhook>)> {
+    let webhook = ensure_webhook(&app_state, &auth, id).await?;
+    validate(
+        &app_state.webhooks,
+        scope_of(webhook.engine_id, webhook.project_id)?,
+        payload.url.as_deref(),
+        payload.events.as_deref(),
+    )
+    .await?;
+    let updated = app_state
+        .database
+        .update_webhook(id, &payload)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Webhook".into()))?;
+    let change = AuditChange::new("webhooks", id)
+        .before(&webhook)
+        .after(&updated);
+    Ok((Extension(change), Json(updated)))
+}
+
+/// Also drops the delivery log of the webhook.
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook id")),
+    security(("bearer" = ["engines:publish"]), ("bearer" = ["projects:write"])),
+    responses(
+        (status = 204),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    )
+)]
+async fn delete_webhook(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<(StatusCode, Extension<AuditChange>)> {
+    let webhook = ensure_webhook(&app_state, &auth, id).await?;
+    match app_state.database.delete_webhook(id).await? {
+        true => {
+            let change = AuditChange::new("webhooks", id).before(&webhook);
+            Ok((StatusCode::NO_CONTENT, Extension(change)))
+        }
+        false => Err(ApiError::NotFound("Webhook".into())),
+    }
+}
+
+/// Delivery log, most recent first.
+#[utoipa::path(
+    get,
+    path = "/{id}/deliveries",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook id"), ApiPagination),
+    security(("bearer" = ["engines:publish"]), ("bearer" = ["projects:write"])),
+    responses(
+        (status = 200, body = Vec<WebhookDelivery>),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    )
+)]
+async fn get_deliveries(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Query(pagination): Query<ApiPagination>,
+) -> ApiResult<Json<Vec<WebhookDelivery>>> {
+    ensure_webhook(&app_state, &auth, id).await?;
+    let deliveries = app_state
+        .database
+        .get_webhook_deliveries(id, pagination)
+        .await?;
+    Ok(Json(deliveries))
+}
+
+/// Queues a new delivery with the payload of a past one, sent with a fresh
+/// signature and delivery id.
+#[utoipa::path(
+    post,
+    path = "/{id}/deliveries/{delivery_id}/redeliver",
+    tag = "webhooks",
+    params(
+        ("id" = Uuid, Path, description = "Webhook id"),
+        ("delivery_id" = Uuid, Path, description = "Delivery to send again"),
+    ),
+    security(("bearer" = ["engines:publish"]), ("bearer" = ["projects:write"])),
+    responses(
+        (status = 202, body = WebhookDelivery),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, description = "Unknown webhook or delivery", body = ErrorBody),
+    )
+)]
+async fn redeliver(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path((id, delivery_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<(StatusCode, Json<WebhookDelivery>)> {
+    ensure_webhook(&app_state, &auth, id).await?;
+    let delivery = app_state
+        .database
+        .redeliver_webhook_delivery(id, delivery_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Delivery".into()))?;
+    Ok((StatusCode::ACCEPTED, Json(delivery)))
+}
+
+fn scope_of(engine_id: Option<Uuid>, project_id: Option<Uuid>) -> ApiResult<WebhookScope> {
+    match (engine_id, project_id) {
+        (Some(id), None) => Ok(WebhookScope::Engine(id)),
+        (None, Some(id)) => Ok(WebhookScope::Project(id)),
+        _ => Err(ApiError::BadRequest(
+            "Exactly one of engine_id and project_id must be given".into(),
+        )),
+    }
+}
+
+/// Webhooks are managed by the owners of what they are registered on.
+async fn authorize(app_state: &AppState, auth: &AuthUser, scope: WebhookScope) -> ApiResult<()> {
+    match scope {
+        WebhookScope::Engine(id) => {
+            policy::authorize_engine(app_state, auth, id, EngineAction::ManageWebhooks).await
+        }
+        WebhookScope::Project(id) => {
+            policy::authorize_project(app_state, Some(auth), id, ProjectAction::ManageWebhooks)
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+async fn ensure_webhook(app_state: &AppState, auth: &AuthUser, id: Uuid) -> ApiResult<Webhook> {
+    let webhook = app_state
+        .database
+        .get_webhook(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Webhook".into()))?;
+    authorize(
+        app_state,
+        auth,
+        scope_of(webhook.engine_id, webhook.project_id)?,
+    )
+    .await?;
+    Ok(webhook)
+}
+
+async fn validate(
+    webhooks: &WebhookService,
+    scope: WebhookScope,
+    url: Option<&str>,
+    events: Option<&[WebhookEvent]>,
+) -> ApiResult<()> {
+    if let Some(url) = url {
+        webhooks
+            .validate_url(url)
+            .await
+            .map_err(ApiError::BadRequest)?;
+    }
+    if events.is_some_and(|events| events.is_empty()) {
+        return Err(ApiError::BadRequest(
+            "A webhook must subscribe to at least one event".into(),
+        ));
+    }
+    let engine = matches!(scope, WebhookScope::Engine(_));
+    if let Some(event) = events
+        .unwrap_or_default()
+        .iter()
+        .find(|event| event.is_engine_event() != engine)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "{} is not raised by this kind of webhook",
+            event.as_str()
+        )));
+    }
+    Ok(())
+}
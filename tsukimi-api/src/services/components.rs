@@ -0,0 +1,166 @@
+use bytes::Bytes;
+use tsukimi_core::models::ComponentReport;
+use wasmtime::{
+    Config, Engine, Store, StoreLimits, StoreLimitsBuilder,
+    component::{Component, Linker, types::ComponentItem},
+};
+
+use crate::error::ApiError;
+
+/// Version of `tsukimi-extension/wit/extension.wit`, whose package is not
+/// versioned so components built from it don't carry it
+static EXTENSION_WORLD_VERSION: &str = "0.1.0";
+/// Prefix of the interfaces of a versioned `tsukimi:extension` package
+static EXTENSION_PACKAGE_PREFIX: &str = "tsukimi:extension/";
+/// Imports declared by the world itself
+static WORLD_IMPORTS: [&str; 1] = ["utils"];
+/// WASI packages an extension may import, it never gets network access
+static ALLOWED_WASI_PACKAGES: [&str; 5] = [
+    "wasi:cli",
+    "wasi:clocks",
+    "wasi:filesystem",
+    "wasi:io",
+    "wasi:random",
+];
+/// Budget of the start functions run while instantiating
+static INSTANTIATION_FUEL: u64 = 100_000_000;
+static MAX_MEMORY_SIZE: usize = 256 * 1024 * 1024;
+
+mod bindings {
+    wasmtime::component::bindgen!("extension" in "../tsukimi-extension/wit/extension.wit");
+}
+
+/// Checks uploaded artifacts against the `tsukimi:extension` world before
+/// they are published.
+#[derive(Clone)]
+pub struct ComponentService {
+    engine: Engine,
+}
+
+struct ValidationState {
+    limits: StoreLimits,
+}
+
+impl ComponentService {
+    pub fn new() -> Result<Self, String> {
+        let mut config = Config::new();
+        config
+            .wasm_component_model(true)
+            .wasm_component_model_async(true)
+            .consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| e.to_string())?;
+        Ok(ComponentService { engine })
+    }
+
+    /// Compiling a component takes a while, it runs on the blocking pool.
+    pub async fn validate(&self, artifact: Bytes) -> Result<ComponentReport, ApiError> {
+        let engine = self.engine.clone();
+        tokio::task::spawn_blocking(move || validate(&engine, &artifact))
+            .await
+            .map_err(|e| ApiError::Internal(format!("Component validation failed: {}", e)))
+    }
+}
+
+fn validate(engine: &Engine, artifact: &[u8]) -> ComponentReport {
+    let mut report = ComponentReport::default();
+
+    let component = match Component::new(engine, artifact) {
+        Ok(component) => component,
+        Err(e) => {
+            report
+                .errors
+                .push(format!("Not a valid WebAssembly component: {:#}", e));
+            return report;
+        }
+    };
+
+    let component_type = component.component_type();
+    for (name, _) in component_type.imports(engine) {
+        report.imports.push(name.to_string());
+        if !is_allowed_import(name) {
+            report
+                .errors
+                .push(format!("Import `{}` is not allowed", name));
+        }
+        if let Some(version) = world_version(name) {
+            report.wit_version = Some(version);
+        }
+    }
+    for (name, item) in component_type.exports(engine) {
+        match item {
+            ComponentItem::ComponentInstance(instance) => {
+                for (function, item) in instance.exports(engine) {
+                    if matches!(item, ComponentItem::ComponentFunc(_)) {
+                        report.capabilities.push(format!("{}.{}", name, function));
+                    }
+                }
+            }
+            ComponentItem::ComponentFunc(_) => report.capabilities.push(name.to_string()),
+            _ => {}
+        }
+        if let Some(version) = world_version(name) {
+            report.wit_version = Some(version);
+        }
+    }
+    if !report.errors.is_empty() {
+        return report;
+    }
+
+    // Imports are never called while instantiating, traps stand in for them
+    let mut linker = Linker::<ValidationState>::new(engine);
+    let instance_pre = linker
+        .define_unknown_imports_as_traps(&component)
+        .and_then(|_| linker.instantiate_pre(&component))
+        .and_then(bindings::ExtensionPre::new);
+    let instance_pre = match instance_pre {
+        Ok(instance_pre) => instance_pre,
+        Err(e) => {
+            report.errors.push(format!(
+                "Does not implement the `tsukimi:extension` world: {:#}",
+                e
+            ));
+            return report;
+        }
+    };
+
+    let mut store = Store::new(
+        engine,
+        ValidationState {
+            limits: StoreLimitsBuilder::new()
+                .memory_size(MAX_MEMORY_SIZE)
+                .build(),
+        },
+    );
+    store.limiter(|state| &mut state.limits);
+    let instantiated = store
+        .set_fuel(INSTANTIATION_FUEL)
+        .and_then(|_| instance_pre.instantiate(&mut store));
+    if let Err(e) = instantiated {
+        report
+            .errors
+            .push(format!("Failed to instantiate the component: {:#}", e));
+        return report;
+    }
+
+    report
+        .wit_version
+        .get_or_insert_with(|| EXTENSION_WORLD_VERSION.to_string());
+    report.valid = true;
+    report
+}
+
+/// Imports are named after their interface, e.g. `wasi:io/streams@0.2.0`.
+fn is_allowed_import(name: &str) -> bool {
+    WORLD_IMPORTS.contains(&name)
+        || ALLOWED_WASI_PACKAGES.iter().any(|package| {
+            name.strip_prefix(package)
+                .is_some_and(|interface| interface.starts_with('/'))
+        })
+}
+
+/// Version of a `tsukimi:extension/<interface>@<version>` name.
+fn world_version(name: &str) -> Option<String> {
+    name.strip_prefix(EXTENSION_PACKAGE_PREFIX)?
+        .split_once('@')
+        .map(|(_, version)| version.to_string())
+}
@@ -0,0 +1,90 @@
+use serde::Deserialize;
+use time::OffsetDateTime;
+use tsukimi_core::models::AuditEntry;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use super::{ApiPagination, DatabaseService};
+
+#[derive(Deserialize, Debug, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditLogFilter {
+    pub actor_id: Option<Uuid>,
+    /// e.g. `projects`, `engines`, `users`
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    /// Entries at or after this time
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub since: Option<OffsetDateTime>,
+    /// Entries before this time
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub until: Option<OffsetDateTime>,
+}
+
+/// Entry written by the audit middleware.
+#[derive(Debug, Clone)]
+pub struct NewAuditEntry {
+    pub actor_id: Option<Uuid>,
+    pub token_id: Option<Uuid>,
+    pub action: String,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub ip: Option<String>,
+    pub status: i16,
+}
+
+impl DatabaseService {
+    pub async fn record_audit_entry(&self, entry: &NewAuditEntry) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_log
+                (actor_id, token_id, action, target_type, target_id, before, after, ip, status)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+        )
+        .bind(entry.actor_id)
+        .bind(entry.token_id)
+        .bind(&entry.action)
+        .bind(&entry.target_type)
+        .bind(&entry.target_id)
+        .bind(entry.before.as_ref().map(sqlx::types::Json))
+        .bind(entry.after.as_ref().map(sqlx::types::Json))
+        .bind(&entry.ip)
+        .bind(entry.status)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Most recent entries first.
+    pub async fn get_audit_log(
+        &self,
+        pagination: ApiPagination,
+        filter: AuditLogFilter,
+    ) -> Result<Vec<AuditEntry>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT *
+            FROM audit_log
+            WHERE ($1::uuid IS NULL OR actor_id = $1)
+                AND ($2::text IS NULL OR target_type = $2)
+                AND ($3::text IS NULL OR target_id = $3)
+                AND ($4::timestamptz IS NULL OR occurred_at >= $4)
+                AND ($5::timestamptz IS NULL OR occurred_at < $5)
+            ORDER BY occurred_at DESC
+            LIMIT $6 OFFSET $7
+        "#,
+        )
+        .bind(filter.actor_id)
+        .bind(filter.target_type)
+        .bind(filter.target_id)
+        .bind(filter.since)
+        .bind(filter.until)
+        .bind(pagination.limit())
+        .bind(pagination.offset())
+        .fetch_all(&self.pool)
+        .await
+    }
+}
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use sqlx::PgConnection;
+use tsukimi_core::models::{Comment, CommentThread, IssueFlag};
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use super::{ApiPagination, DatabaseService};
+
+#[derive(Deserialize, Debug, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ThreadFilter {
+    pub unit_id: Option<Uuid>,
+    pub language: Option<String>,
+    pub flag: Option<IssueFlag>,
+    pub resolved: Option<bool>,
+}
+
+impl DatabaseService {
+    pub async fn get_threads(
+        &self,
+        project_id: Uuid,
+        pagination: ApiPagination,
+        filter: ThreadFilter,
+    ) -> Result<Vec<CommentThread>, sqlx::Error> {
+        let mut threads: Vec<CommentThread> = sqlx::query_as(
+            r#"
+            SELECT t.*
+            FROM comment_threads t
+            JOIN source_units u ON u.id = t.unit_id
+            WHERE u.project_id = $1
+                AND ($2::uuid IS NULL OR t.unit_id = $2)
+                AND ($3::text IS NULL OR t.language = $3)
+                AND ($4::issue_flag IS NULL OR t.flag = $4)
+                AND ($5::boolean IS NULL OR (t.resolved_at IS NOT NULL) = $5)
+            ORDER BY t.created_at DESC
+            LIMIT $6 OFFSET $7
+        "#,
+        )
+        .bind(project_id)
+        .bind(filter.unit_id)
+        .bind(filter.language)
+        .bind(filter.flag)
+        .bind(filter.resolved)
+        .bind(pagination.limit())
+        .bind(pagination.offset())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let ids: Vec<Uuid> = threads.iter().map(|t| t.id).collect();
+        let mut comments = self.get_comments(&ids).await?;
+        for thread in &mut threads {
+            thread.comments = comments.remove(&thread.id).unwrap_or_default();
+        }
+        Ok(threads)
+    }
+
+    /// Returns the thread with its comments if it belongs to the project.
+    pub async fn get_project_thread(
+        &self,
+        project_id: Uuid,
+        thread_id: Uuid,
+    ) -> Result<Option<CommentThread>, sqlx::Error> {
+        let thread: Option<CommentThread> = sqlx::query_as(
+            r#"
+            SELECT t.*
+            FROM comment_threads t
+            JOIN source_units u ON u.id = t.unit_id
+            WHERE t.id = $1 AND u.project_id = $2
+        "#,
+        )
+        .bind(thread_id)
+        .bind(project_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(mut thread) = thread else {
+            return Ok(None);
+        };
+        thread.comments = self
+            .get_comments(&[thread.id])
+            .await?
+            .remove(&thread.id)
+            .unwrap_or_default();
+        Ok(Some(thread))
+    }
+
+    async fn get_comments(
+        &self,
+        thread_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<Comment>>, sqlx::Error> {
+        let comments: Vec<Comment> = sqlx::query_as(
+            r#"
+            SELECT c.id, c.thread_id, c.author_id, a.username AS author, c.body, c.created_at,
+                ARRAY(
+                    SELECT u.username FROM comment_mentions m
+                    JOIN users u ON u.id = m.user_id
+                    WHERE m.comment_id = c.id
+                    ORDER BY u.username
+                ) AS mentions
+            FROM comments c
+            LEFT JOIN users a ON a.id = c.author_id
+            WHERE c.thread_id = ANY($1)
+            ORDER BY c.created_at ASC
+        "#,
+        )
+        .bind(thread_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut grouped: HashMap<Uuid, Vec<Comment>> = HashMap::new();
+        for comment in comments {
+            grouped.entry(comment.thread_id).or_default().push(comment);
+        }
+        Ok(grouped)
+    }
+
+    /// Opens a thread with its first comment.
+    pub async fn create_thread(
+        &self,
+        unit_id: Uuid,
+        language: Option<&str>,
+        flag: Option<IssueFlag>,
+        author_id: Uuid,
+        body: &str,
+        mentions: &[String],
+    ) -> Result<Uuid, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let thread_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO comment_threads (unit_id, language, flag, created_by)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+        "#,
+        )
+        .bind(unit_id)
+        .bind(language)
+        .bind(flag)
+        .bind(author_id)
+        .fetch_one(&mut *transaction)
+        .await?;
+        insert_comment(&mut transaction, thread_id, author_id, body, mentions).await?;
+
+        transaction.commit().await?;
+        Ok(thread_id)
+    }
+
+    /// Replying to a resolved thread reopens it.
+    pub async fn create_comment(
+        &self,
+        thread_id: Uuid,
+        author_id: Uuid,
+        body: &str,
+        mentions: &[String],
+    ) -> Result<Uuid, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let comment_id =
+            insert_comment(&mut transaction, thread_id, author_id, body, mentions).await?;
+        sqlx::query(
+            "UPDATE comment_threads SET resolved_at = NULL, resolved_by = NULL WHERE id = $1",
+        )
+        .bind(thread_id)
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+        Ok(comment_id)
+    }
+
+    pub async fn set_thread_resolved(
+        &self,
+        thread_id: Uuid,
+        resolved_by: Option<Uuid>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE comment_threads
+            SET resolved_by = $2, resolved_at = CASE WHEN $2 IS NULL THEN NULL ELSE NOW() END
+            WHERE id = $1
+        "#,
+        )
+        .bind(thread_id)
+        .bind(resolved_by)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+async fn insert_comment(
+    connection: &mut PgConnection,
+    thread_id: Uuid,
+    author_id: Uuid,
+    body: &str,
+    mentions: &[String],
+) -> Result<Uuid, sqlx::Error> {
+    let comment_id: Uuid = sqlx::query_scalar(
+        "INSERT INTO comments (thread_id, author_id, body) VALUES ($1, $2, $3) RETURNING id",
+    )
+    .bind(thread_id)
+    .bind(author_id)
+    .bind(body)
+    .fetch_one(&mut *connection)
+    .await?;
+
+    // Unknown usernames are silently ignored
+    sqlx::query(
+        r#"
+        INSERT INTO comment_mentions (comment_id, user_id)
+        SELECT $1, id FROM users WHERE username = ANY($2)
+    "#,
+    )
+    .bind(comment_id)
+    .bind(mentions)
+    .execute(&mut *connection)
+    .await?;
+    Ok(comment_id)
+}
@@ -0,0 +1,470 @@
+use serde::Deserialize;
+use serde_json::json;
+use tsukimi_core::models::{
+    ComponentReport, Engine, EngineVersion, ValidationStatus, Version, WebhookEvent,
+};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::{
+    ApiPagination, DatabaseService,
+    jobs::{Job, enqueue_job},
+    webhooks::{WebhookScope, enqueue_event},
+};
+
+/// Minimum `pg_trgm` similarity for a name to match a misspelled query
+static TYPO_SIMILARITY: f32 = 0.3;
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EngineSort {
+    /// Best matches first, by name when there is no query
+    #[default]
+    Relevance,
+    Name,
+    Downloads,
+    RecentlyUpdated,
+}
+
+impl EngineSort {
+    fn order_by(self) -> &'static str {
+        match self {
+            EngineSort::Relevance => "rank DESC, name ASC",
+            EngineSort::Name => "name ASC",
+            EngineSort::Downloads => "downloads DESC, name ASC",
+            EngineSort::RecentlyUpdated => "updated_at DESC, name ASC",
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EngineFilter {
+    #[serde(default)]
+    pub sort: EngineSort,
+    /// Only engines supporting this source language
+    pub language: Option<String>,
+    pub wit_version: Option<String>,
+}
+
+impl DatabaseService {
+    /// Full-text search over name, tags and description. Every word of the
+    /// query matches as a prefix, and names close to the query still match so
+    /// typos return something.
+    pub async fn get_engines(
+        &self,
+        pagination: ApiPagination,
+        filter: EngineFilter,
+    ) -> Result<Vec<Engine>, sqlx::Error> {
+        let query = format!(
+            r#"
+            SELECT *
+            FROM (
+                SELECT e.*,
+                    CASE WHEN $1 = '' THEN 0
+                    ELSE ts_rank(e.search_vector, to_tsquery('simple', $2)) + similarity(e.name, $1) END AS rank
+                FROM engines e
+                WHERE ($1 = ''
+                        OR ($2 <> '' AND e.search_vector @@ to_tsquery('simple', $2))
+                        OR similarity(e.name, $1) >= $3)
+                    AND ($4::text IS NULL OR $4 = ANY(e.supported_languages))
+                    AND ($5::text IS NULL OR e.wit_version = $5)
+            ) ranked
+            ORDER BY {}
+            LIMIT $6 OFFSET $7
+        "#,
+            filter.sort.order_by()
+        );
+
+        sqlx::query_as(&query)
+            .bind(pagination.query.trim())
+            .bind(prefix_tsquery(&pagination.query))
+            .bind(TYPO_SIMILARITY)
+            .bind(&filter.language)
+            .bind(&filter.wit_version)
+            .bind(pagination.limit())
+            .bind(pagination.offset())
+            .fetch_all(&self.pool)
+            .await
+    }
+}
+
+/// Release written by `publish_version`, its artifact already stored.
+#[derive(Debug, Clone)]
+pub struct NewEngineVersion<'a> {
+    pub engine_id: Uuid,
+    pub version: &'a str,
+    pub description: Option<&'a str>,
+    pub artifact_key: &'a str,
+    pub artifact_size: i64,
+    pub artifact_sha256: &'a str,
+    pub signature: &'a str,
+    pub publisher_key_id: Uuid,
+}
+
+/// Artifact of a published version.
+#[derive(sqlx::FromRow, Debug, Clone)]
+pub struct VersionArtifact {
+    pub engine_name: String,
+    pub artifact_key: String,
+    pub artifact_sha256: Option<String>,
+    pub signature: Option<String>,
+    pub publisher_key_id: Option<Uuid>,
+    /// Yanked versions are still served to existing lockfiles
+    pub yanked: bool,
+}
+
+impl DatabaseService {
+    /// Returns `None` if the version doesn't exist, has no artifact or is not
+    /// validated.
+    pub async fn get_version_artifact(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+    ) -> Result<Option<VersionArtifact>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT e.name AS engine_name, v.artifact_key, v.artifact_sha256, v.signature,
+                v.publisher_key_id, v.yanked_at IS NOT NULL AS yanked
+            FROM engine_versions v
+            JOIN engines e ON e.id = v.engine_id
+            WHERE v.engine_id = $1 AND v.version = $2 AND v.artifact_key IS NOT NULL
+                AND v.validation_status = 'valid'
+        "#,
+        )
+        .bind(engine_id)
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    pub async fn get_engine(&self, id: Uuid) -> Result<Option<Engine>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM engines WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// Every uploaded version, yanked and unvalidated ones included, most
+    /// recent first.
+    pub async fn get_engine_versions(
+        &self,
+        engine_id: Uuid,
+    ) -> Result<Vec<EngineVersion>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT * FROM engine_versions WHERE engine_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(engine_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Yanks the version and announces it, unless it was already yanked. The
+    /// database picks the next current version when it was the current one.
+    pub async fn yank_version(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+        reason: Option<&str>,
+        yanked_by: Uuid,
+    ) -> Result<Option<EngineVersion>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let yanked: Option<EngineVersion> = sqlx::query_as(
+            r#"
+            UPDATE engine_versions
+            SET yanked_at = NOW(), yanked_by = $3, yank_reason = $4
+            WHERE engine_id = $1 AND version = $2 AND yanked_at IS NULL
+            RETURNING *
+        "#,
+        )
+        .bind(engine_id)
+        .bind(version)
+        .bind(yanked_by)
+        .bind(reason)
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        let Some(yanked) = yanked else {
+            transaction.rollback().await?;
+            return self.get_engine_version(engine_id, version).await;
+        };
+        enqueue_event(
+            &mut transaction,
+            WebhookScope::Engine(engine_id),
+            WebhookEvent::EngineVersionYanked,
+            json!({
+                "version": yanked.version,
+                "reason": yanked.yank_reason,
+                "yanked_by": yanked_by,
+            }),
+        )
+        .await?;
+
+        transaction.commit().await?;
+        Ok(Some(yanked))
+    }
+
+    /// Makes a yanked version resolvable again. It only becomes the current
+    /// version if the engine has none.
+    pub async fn unyank_version(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+    ) -> Result<Option<EngineVersion>, sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE engine_versions
+            SET yanked_at = NULL, yanked_by = NULL, yank_reason = NULL
+            WHERE engine_id = $1 AND version = $2 AND yanked_at IS NOT NULL
+        "#,
+        )
+        .bind(engine_id)
+        .bind(version)
+        .execute(&self.pool)
+        .await?;
+        // Read back, the fallback trigger may have activated it
+        self.get_engine_version(engine_id, version).await
+    }
+
+    pub async fn get_engine_version(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+    ) -> Result<Option<EngineVersion>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM engine_versions WHERE engine_id = $1 AND version = $2")
+            .bind(engine_id)
+            .bind(version)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    pub async fn deprecate_engine(
+        &self,
+        id: Uuid,
+        message: &str,
+        replacement_engine_id: Option<Uuid>,
+    ) -> Result<Option<Engine>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            UPDATE engines
+            SET deprecated_at = COALESCE(deprecated_at, NOW()),
+                deprecation_message = $2,
+                replacement_engine_id = $3
+            WHERE id = $1
+            RETURNING *
+        "#,
+        )
+        .bind(id)
+        .bind(message)
+        .bind(replacement_engine_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    pub async fn undeprecate_engine(&self, id: Uuid) -> Result<Option<Engine>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            UPDATE engines
+            SET deprecated_at = NULL, deprecation_message = NULL, replacement_engine_id = NULL
+            WHERE id = $1
+            RETURNING *
+        "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Records a signed release, pending until its validation job is done.
+    /// A previous upload of the version that failed validation is replaced.
+    /// Fails with a unique violation if the version already exists.
+    pub async fn publish_version(
+        &self,
+        version: &NewEngineVersion<'_>,
+    ) -> Result<EngineVersion, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM engine_versions
+            WHERE engine_id = $1 AND version = $2 AND validation_status = 'invalid'
+        "#,
+        )
+        .bind(version.engine_id)
+        .bind(version.version)
+        .execute(&mut *transaction)
+        .await?;
+
+        let published: EngineVersion = sqlx::query_as(
+            r#"
+            INSERT INTO engine_versions
+                (engine_id, version, description, artifact_key, artifact_size,
+                 artifact_sha256, signature, publisher_key_id, validation_status)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 'pending')
+            RETURNING *
+        "#,
+        )
+        .bind(version.engine_id)
+        .bind(version.version)
+        .bind(version.description)
+        .bind(version.artifact_key)
+        .bind(version.artifact_size)
+        .bind(version.artifact_sha256)
+        .bind(version.signature)
+        .bind(version.publisher_key_id)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        enqueue_job(
+            &mut transaction,
+            &Job::ValidateArtifact {
+                version_id: published.id,
+            },
+            None,
+        )
+        .await?;
+
+        transaction.commit().await?;
+        Ok(published)
+    }
+
+    /// Artifact of a version waiting for validation, `None` once validated.
+    pub async fn get_pending_artifact_key(
+        &self,
+        version_id: Uuid,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT artifact_key
+            FROM engine_versions
+            WHERE id = $1 AND validation_status = 'pending' AND artifact_key IS NOT NULL
+        "#,
+        )
+        .bind(version_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Records the outcome of the validation of a pending version. A valid
+    /// version becomes current unless a greater one already is, and is
+    /// announced. Returns `None` if the version is no longer pending.
+    pub async fn finish_version_validation(
+        &self,
+        version_id: Uuid,
+        report: &ComponentReport,
+    ) -> Result<Option<EngineVersion>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let pending: Option<EngineVersion> = sqlx::query_as(
+            "SELECT * FROM engine_versions WHERE id = $1 AND validation_status = 'pending' FOR UPDATE",
+        )
+        .bind(version_id)
+        .fetch_optional(&mut *transaction)
+        .await?;
+        let Some(pending) = pending else {
+            transaction.rollback().await?;
+            return Ok(None);
+        };
+        // Locks the engine so concurrent validations agree on the current version
+        let current: Option<Version> =
+            sqlx::query_scalar("SELECT current_version FROM engines WHERE id = $1 FOR UPDATE")
+                .bind(pending.engine_id)
+                .fetch_one(&mut *transaction)
+                .await?;
+        let is_current = report.valid
+            && pending.yanked_at.is_none()
+            && current.is_none_or(|current| pending.version > current);
+
+        let validated: EngineVersion = sqlx::query_as(
+            r#"
+            UPDATE engine_versions
+            SET validation_status = $2,
+                validation_report = $3,
+                wit_version = $4,
+                capabilities = $5,
+                is_active = $6
+            WHERE id = $1
+            RETURNING *
+        "#,
+        )
+        .bind(version_id)
+        .bind(match report.valid {
+            true => ValidationStatus::Valid,
+            false => ValidationStatus::Invalid,
+        })
+        .bind(sqlx::types::Json(report))
+        .bind(&report.wit_version)
+        .bind(&report.capabilities)
+        .bind(is_current)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        if report.valid {
+            enqueue_event(
+                &mut transaction,
+                WebhookScope::Engine(validated.engine_id),
+                WebhookEvent::EngineVersionPublished,
+                json!({
+                    "version": validated.version,
+                    "description": validated.description,
+                    "current": validated.is_active,
+                    "artifact_sha256": validated.artifact_sha256,
+                    "publisher_key_id": validated.publisher_key_id,
+                    "wit_version": validated.wit_version,
+                    "capabilities": validated.capabilities,
+                }),
+            )
+            .await?;
+        }
+
+        transaction.commit().await?;
+        Ok(Some(validated))
+    }
+
+    pub async fn increment_downloads(&self, engine_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE engines SET downloads = downloads + 1 WHERE id = $1")
+            .bind(engine_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Turns free text into a `to_tsquery` expression matching every word as a
+/// prefix, e.g. `ren py` becomes `ren:* & py:*`. Anything that is not part of a
+/// word is dropped so user input can never be a tsquery syntax error.
+fn prefix_tsquery(query: &str) -> String {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("{}:*", word.to_lowercase()))
+        .collect::<Vec<_>>()
+        .join(" & ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_become_prefixes() {
+        assert_eq!(prefix_tsquery("ren py"), "ren:* & py:*");
+        assert_eq!(prefix_tsquery("  KiriKiri  Z "), "kirikiri:* & z:*");
+        assert_eq!(prefix_tsquery("吉里吉里 2"), "吉里吉里:* & 2:*");
+    }
+
+    #[test]
+    fn tsquery_syntax_is_dropped() {
+        assert_eq!(prefix_tsquery("ren & !py"), "ren:* & py:*");
+        assert_eq!(prefix_tsquery("(a | b) <-> c:*"), "a:* & b:* & c:*");
+        assert_eq!(prefix_tsquery("'quoted' \"text\" \\"), "quoted:* & text:*");
+        assert_eq!(prefix_tsquery("renpy:A*B"), "renpy:* & a:* & b:*");
+    }
+
+    #[test]
+    fn blank_queries_match_nothing() {
+        assert_eq!(prefix_tsquery(""), "");
+        assert_eq!(prefix_tsquery(" &|!():*<->' "), "");
+    }
+}
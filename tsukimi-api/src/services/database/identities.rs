@@ -0,0 +1,162 @@
+use tsukimi_core::{auth::UserIdentity, models::User};
+use uuid::Uuid;
+
+use super::DatabaseService;
+
+/// Usernames are suffixed with a number when taken, up to this number
+static MAX_USERNAME_SUFFIX: u32 = 20;
+
+/// Account returned by an identity provider after a login.
+#[derive(Debug, Clone)]
+pub struct NewIdentity {
+    pub provider: String,
+    pub subject: String,
+    pub username: String,
+    pub email: Option<String>,
+}
+
+impl DatabaseService {
+    /// Returns the user owning the identity, creating both on first login.
+    /// A new user takes the username of the identity, suffixed if already
+    /// taken, and its email unless another user has it.
+    pub async fn upsert_identity_user(&self, identity: &NewIdentity) -> Result<User, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let user_id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE user_identities
+            SET username = $3, email = $4, last_login_at = NOW()
+            WHERE provider = $1 AND subject = $2
+            RETURNING user_id
+        "#,
+        )
+        .bind(&identity.provider)
+        .bind(&identity.subject)
+        .bind(&identity.username)
+        .bind(&identity.email)
+        .fetch_optional(&mut *transaction)
+        .await?;
+        if let Some(user_id) = user_id {
+            let user = sqlx::query_as("SELECT * FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_one(&mut *transaction)
+                .await?;
+            transaction.commit().await?;
+            return Ok(user);
+        }
+
+        let base: String = identity.username.chars().take(40).collect();
+        let mut user: Option<User> = None;
+        for suffix in 1..=MAX_USERNAME_SUFFIX {
+            let username = match suffix {
+                1 => base.clone(),
+                _ => format!("{}-{}", base, suffix),
+            };
+            // Any conflict left is on the username, the email is dropped first
+            user = sqlx::query_as(
+                r#"
+                INSERT INTO users (username, email)
+                VALUES (
+                    $1,
+                    CASE WHEN EXISTS (SELECT 1 FROM users WHERE email = $2) THEN NULL ELSE $2 END
+                )
+                ON CONFLICT DO NOTHING
+                RETURNING *
+            "#,
+            )
+            .bind(&username)
+            .bind(&identity.email)
+            .fetch_optional(&mut *transaction)
+            .await?;
+            if user.is_some() {
+                break;
+            }
+        }
+        let user = match user {
+            Some(user) => user,
+            None => {
+                let username = format!("{}-{}", base, &Uuid::new_v4().simple().to_string()[..8]);
+                sqlx::query_as("INSERT INTO users (username) VALUES ($1) RETURNING *")
+                    .bind(username)
+                    .fetch_one(&mut *transaction)
+                    .await?
+            }
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO user_identities (user_id, provider, subject, username, email, last_login_at)
+            VALUES ($1, $2, $3, $4, $5, NOW())
+        "#,
+        )
+        .bind(user.id)
+        .bind(&identity.provider)
+        .bind(&identity.subject)
+        .bind(&identity.username)
+        .bind(&identity.email)
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+        Ok(user)
+    }
+
+    pub async fn get_user_identities(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<UserIdentity>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT provider, subject, username, email, created_at, last_login_at
+            FROM user_identities
+            WHERE user_id = $1
+            ORDER BY created_at
+        "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Links the identity to the user, refreshing it if they already have it.
+    /// Returns `None` if it belongs to another user, fails with a unique
+    /// violation if the user has another account of the same provider.
+    pub async fn link_identity(
+        &self,
+        user_id: Uuid,
+        identity: &NewIdentity,
+    ) -> Result<Option<UserIdentity>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            INSERT INTO user_identities (user_id, provider, subject, username, email)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (provider, subject) DO UPDATE
+            SET username = EXCLUDED.username, email = EXCLUDED.email
+            WHERE user_identities.user_id = EXCLUDED.user_id
+            RETURNING provider, subject, username, email, created_at, last_login_at
+        "#,
+        )
+        .bind(user_id)
+        .bind(&identity.provider)
+        .bind(&identity.subject)
+        .bind(&identity.username)
+        .bind(&identity.email)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Returns whether the user had an identity of this provider.
+    pub async fn unlink_identity(
+        &self,
+        user_id: Uuid,
+        provider: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result =
+            sqlx::query("DELETE FROM user_identities WHERE user_id = $1 AND provider = $2")
+                .bind(user_id)
+                .bind(provider)
+                .execute(&self.pool)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,254 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use time::OffsetDateTime;
+use tsukimi_core::models::{BackgroundJob, JobStatus};
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use super::{ApiPagination, DatabaseService};
+
+/// Attempts given to a job before it is dead
+pub(super) static DEFAULT_MAX_ATTEMPTS: i32 = 5;
+
+/// Work done by the job workers, stored as the `payload` of the job.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind")]
+pub enum Job {
+    /// Checks an uploaded artifact, then publishes or rejects its version
+    #[serde(rename = "artifact.validate")]
+    ValidateArtifact { version_id: Uuid },
+    /// Recomputes the progress of every file of a project
+    #[serde(rename = "stats.refresh_project")]
+    RefreshProjectStats { project_id: Uuid },
+    /// Deletes old succeeded jobs
+    #[serde(rename = "jobs.prune")]
+    PruneJobs,
+}
+
+impl Job {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Job::ValidateArtifact { .. } => "artifact.validate",
+            Job::RefreshProjectStats { .. } => "stats.refresh_project",
+            Job::PruneJobs => "jobs.prune",
+        }
+    }
+
+    /// Jobs sharing a key are not queued twice, the queued one does the work
+    /// of both.
+    fn unique_key(&self) -> Option<String> {
+        match self {
+            Job::ValidateArtifact { .. } => None,
+            Job::RefreshProjectStats { project_id } => {
+                Some(format!("{}:{}", self.kind(), project_id))
+            }
+            Job::PruneJobs => Some(self.kind().to_string()),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct JobFilter {
+    pub status: Option<JobStatus>,
+    /// e.g. `artifact.validate`
+    pub kind: Option<String>,
+}
+
+/// A job claimed by a worker.
+#[derive(sqlx::FromRow, Debug, Clone)]
+pub struct ClaimedJob {
+    pub id: Uuid,
+    pub kind: String,
+    pub payload: sqlx::types::Json<serde_json::Value>,
+    /// Including the current one
+    pub attempts: i32,
+    pub max_attempts: i32,
+}
+
+/// Queues `job`, to run once `run_at` is reached when given. Runs in the
+/// transaction of the change so the job only exists once it is committed.
+pub(super) async fn enqueue_job(
+    connection: &mut PgConnection,
+    job: &Job,
+    run_at: Option<OffsetDateTime>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO jobs (kind, payload, max_attempts, run_at, unique_key)
+        VALUES ($1, $2, $3, COALESCE($4, NOW()), $5)
+        ON CONFLICT DO NOTHING
+    "#,
+    )
+    .bind(job.kind())
+    .bind(sqlx::types::Json(job))
+    .bind(DEFAULT_MAX_ATTEMPTS)
+    .bind(run_at)
+    .bind(job.unique_key())
+    .execute(&mut *connection)
+    .await?;
+    Ok(())
+}
+
+impl DatabaseService {
+    pub async fn enqueue_job(
+        &self,
+        job: &Job,
+        run_at: Option<OffsetDateTime>,
+    ) -> Result<(), sqlx::Error> {
+        let mut connection = self.pool.acquire().await?;
+        enqueue_job(&mut connection, job, run_at).await
+    }
+
+    /// Takes up to `limit` due jobs for `worker` and holds them for `lease`.
+    /// Jobs whose worker let the lease expire are taken again, or are dead
+    /// if that was their last attempt.
+    pub async fn claim_jobs(
+        &self,
+        worker: &str,
+        limit: i64,
+        lease: std::time::Duration,
+    ) -> Result<Vec<ClaimedJob>, sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE jobs
+            SET status = 'dead', finished_at = NOW(), locked_by = NULL, locked_until = NULL,
+                last_error = COALESCE(last_error, 'Lease expired')
+            WHERE status = 'running' AND locked_until < NOW() AND attempts >= max_attempts
+        "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query_as(
+            r#"
+            WITH due AS (
+                SELECT id
+                FROM jobs
+                WHERE (status = 'queued' AND run_at <= NOW())
+                    OR (status = 'running' AND locked_until < NOW())
+                ORDER BY run_at
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE jobs j
+            SET status = 'running',
+                attempts = j.attempts + 1,
+                locked_by = $1,
+                locked_until = NOW() + make_interval(secs => $3)
+            FROM due
+            WHERE j.id = due.id
+            RETURNING j.id, j.kind, j.payload, j.attempts, j.max_attempts
+        "#,
+        )
+        .bind(worker)
+        .bind(limit)
+        .bind(lease.as_secs_f64())
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn complete_job(&self, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE jobs
+            SET status = 'succeeded', finished_at = NOW(), locked_by = NULL, locked_until = NULL
+            WHERE id = $1
+        "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Queues the job again at `retry_at`, or marks it dead without one. A
+    /// retry is dropped when a job with the same key is already queued.
+    pub async fn fail_job(
+        &self,
+        id: Uuid,
+        error: &str,
+        retry_at: Option<OffsetDateTime>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            WITH outcome AS (
+                SELECT CASE
+                    WHEN $3::timestamptz IS NULL THEN 'dead'
+                    WHEN EXISTS (
+                        SELECT 1 FROM jobs o
+                        WHERE o.unique_key = j.unique_key AND o.status = 'queued'
+                    ) THEN 'succeeded'
+                    ELSE 'queued'
+                END::job_status AS status
+                FROM jobs j
+                WHERE j.id = $1
+            )
+            UPDATE jobs
+            SET status = outcome.status,
+                run_at = COALESCE($3, run_at),
+                finished_at = CASE WHEN outcome.status <> 'queued' THEN NOW() END,
+                last_error = $2,
+                locked_by = NULL,
+                locked_until = NULL
+            FROM outcome
+            WHERE id = $1
+        "#,
+        )
+        .bind(id)
+        .bind(error)
+        .bind(retry_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Most recent first.
+    pub async fn get_jobs(
+        &self,
+        pagination: ApiPagination,
+        filter: JobFilter,
+    ) -> Result<Vec<BackgroundJob>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT *
+            FROM jobs
+            WHERE ($1::job_status IS NULL OR status = $1)
+                AND ($2::text IS NULL OR kind = $2)
+            ORDER BY created_at DESC
+            LIMIT $3 OFFSET $4
+        "#,
+        )
+        .bind(filter.status)
+        .bind(filter.kind)
+        .bind(pagination.limit())
+        .bind(pagination.offset())
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Queues a dead job again with fresh attempts, `None` if it is not dead.
+    pub async fn retry_job(&self, id: Uuid) -> Result<Option<BackgroundJob>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            UPDATE jobs
+            SET status = 'queued', attempts = 0, run_at = NOW(), finished_at = NULL
+            WHERE id = $1 AND status = 'dead'
+            RETURNING *
+        "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Deletes the jobs that succeeded before `before`, dead ones are kept.
+    pub async fn prune_jobs(&self, before: OffsetDateTime) -> Result<u64, sqlx::Error> {
+        let result =
+            sqlx::query("DELETE FROM jobs WHERE status = 'succeeded' AND finished_at < $1")
+                .bind(before)
+                .execute(&self.pool)
+                .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,144 @@
+use sqlx::PgConnection;
+use tsukimi_core::models::{EngineMember, EngineRole, GlobalRole};
+use uuid::Uuid;
+
+use super::DatabaseService;
+
+/// Outcome of a membership change, which must leave the engine or project
+/// with at least one owner.
+#[derive(Debug, PartialEq)]
+pub enum MemberUpdate<R> {
+    /// Holds the role the user had before, `None` if they were not a member
+    Applied(Option<R>),
+    /// The user is the last owner, nothing changed
+    LastOwner,
+}
+
+impl DatabaseService {
+    pub async fn get_user_role(&self, user_id: Uuid) -> Result<Option<GlobalRole>, sqlx::Error> {
+        sqlx::query_scalar("SELECT role FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    pub async fn set_user_role(
+        &self,
+        user_id: Uuid,
+        role: GlobalRole,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE users SET role = $2 WHERE id = $1")
+            .bind(user_id)
+            .bind(role)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_engine_role(
+        &self,
+        engine_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<EngineRole>, sqlx::Error> {
+        sqlx::query_scalar("SELECT role FROM engine_members WHERE engine_id = $1 AND user_id = $2")
+            .bind(engine_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    pub async fn get_engine_members(
+        &self,
+        engine_id: Uuid,
+    ) -> Result<Vec<EngineMember>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT m.user_id, u.username, m.role, m.created_at
+            FROM engine_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.engine_id = $1
+            ORDER BY m.role, u.username
+        "#,
+        )
+        .bind(engine_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Adds the member or changes their role, unless that demotes the last
+    /// owner.
+    pub async fn set_engine_member(
+        &self,
+        engine_id: Uuid,
+        user_id: Uuid,
+        role: EngineRole,
+    ) -> Result<MemberUpdate<EngineRole>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let owners = lock_engine_owners(&mut transaction, engine_id).await?;
+        if role != EngineRole::Owner && owners == [user_id] {
+            return Ok(MemberUpdate::LastOwner);
+        }
+        let previous = sqlx::query_scalar(
+            "SELECT role FROM engine_members WHERE engine_id = $1 AND user_id = $2",
+        )
+        .bind(engine_id)
+        .bind(user_id)
+        .fetch_optional(&mut *transaction)
+        .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO engine_members (engine_id, user_id, role)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (engine_id, user_id) DO UPDATE SET role = EXCLUDED.role
+        "#,
+        )
+        .bind(engine_id)
+        .bind(user_id)
+        .bind(role)
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+        Ok(MemberUpdate::Applied(previous))
+    }
+
+    /// Removes the member unless they are the last owner.
+    pub async fn remove_engine_member(
+        &self,
+        engine_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<MemberUpdate<EngineRole>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let owners = lock_engine_owners(&mut transaction, engine_id).await?;
+        if owners == [user_id] {
+            return Ok(MemberUpdate::LastOwner);
+        }
+        let previous = sqlx::query_scalar(
+            "DELETE FROM engine_members WHERE engine_id = $1 AND user_id = $2 RETURNING role",
+        )
+        .bind(engine_id)
+        .bind(user_id)
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+        Ok(MemberUpdate::Applied(previous))
+    }
+}
+
+/// Owners of the engine, locked until the end of the transaction so that
+/// two owners can't demote each other at the same time.
+async fn lock_engine_owners(
+    connection: &mut PgConnection,
+    engine_id: Uuid,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    // Une rétrogradation concurrente attend ici puis relit les propriétaires
+    sqlx::query_scalar(
+        "SELECT user_id FROM engine_members WHERE engine_id = $1 AND role = 'owner' FOR UPDATE",
+    )
+    .bind(engine_id)
+    .fetch_all(connection)
+    .await
+}
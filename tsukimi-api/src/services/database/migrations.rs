@@ -0,0 +1,154 @@
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
+use tracing::info;
+
+use super::DatabaseService;
+
+/// Schema migrations, embedded in the binary at compile time.
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Demo data for local development, never run in other environments.
+static DEV_SEED: &str = include_str!("../../../seeds/dev.sql");
+
+/// Tables created by the first migration, which the former `sql/init.sql`
+/// created as well.
+const INITIAL_SCHEMA_TABLES: &[&str] = &[
+    "users",
+    "sessions",
+    "personal_access_tokens",
+    "engines",
+    "engine_members",
+    "visual_novels",
+    "engine_versions",
+    "projects",
+    "project_members",
+    "source_units",
+    "translations",
+    "translation_history",
+    "suggestions",
+    "comment_threads",
+    "comments",
+    "comment_mentions",
+    "project_file_stats",
+    "project_contributor_stats",
+    "project_activity",
+];
+
+impl DatabaseService {
+    /// Applies the pending migrations. Refuses to touch a database migrated
+    /// by a newer release, rolling back the binary must not corrupt it.
+    pub async fn migrate(&self) -> Result<(), String> {
+        let latest_known = MIGRATOR.iter().map(|m| m.version).max().unwrap_or(0);
+        // The migrations table does not exist before the first run
+        let initialized: bool =
+            sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| format!("Failed to read the applied migrations: {}", e))?;
+        let latest_applied: Option<i64> = match initialized {
+            true => sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| format!("Failed to read the applied migrations: {}", e))?,
+            false => None,
+        };
+        if !initialized {
+            self.adopt_initial_schema().await?;
+        }
+
+        if let Some(applied) = latest_applied
+            && applied > latest_known
+        {
+            return Err(format!(
+                "Database schema is at migration {} but this binary only knows up to {}, \
+                 refusing to start an older release",
+                applied, latest_known
+            ));
+        }
+
+        MIGRATOR.run(&self.pool).await.map_err(|e| match e {
+            MigrateError::VersionMismatch(version) => format!(
+                "Migration {} was modified after being applied, restore the original file",
+                version
+            ),
+            e => format!("Failed to run migrations: {}", e),
+        })?;
+        info!("Database schema is up to date (migration {})", latest_known);
+        Ok(())
+    }
+
+    /// Records the first migration as applied on databases created from the
+    /// former `sql/init.sql`, so the following ones run on top of them. Does
+    /// nothing on an empty database.
+    async fn adopt_initial_schema(&self) -> Result<(), String> {
+        let existing: Vec<String> = sqlx::query_scalar(
+            "SELECT table_name::text FROM information_schema.tables
+             WHERE table_schema = current_schema() AND table_name = ANY($1)",
+        )
+        .bind(INITIAL_SCHEMA_TABLES)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to inspect the existing schema: {}", e))?;
+        if existing.is_empty() {
+            return Ok(());
+        }
+        let missing: Vec<&str> = INITIAL_SCHEMA_TABLES
+            .iter()
+            .copied()
+            .filter(|table| !existing.iter().any(|name| name == table))
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!(
+                "Database has a schema without migration history and is missing \
+                 the tables {}, refusing to adopt it",
+                missing.join(", ")
+            ));
+        }
+
+        let initial = MIGRATOR
+            .iter()
+            .min_by_key(|m| m.version)
+            .ok_or("No migration embedded in the binary")?;
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to adopt the existing schema: {}", e))?;
+        tx.ensure_migrations_table()
+            .await
+            .map_err(|e| format!("Failed to adopt the existing schema: {}", e))?;
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+             VALUES ($1, $2, TRUE, $3, 0)",
+        )
+        .bind(initial.version)
+        .bind(&*initial.description)
+        .bind(&*initial.checksum)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to adopt the existing schema: {}", e))?;
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to adopt the existing schema: {}", e))?;
+        info!(
+            "Adopted the schema created by init.sql as migration {}",
+            initial.version
+        );
+        Ok(())
+    }
+
+    /// Whether every embedded migration has been applied.
+    pub async fn is_schema_current(&self) -> Result<bool, sqlx::Error> {
+        let latest_known = MIGRATOR.iter().map(|m| m.version).max().unwrap_or(0);
+        let latest_applied: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(latest_applied == Some(latest_known))
+    }
+
+    pub async fn seed_dev_data(&self) -> Result<(), sqlx::Error> {
+        sqlx::raw_sql(DEV_SEED).execute(&self.pool).await?;
+        info!("Development seed data loaded");
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use serde::Deserialize;
+use sqlx::postgres::PgPoolOptions;
+use utoipa::IntoParams;
+
+use crate::config::DatabaseConfiguration;
+
+mod audit;
+mod comments;
+mod engines;
+mod identities;
+mod jobs;
+mod members;
+mod migrations;
+mod oauth_states;
+mod projects;
+mod publisher_keys;
+mod reviews;
+mod sessions;
+mod stats;
+mod tokens;
+mod units;
+mod users;
+mod visual_novels;
+mod webhooks;
+
+pub use audit::{AuditLogFilter, NewAuditEntry};
+pub use comments::ThreadFilter;
+pub use engines::{EngineFilter, EngineSort, NewEngineVersion, VersionArtifact};
+pub use identities::NewIdentity;
+pub use jobs::{ClaimedJob, Job, JobFilter};
+pub use members::MemberUpdate;
+pub use oauth_states::OauthState;
+pub use projects::{ProjectAccess, ProjectFilter};
+pub use reviews::ReviewQueueFilter;
+pub use stats::StatsFilter;
+pub use units::{TranslationUpdate, UnitFilter};
+pub use visual_novels::VisualNovelFilter;
+pub use webhooks::{DeliveryAttempt, PendingDelivery, WebhookFilter, WebhookScope};
+
+#[derive(Clone)]
+pub struct DatabaseService {
+    pool: sqlx::Pool<sqlx::Postgres>,
+}
+
+/// Largest page a list endpoint returns, larger requests are clamped
+pub const MAX_PER_PAGE: u32 = 100;
+
+/// Validated when the query string is parsed, a page out of range is a bad
+/// request before any backend sees it.
+#[derive(Deserialize, Debug, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(try_from = "RawPagination")]
+pub struct ApiPagination {
+    /// Full-text search, everything when empty
+    #[serde(default)]
+    pub query: String,
+    /// Starts at 1
+    #[serde(default)]
+    #[param(minimum = 1, default = 1)]
+    page: u32,
+    /// At most 100, larger values are clamped
+    #[serde(default)]
+    #[param(minimum = 1, default = 10)]
+    per_page: u32,
+}
+
+impl ApiPagination {
+    pub fn limit(&self) -> i64 {
+        self.per_page as i64
+    }
+
+    /// Items of the pages before this one
+    pub fn offset(&self) -> i64 {
+        (self.page as i64 - 1) * self.per_page as i64
+    }
+}
+
+#[derive(Deserialize)]
+struct RawPagination {
+    #[serde(default)]
+    query: String,
+    #[serde(default = "default_page")]
+    page: u32,
+    #[serde(default = "default_per_page")]
+    per_page: u32,
+}
+
+impl TryFrom<RawPagination> for ApiPagination {
+    type Error = String;
+
+    fn try_from(raw: RawPagination) -> Result<Self, Self::Error> {
+        if raw.page == 0 {
+            return Err("page starts at 1".into());
+        }
+        if raw.per_page == 0 {
+            return Err("per_page must be at least 1".into());
+        }
+        Ok(ApiPagination {
+            query: raw.query,
+            page: raw.page,
+            per_page: raw.per_page.min(MAX_PER_PAGE),
+        })
+    }
+}
+
+fn default_page() -> u32 {
+    1
+}
+
+fn default_per_page() -> u32 {
+    10
+}
+
+impl DatabaseService {
+    /// Connections are opened on first use, an unreachable database surfaces
+    /// when the migrations run at startup.
+    pub fn new(database_config: &DatabaseConfiguration) -> Self {
+        let pool = PgPoolOptions::new()
+            .max_connections(database_config.max_connections())
+            .min_connections(database_config.min_connections())
+            .acquire_timeout(database_config.acquire_timeout())
+            .idle_timeout(database_config.idle_timeout())
+            .connect_lazy_with(database_config.connect_options().clone());
+        Self { pool }
+    }
+
+    /// Open connections, idle connections and pool capacity.
+    pub fn pool_usage(&self) -> (u32, usize, u32) {
+        (
+            self.pool.size(),
+            self.pool.num_idle(),
+            self.pool.options().get_max_connections(),
+        )
+    }
+
+    pub async fn ping(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+}
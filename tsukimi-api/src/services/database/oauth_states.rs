@@ -0,0 +1,61 @@
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::DatabaseService;
+
+/// What the API keeps of an authorization until its code is exchanged.
+#[derive(sqlx::FromRow, Debug, Clone)]
+pub struct OauthState {
+    pub provider: String,
+    pub pkce_verifier: String,
+    /// Expected in the OpenID Connect id token
+    pub nonce: Option<String>,
+    /// User linking another identity, `None` for a login
+    pub user_id: Option<Uuid>,
+}
+
+impl DatabaseService {
+    /// Remembers an authorization, dropping the expired ones on the way.
+    pub async fn create_oauth_state(
+        &self,
+        state: &str,
+        oauth_state: &OauthState,
+        expires_at: OffsetDateTime,
+    ) -> Result<(), sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM oauth_states WHERE expires_at <= NOW()")
+            .execute(&mut *transaction)
+            .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO oauth_states (state, provider, pkce_verifier, nonce, user_id, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        )
+        .bind(state)
+        .bind(&oauth_state.provider)
+        .bind(&oauth_state.pkce_verifier)
+        .bind(&oauth_state.nonce)
+        .bind(oauth_state.user_id)
+        .bind(expires_at)
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await
+    }
+
+    /// Spends the state, `None` if it is unknown, expired or already used.
+    pub async fn take_oauth_state(&self, state: &str) -> Result<Option<OauthState>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            DELETE FROM oauth_states
+            WHERE state = $1 AND expires_at > NOW()
+            RETURNING provider, pkce_verifier, nonce, user_id
+        "#,
+        )
+        .bind(state)
+        .fetch_optional(&self.pool)
+        .await
+    }
+}
@@ -0,0 +1,310 @@
+use serde::Deserialize;
+use sqlx::PgConnection;
+use tsukimi_core::models::{
+    CreateProjectRequest, Project, ProjectMember, ProjectRole, ProjectVisibility,
+    UpdateProjectRequest,
+};
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use super::{
+    ApiPagination, DatabaseService,
+    jobs::{Job, enqueue_job},
+    members::MemberUpdate,
+};
+
+#[derive(Deserialize, Debug, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProjectFilter {
+    pub visual_novel_id: Option<Uuid>,
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+/// What the policy needs to know about a project to authorize a caller.
+#[derive(sqlx::FromRow, Debug, Clone)]
+pub struct ProjectAccess {
+    pub visibility: ProjectVisibility,
+    pub archived: bool,
+    pub role: Option<ProjectRole>,
+}
+
+impl DatabaseService {
+    /// Lists public projects, plus the private ones `user_id` is a member of.
+    /// Administrators see every project.
+    pub async fn get_projects(
+        &self,
+        pagination: ApiPagination,
+        filter: ProjectFilter,
+        user_id: Option<Uuid>,
+        is_admin: bool,
+    ) -> Result<Vec<Project>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT p.*
+            FROM projects p
+            WHERE p.name ILIKE $1
+                AND ($2::uuid IS NULL OR p.visual_novel_id = $2)
+                AND ($3 OR p.archived_at IS NULL)
+                AND (
+                    $5
+                    OR p.visibility = 'public'
+                    OR EXISTS (
+                        SELECT 1 FROM project_members m
+                        WHERE m.project_id = p.id AND m.user_id = $4
+                    )
+                )
+            ORDER BY p.name ASC
+            LIMIT $6 OFFSET $7
+        "#,
+        )
+        .bind(format!("%{}%", pagination.query))
+        .bind(filter.visual_novel_id)
+        .bind(filter.include_archived)
+        .bind(user_id)
+        .bind(is_admin)
+        .bind(pagination.limit())
+        .bind(pagination.offset())
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn get_project(&self, id: Uuid) -> Result<Option<Project>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM projects WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    pub async fn get_project_access(
+        &self,
+        project_id: Uuid,
+        user_id: Option<Uuid>,
+    ) -> Result<Option<ProjectAccess>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT p.visibility, p.archived_at IS NOT NULL AS archived, m.role
+            FROM projects p
+            LEFT JOIN project_members m ON m.project_id = p.id AND m.user_id = $2
+            WHERE p.id = $1
+        "#,
+        )
+        .bind(project_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Creates the project and makes `owner_id` its owner in one transaction.
+    pub async fn create_project(
+        &self,
+        request: &CreateProjectRequest,
+        source_language: &str,
+        owner_id: Uuid,
+    ) -> Result<Project, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let project: Project = sqlx::query_as(
+            r#"
+            INSERT INTO projects (
+                name, description, visual_novel_id, engine_version_id,
+                source_language, target_languages, visibility, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *
+        "#,
+        )
+        .bind(&request.name)
+        .bind(&request.description)
+        .bind(request.visual_novel_id)
+        .bind(request.engine_version_id)
+        .bind(source_language)
+        .bind(&request.target_languages)
+        .bind(request.visibility)
+        .bind(owner_id)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO project_members (project_id, user_id, role) VALUES ($1, $2, 'owner')",
+        )
+        .bind(project.id)
+        .bind(owner_id)
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+        Ok(project)
+    }
+
+    pub async fn update_project(
+        &self,
+        id: Uuid,
+        request: &UpdateProjectRequest,
+    ) -> Result<Option<Project>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let project: Option<Project> = sqlx::query_as(
+            r#"
+            UPDATE projects
+            SET name = COALESCE($2, name),
+                description = COALESCE($3, description),
+                engine_version_id = COALESCE($4, engine_version_id),
+                target_languages = COALESCE($5, target_languages),
+                visibility = COALESCE($6, visibility)
+            WHERE id = $1
+            RETURNING *
+        "#,
+        )
+        .bind(id)
+        .bind(&request.name)
+        .bind(&request.description)
+        .bind(request.engine_version_id)
+        .bind(&request.target_languages)
+        .bind(request.visibility)
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        // Progress is tracked per target language, recomputing it for every
+        // file takes too long for the request
+        if project.is_some() && request.target_languages.is_some() {
+            enqueue_job(
+                &mut transaction,
+                &Job::RefreshProjectStats { project_id: id },
+                None,
+            )
+            .await?;
+        }
+
+        transaction.commit().await?;
+        Ok(project)
+    }
+
+    pub async fn set_project_archived(
+        &self,
+        id: Uuid,
+        archived: bool,
+    ) -> Result<Option<Project>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            UPDATE projects
+            SET archived_at = CASE WHEN $2 THEN COALESCE(archived_at, NOW()) END
+            WHERE id = $1
+            RETURNING *
+        "#,
+        )
+        .bind(id)
+        .bind(archived)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    pub async fn get_project_members(
+        &self,
+        project_id: Uuid,
+    ) -> Result<Vec<ProjectMember>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT m.user_id, u.username, m.role, m.created_at
+            FROM project_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.project_id = $1
+            ORDER BY m.role, u.username
+        "#,
+        )
+        .bind(project_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn get_project_role(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<ProjectRole>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT role FROM project_members WHERE project_id = $1 AND user_id = $2",
+        )
+        .bind(project_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Adds the member or changes their role, unless that demotes the last
+    /// owner.
+    pub async fn set_project_member(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+        role: ProjectRole,
+    ) -> Result<MemberUpdate<ProjectRole>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let owners = lock_project_owners(&mut transaction, project_id).await?;
+        if role != ProjectRole::Owner && owners == [user_id] {
+            return Ok(MemberUpdate::LastOwner);
+        }
+        let previous = sqlx::query_scalar(
+            "SELECT role FROM project_members WHERE project_id = $1 AND user_id = $2",
+        )
+        .bind(project_id)
+        .bind(user_id)
+        .fetch_optional(&mut *transaction)
+        .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO project_members (project_id, user_id, role)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (project_id, user_id) DO UPDATE SET role = EXCLUDED.role
+        "#,
+        )
+        .bind(project_id)
+        .bind(user_id)
+        .bind(role)
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+        Ok(MemberUpdate::Applied(previous))
+    }
+
+    /// Removes the member unless they are the last owner.
+    pub async fn remove_project_member(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<MemberUpdate<ProjectRole>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let owners = lock_project_owners(&mut transaction, project_id).await?;
+        if owners == [user_id] {
+            return Ok(MemberUpdate::LastOwner);
+        }
+        let previous = sqlx::query_scalar(
+            "DELETE FROM project_members WHERE project_id = $1 AND user_id = $2 RETURNING role",
+        )
+        .bind(project_id)
+        .bind(user_id)
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+        Ok(MemberUpdate::Applied(previous))
+    }
+}
+
+/// Owners of the project, locked until the end of the transaction so that
+/// two owners can't demote each other at the same time.
+async fn lock_project_owners(
+    connection: &mut PgConnection,
+    project_id: Uuid,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    // Une rétrogradation concurrente attend ici puis relit les propriétaires
+    sqlx::query_scalar(
+        "SELECT user_id FROM project_members WHERE project_id = $1 AND role = 'owner' FOR UPDATE",
+    )
+    .bind(project_id)
+    .fetch_all(connection)
+    .await
+}
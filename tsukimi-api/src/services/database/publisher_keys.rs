@@ -0,0 +1,68 @@
+use tsukimi_core::models::PublisherKey;
+use uuid::Uuid;
+
+use super::DatabaseService;
+
+impl DatabaseService {
+    /// Fails with a unique violation if the key is already registered.
+    pub async fn create_publisher_key(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        public_key: &str,
+        fingerprint: &str,
+    ) -> Result<PublisherKey, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            INSERT INTO publisher_keys (user_id, name, public_key, fingerprint)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+        "#,
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(public_key)
+        .bind(fingerprint)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// Lists the keys of a user, revoked ones included.
+    pub async fn get_publisher_keys(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<PublisherKey>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM publisher_keys WHERE user_id = $1 ORDER BY created_at DESC")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    pub async fn get_publisher_key(&self, id: Uuid) -> Result<Option<PublisherKey>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM publisher_keys WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// Returns `None` if the key does not exist, does not belong to the user
+    /// or is already revoked.
+    pub async fn revoke_publisher_key(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<PublisherKey>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            UPDATE publisher_keys
+            SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            RETURNING *
+        "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+}
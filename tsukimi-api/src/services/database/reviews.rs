@@ -0,0 +1,288 @@
+use serde::Deserialize;
+use serde_json::json;
+use tsukimi_core::models::{ReviewQueueItem, ReviewState, Suggestion, Translation, WebhookEvent};
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use super::{
+    ApiPagination, DatabaseService,
+    stats::{count_translation, record_approval, record_translation, uncount_translation},
+    units::{TranslationUpdate, record_history},
+    webhooks::{WebhookScope, enqueue_event, enqueue_milestones},
+};
+
+#[derive(Deserialize, Debug, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReviewQueueFilter {
+    pub language: String,
+    pub file: Option<String>,
+    /// Without a state, lists what needs a reviewer: unreviewed translations
+    /// and units with pending suggestions
+    pub state: Option<ReviewState>,
+}
+
+impl DatabaseService {
+    pub async fn create_suggestion(
+        &self,
+        unit_id: Uuid,
+        language: &str,
+        text: &str,
+        author_id: Uuid,
+    ) -> Result<Suggestion, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            INSERT INTO suggestions (unit_id, language, text, base_revision, author_id)
+            VALUES ($1, $2, $3,
+                COALESCE((SELECT revision FROM translations WHERE unit_id = $1 AND language = $2), 0),
+                $4)
+            RETURNING *
+        "#,
+        )
+        .bind(unit_id)
+        .bind(language)
+        .bind(text)
+        .bind(author_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    pub async fn get_suggestions(
+        &self,
+        unit_id: Uuid,
+        language: &str,
+    ) -> Result<Vec<Suggestion>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM suggestions
+            WHERE unit_id = $1 AND language = $2
+            ORDER BY created_at DESC
+        "#,
+        )
+        .bind(unit_id)
+        .bind(language)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Returns the suggestion if it belongs to a unit of the project.
+    pub async fn get_project_suggestion(
+        &self,
+        project_id: Uuid,
+        suggestion_id: Uuid,
+    ) -> Result<Option<Suggestion>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT s.*
+            FROM suggestions s
+            JOIN source_units u ON u.id = s.unit_id
+            WHERE s.id = $1 AND u.project_id = $2
+        "#,
+        )
+        .bind(suggestion_id)
+        .bind(project_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Makes a pending suggestion the current, approved translation.
+    /// Returns `None` if the suggestion was already reviewed, and a conflict
+    /// if the translation changed since the suggestion was made.
+    pub async fn approve_suggestion(
+        &self,
+        suggestion_id: Uuid,
+        reviewer_id: Uuid,
+        comment: Option<&str>,
+    ) -> Result<Option<TranslationUpdate>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let suggestion: Option<Suggestion> = sqlx::query_as(
+            r#"
+            UPDATE suggestions
+            SET status = 'approved', reviewer_id = $2, review_comment = $3, reviewed_at = NOW()
+            WHERE id = $1 AND status = 'pending'
+            RETURNING *
+        "#,
+        )
+        .bind(suggestion_id)
+        .bind(reviewer_id)
+        .bind(comment)
+        .fetch_optional(&mut *transaction)
+        .await?;
+        let Some(suggestion) = suggestion else {
+            return Ok(None);
+        };
+        let project_id =
+            uncount_translation(&mut transaction, suggestion.unit_id, &suggestion.language).await?;
+
+        // Same guard as `update_translation`, the suggestion must be based on
+        // the current revision
+        let translation: Option<Translation> = sqlx::query_as(
+            r#"
+            INSERT INTO translations (unit_id, language, text, revision, review_state, updated_by)
+            VALUES ($1, $2, $3, 1, 'approved', $4)
+            ON CONFLICT (unit_id, language) DO UPDATE
+            SET text = EXCLUDED.text,
+                revision = translations.revision + 1,
+                fuzzy = false,
+                review_state = 'approved',
+                updated_by = EXCLUDED.updated_by,
+                updated_at = NOW()
+            WHERE translations.revision = $5
+            RETURNING *
+        "#,
+        )
+        .bind(suggestion.unit_id)
+        .bind(&suggestion.language)
+        .bind(&suggestion.text)
+        .bind(suggestion.author_id)
+        .bind(suggestion.base_revision)
+        .fetch_optional(&mut *transaction)
+        .await?;
+        let Some(translation) = translation else {
+            // The suggestion stays pending
+            transaction.rollback().await?;
+            return Ok(Some(TranslationUpdate::Conflict(
+                self.get_translation(suggestion.unit_id, &suggestion.language)
+                    .await?,
+            )));
+        };
+
+        // The history credits the translator, the reviewer is on the suggestion
+        let author_id = suggestion.author_id.unwrap_or(reviewer_id);
+        record_history(&mut transaction, &translation, author_id).await?;
+        record_translation(
+            &mut transaction,
+            suggestion.unit_id,
+            &suggestion.language,
+            author_id,
+        )
+        .await?;
+        record_approval(
+            &mut transaction,
+            suggestion.unit_id,
+            &suggestion.language,
+            reviewer_id,
+        )
+        .await?;
+        count_translation(&mut transaction, suggestion.unit_id, &suggestion.language).await?;
+        enqueue_event(
+            &mut transaction,
+            WebhookScope::Project(project_id),
+            WebhookEvent::SuggestionApproved,
+            json!({
+                "suggestion_id": suggestion.id,
+                "unit_id": suggestion.unit_id,
+                "language": suggestion.language,
+                "text": suggestion.text,
+                "author_id": suggestion.author_id,
+                "reviewer_id": reviewer_id,
+                "revision": translation.revision,
+            }),
+        )
+        .await?;
+        enqueue_milestones(&mut transaction, project_id, &suggestion.language).await?;
+
+        transaction.commit().await?;
+        Ok(Some(TranslationUpdate::Saved(translation)))
+    }
+
+    /// Returns `None` if the suggestion was already reviewed.
+    pub async fn reject_suggestion(
+        &self,
+        suggestion_id: Uuid,
+        reviewer_id: Uuid,
+        comment: Option<&str>,
+    ) -> Result<Option<Suggestion>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            UPDATE suggestions
+            SET status = 'rejected', reviewer_id = $2, review_comment = $3, reviewed_at = NOW()
+            WHERE id = $1 AND status = 'pending'
+            RETURNING *
+        "#,
+        )
+        .bind(suggestion_id)
+        .bind(reviewer_id)
+        .bind(comment)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    pub async fn set_review_state(
+        &self,
+        unit_id: Uuid,
+        language: &str,
+        state: ReviewState,
+        reviewer_id: Uuid,
+    ) -> Result<Option<Translation>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+        uncount_translation(&mut transaction, unit_id, language).await?;
+
+        let translation: Option<Translation> = sqlx::query_as(
+            r#"
+            UPDATE translations
+            SET review_state = $3
+            WHERE unit_id = $1 AND language = $2
+            RETURNING *
+        "#,
+        )
+        .bind(unit_id)
+        .bind(language)
+        .bind(state)
+        .fetch_optional(&mut *transaction)
+        .await?;
+        let Some(translation) = translation else {
+            return Ok(None);
+        };
+
+        if state == ReviewState::Approved {
+            record_approval(&mut transaction, unit_id, language, reviewer_id).await?;
+        }
+        count_translation(&mut transaction, unit_id, language).await?;
+
+        transaction.commit().await?;
+        Ok(Some(translation))
+    }
+
+    pub async fn get_review_queue(
+        &self,
+        project_id: Uuid,
+        pagination: ApiPagination,
+        filter: ReviewQueueFilter,
+    ) -> Result<Vec<ReviewQueueItem>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM (
+                SELECT u.id AS unit_id, u.file, u.key, u.source_text, u.position,
+                    t.text AS translation,
+                    COALESCE(t.revision, 0) AS revision,
+                    t.review_state,
+                    (
+                        SELECT COUNT(*) FROM suggestions s
+                        WHERE s.unit_id = u.id AND s.language = $2 AND s.status = 'pending'
+                    ) AS pending_suggestions
+                FROM source_units u
+                LEFT JOIN translations t ON t.unit_id = u.id AND t.language = $2
+                WHERE u.project_id = $1
+                    AND NOT u.obsolete
+                    AND ($3::text IS NULL OR u.file = $3)
+            ) queue
+            WHERE CASE
+                WHEN $4::review_state IS NULL
+                    THEN pending_suggestions > 0 OR review_state = 'translated'
+                ELSE review_state = $4
+            END
+            ORDER BY file, position
+            LIMIT $5 OFFSET $6
+        "#,
+        )
+        .bind(project_id)
+        .bind(filter.language)
+        .bind(filter.file)
+        .bind(filter.state)
+        .bind(pagination.limit())
+        .bind(pagination.offset())
+        .fetch_all(&self.pool)
+        .await
+    }
+}
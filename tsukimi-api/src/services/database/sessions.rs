@@ -0,0 +1,85 @@
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::DatabaseService;
+
+impl DatabaseService {
+    pub async fn create_session(
+        &self,
+        user_id: Uuid,
+        refresh_token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<Uuid, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO sessions (user_id, refresh_token_hash, expires_at)
+            VALUES ($1, $2, $3)
+            RETURNING id
+        "#,
+        )
+        .bind(user_id)
+        .bind(refresh_token_hash)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// Swaps the refresh token of a live session in a single statement so a
+    /// refresh token can only ever be used once.
+    /// Returns the `(session_id, user_id)` pair, `None` if the token is unknown,
+    /// expired or revoked.
+    pub async fn rotate_session(
+        &self,
+        refresh_token_hash: &str,
+        new_refresh_token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<Option<(Uuid, Uuid)>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            UPDATE sessions
+            SET refresh_token_hash = $2, expires_at = $3, refreshed_at = NOW()
+            WHERE refresh_token_hash = $1
+                AND revoked_at IS NULL
+                AND expires_at > NOW()
+            RETURNING id, user_id
+        "#,
+        )
+        .bind(refresh_token_hash)
+        .bind(new_refresh_token_hash)
+        .bind(expires_at)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    pub async fn revoke_session(&self, session_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE sessions SET revoked_at = NOW() WHERE id = $1 AND revoked_at IS NULL")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn revoke_user_sessions(&self, user_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE sessions SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn is_session_active(&self, session_id: Uuid) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM sessions
+                WHERE id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+            )
+        "#,
+        )
+        .bind(session_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+}
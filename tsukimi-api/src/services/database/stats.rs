@@ -0,0 +1,354 @@
+//! Project statistics are read from aggregate tables kept up to date by the
+//! write paths, so the stats endpoint never scans the units of large projects.
+
+use serde::Deserialize;
+use sqlx::PgConnection;
+use tsukimi_core::models::{
+    ActivityPoint, ContributorStats, FileProgress, LanguageProgress, ProjectStats,
+};
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use super::DatabaseService;
+
+/// Size of the contributor leaderboard
+static MAX_CONTRIBUTORS: i64 = 50;
+
+#[derive(Deserialize, Debug, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StatsFilter {
+    pub language: Option<String>,
+    /// Number of days of activity to return
+    #[serde(default = "default_days")]
+    pub days: u32,
+}
+
+fn default_days() -> u32 {
+    30
+}
+
+impl DatabaseService {
+    pub async fn get_project_stats(
+        &self,
+        project_id: Uuid,
+        filter: StatsFilter,
+    ) -> Result<ProjectStats, sqlx::Error> {
+        let languages: Vec<LanguageProgress> = sqlx::query_as(
+            r#"
+            SELECT language,
+                SUM(units)::bigint AS units, SUM(words)::bigint AS words,
+                SUM(translated_units)::bigint AS translated_units,
+                SUM(translated_words)::bigint AS translated_words,
+                SUM(reviewed_units)::bigint AS reviewed_units,
+                SUM(reviewed_words)::bigint AS reviewed_words,
+                SUM(fuzzy_units)::bigint AS fuzzy_units,
+                SUM(fuzzy_words)::bigint AS fuzzy_words
+            FROM project_file_stats
+            WHERE project_id = $1 AND ($2::text IS NULL OR language = $2)
+            GROUP BY language
+            ORDER BY language
+        "#,
+        )
+        .bind(project_id)
+        .bind(&filter.language)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let files: Vec<FileProgress> = sqlx::query_as(
+            r#"
+            SELECT file, language, units, words, translated_units, translated_words,
+                reviewed_units, reviewed_words, fuzzy_units, fuzzy_words
+            FROM project_file_stats
+            WHERE project_id = $1 AND ($2::text IS NULL OR language = $2)
+            ORDER BY file, language
+        "#,
+        )
+        .bind(project_id)
+        .bind(&filter.language)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let contributors: Vec<ContributorStats> = sqlx::query_as(
+            r#"
+            SELECT c.user_id, u.username, c.language, c.translations, c.translated_words, c.approvals
+            FROM project_contributor_stats c
+            JOIN users u ON u.id = c.user_id
+            WHERE c.project_id = $1 AND ($2::text IS NULL OR c.language = $2)
+            ORDER BY c.translated_words DESC, c.approvals DESC
+            LIMIT $3
+        "#,
+        )
+        .bind(project_id)
+        .bind(&filter.language)
+        .bind(MAX_CONTRIBUTORS)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let activity: Vec<ActivityPoint> = sqlx::query_as(
+            r#"
+            SELECT day, language, translations, approvals
+            FROM project_activity
+            WHERE project_id = $1
+                AND ($2::text IS NULL OR language = $2)
+                AND day > CURRENT_DATE - $3::integer
+            ORDER BY day, language
+        "#,
+        )
+        .bind(project_id)
+        .bind(&filter.language)
+        .bind(filter.days as i32)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ProjectStats {
+            languages,
+            files,
+            contributors,
+            activity,
+        })
+    }
+
+    /// Recomputes the progress of every file of the project.
+    pub async fn refresh_project_stats(&self, project_id: Uuid) -> Result<(), sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+        lock_project_stats(&mut transaction, project_id, true).await?;
+        refresh_file_stats(&mut transaction, project_id, None).await?;
+        transaction.commit().await
+    }
+}
+
+/// Serializes the recomputations of the project's stats, which delete and
+/// insert its rows, with the incremental updates, which share the lock.
+/// Taken before any translation of the project is locked, or two
+/// transactions could wait on each other.
+pub(super) async fn lock_project_stats(
+    connection: &mut PgConnection,
+    project_id: Uuid,
+    exclusive: bool,
+) -> Result<(), sqlx::Error> {
+    let query = match exclusive {
+        true => "SELECT pg_advisory_xact_lock(hashtext($1::text))",
+        false => "SELECT pg_advisory_xact_lock_shared(hashtext($1::text))",
+    };
+    sqlx::query(query)
+        .bind(project_id)
+        .execute(&mut *connection)
+        .await?;
+    Ok(())
+}
+
+/// Recomputes the progress of `files` (every file when `None`) in every target
+/// language of the project. The caller holds the exclusive stats lock.
+pub(super) async fn refresh_file_stats(
+    connection: &mut PgConnection,
+    project_id: Uuid,
+    files: Option<&[&str]>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM project_file_stats WHERE project_id = $1 AND ($2::text[] IS NULL OR file = ANY($2))",
+    )
+    .bind(project_id)
+    .bind(files)
+    .execute(&mut *connection)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO project_file_stats (
+            project_id, file, language, units, words,
+            translated_units, translated_words, reviewed_units, reviewed_words,
+            fuzzy_units, fuzzy_words
+        )
+        SELECT u.project_id, u.file, l.language,
+            COUNT(*),
+            COALESCE(SUM(u.words), 0),
+            COUNT(*) FILTER (WHERE NOT t.fuzzy),
+            COALESCE(SUM(u.words) FILTER (WHERE NOT t.fuzzy), 0),
+            COUNT(*) FILTER (WHERE NOT t.fuzzy AND t.review_state = 'approved'),
+            COALESCE(SUM(u.words) FILTER (WHERE NOT t.fuzzy AND t.review_state = 'approved'), 0),
+            COUNT(*) FILTER (WHERE t.fuzzy),
+            COALESCE(SUM(u.words) FILTER (WHERE t.fuzzy), 0)
+        FROM source_units u
+        JOIN projects p ON p.id = u.project_id
+        CROSS JOIN LATERAL UNNEST(p.target_languages) AS l(language)
+        LEFT JOIN translations t ON t.unit_id = u.id AND t.language = l.language
+        WHERE u.project_id = $1
+            AND NOT u.obsolete
+            AND ($2::text[] IS NULL OR u.file = ANY($2))
+        GROUP BY u.project_id, u.file, l.language
+    "#,
+    )
+    .bind(project_id)
+    .bind(files)
+    .execute(&mut *connection)
+    .await?;
+    Ok(())
+}
+
+/// Takes the translation of the unit out of the stats of its file, before it
+/// changes. The row stays locked until the transaction ends, so the state
+/// removed here is the one the change replaces. Returns the project.
+pub(super) async fn uncount_translation(
+    connection: &mut PgConnection,
+    unit_id: Uuid,
+    language: &str,
+) -> Result<Uuid, sqlx::Error> {
+    let project_id: Uuid = sqlx::query_scalar("SELECT project_id FROM source_units WHERE id = $1")
+        .bind(unit_id)
+        .fetch_one(&mut *connection)
+        .await?;
+    lock_project_stats(connection, project_id, false).await?;
+    sqlx::query("SELECT 1 FROM translations WHERE unit_id = $1 AND language = $2 FOR UPDATE")
+        .bind(unit_id)
+        .bind(language)
+        .execute(&mut *connection)
+        .await?;
+    count_progress(connection, unit_id, language, -1).await?;
+    Ok(project_id)
+}
+
+/// Puts the translation of the unit back in the stats of its file, once
+/// changed.
+pub(super) async fn count_translation(
+    connection: &mut PgConnection,
+    unit_id: Uuid,
+    language: &str,
+) -> Result<(), sqlx::Error> {
+    count_progress(connection, unit_id, language, 1).await
+}
+
+/// Adds `sign` times the progress the translation stands for to the counters
+/// of its file. Units don't count for languages the project has no row for
+/// yet, the refresh queued when the languages change creates it.
+async fn count_progress(
+    connection: &mut PgConnection,
+    unit_id: Uuid,
+    language: &str,
+    sign: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE project_file_stats s
+        SET translated_units = s.translated_units + $3 * c.translated::int,
+            translated_words = s.translated_words + $3 * c.translated::int * c.words,
+            reviewed_units = s.reviewed_units + $3 * c.reviewed::int,
+            reviewed_words = s.reviewed_words + $3 * c.reviewed::int * c.words,
+            fuzzy_units = s.fuzzy_units + $3 * c.fuzzy::int,
+            fuzzy_words = s.fuzzy_words + $3 * c.fuzzy::int * c.words
+        FROM (
+            SELECT u.project_id, u.file, u.words::bigint AS words,
+                COALESCE(NOT t.fuzzy, false) AS translated,
+                COALESCE(NOT t.fuzzy AND t.review_state = 'approved', false) AS reviewed,
+                COALESCE(t.fuzzy, false) AS fuzzy
+            FROM source_units u
+            LEFT JOIN translations t ON t.unit_id = u.id AND t.language = $2
+            WHERE u.id = $1 AND NOT u.obsolete
+        ) c
+        WHERE s.project_id = c.project_id AND s.file = c.file AND s.language = $2
+    "#,
+    )
+    .bind(unit_id)
+    .bind(language)
+    .bind(sign)
+    .execute(&mut *connection)
+    .await?;
+    Ok(())
+}
+
+/// Credits `author_id` with a new translation revision of the unit.
+pub(super) async fn record_translation(
+    connection: &mut PgConnection,
+    unit_id: Uuid,
+    language: &str,
+    author_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    record_contribution(connection, unit_id, language, author_id, 1, 0).await
+}
+
+/// Credits `reviewer_id` with the approval of a translation of the unit.
+pub(super) async fn record_approval(
+    connection: &mut PgConnection,
+    unit_id: Uuid,
+    language: &str,
+    reviewer_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    record_contribution(connection, unit_id, language, reviewer_id, 0, 1).await
+}
+
+async fn record_contribution(
+    connection: &mut PgConnection,
+    unit_id: Uuid,
+    language: &str,
+    user_id: Uuid,
+    translations: i64,
+    approvals: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO project_contributor_stats
+            (project_id, user_id, language, translations, translated_words, approvals)
+        SELECT project_id, $2, $3, $4, words * $4, $5
+        FROM source_units WHERE id = $1
+        ON CONFLICT (project_id, user_id, language) DO UPDATE
+        SET translations = project_contributor_stats.translations + EXCLUDED.translations,
+            translated_words = project_contributor_stats.translated_words + EXCLUDED.translated_words,
+            approvals = project_contributor_stats.approvals + EXCLUDED.approvals
+    "#,
+    )
+    .bind(unit_id)
+    .bind(user_id)
+    .bind(language)
+    .bind(translations)
+    .bind(approvals)
+    .execute(&mut *connection)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO project_activity (project_id, day, language, translations, approvals)
+        SELECT project_id, CURRENT_DATE, $2, $3, $4
+        FROM source_units WHERE id = $1
+        ON CONFLICT (project_id, day, language) DO UPDATE
+        SET translations = project_activity.translations + EXCLUDED.translations,
+            approvals = project_activity.approvals + EXCLUDED.approvals
+    "#,
+    )
+    .bind(unit_id)
+    .bind(language)
+    .bind(translations)
+    .bind(approvals)
+    .execute(&mut *connection)
+    .await?;
+    Ok(())
+}
+
+/// Words of a source text. Scripts written without spaces (Japanese, Chinese)
+/// count one word per character, like most translation tools do.
+pub(super) fn word_count(text: &str) -> i32 {
+    let mut count = 0;
+    let mut in_word = false;
+    for c in text.chars() {
+        if is_cjk(c) {
+            count += 1;
+            in_word = false;
+        } else if c.is_alphanumeric() {
+            if !in_word {
+                count += 1;
+            }
+            in_word = true;
+        } else if c.is_whitespace() {
+            in_word = false;
+        }
+    }
+    count
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}' // Hiragana, Katakana
+        | '\u{3400}'..='\u{4DBF}' // CJK Extension A
+        | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+        | '\u{AC00}'..='\u{D7AF}' // Hangul syllables
+        | '\u{FF66}'..='\u{FF9F}' // Half-width Katakana
+    )
+}
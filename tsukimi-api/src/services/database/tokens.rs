@@ -0,0 +1,93 @@
+use time::OffsetDateTime;
+use tsukimi_core::auth::{PersonalAccessToken, TokenScope};
+use uuid::Uuid;
+
+use super::DatabaseService;
+
+impl DatabaseService {
+    pub async fn create_personal_access_token(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        token_hash: &str,
+        token_prefix: &str,
+        scopes: &[TokenScope],
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<PersonalAccessToken, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            INSERT INTO personal_access_tokens
+                (user_id, name, token_hash, token_prefix, scopes, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, name, token_prefix, scopes, expires_at, last_used_at, created_at
+        "#,
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(token_hash)
+        .bind(token_prefix)
+        .bind(scopes.iter().map(|s| s.as_str()).collect::<Vec<_>>())
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// Lists the tokens of a user which have not been revoked, expired ones included.
+    pub async fn get_personal_access_tokens(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<PersonalAccessToken>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT id, name, token_prefix, scopes, expires_at, last_used_at, created_at
+            FROM personal_access_tokens
+            WHERE user_id = $1 AND revoked_at IS NULL
+            ORDER BY created_at DESC
+        "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Returns `None` if the token does not exist or does not belong to the user.
+    pub async fn revoke_personal_access_token(
+        &self,
+        user_id: Uuid,
+        token_id: Uuid,
+    ) -> Result<Option<PersonalAccessToken>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            UPDATE personal_access_tokens
+            SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            RETURNING id, name, token_prefix, scopes, expires_at, last_used_at, created_at
+        "#,
+        )
+        .bind(token_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Looks up a live token by its hash and records its use.
+    /// Returns the `(token_id, user_id, scopes)` triple.
+    pub async fn use_personal_access_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<(Uuid, Uuid, Vec<TokenScope>)>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            UPDATE personal_access_tokens
+            SET last_used_at = NOW()
+            WHERE token_hash = $1
+                AND revoked_at IS NULL
+                AND (expires_at IS NULL OR expires_at > NOW())
+            RETURNING id, user_id, scopes
+        "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await
+    }
+}
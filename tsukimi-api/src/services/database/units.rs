@@ -0,0 +1,335 @@
+use serde::Deserialize;
+use sqlx::PgConnection;
+use tsukimi_core::models::{
+    ExtractedUnit, ProjectFile, Translation, TranslationRevision, TranslationUnit,
+    UploadExtractionResponse,
+};
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use super::{
+    ApiPagination, DatabaseService,
+    stats::{
+        count_translation, lock_project_stats, record_translation, refresh_file_stats,
+        uncount_translation, word_count,
+    },
+    webhooks::enqueue_milestones,
+};
+
+#[derive(Deserialize, Debug, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UnitFilter {
+    pub file: Option<String>,
+    /// Joins the translations of this language
+    pub language: Option<String>,
+    #[serde(default)]
+    pub include_obsolete: bool,
+}
+
+/// Outcome of an optimistic translation update.
+pub enum TranslationUpdate {
+    Saved(Translation),
+    /// Someone else saved a revision meanwhile, holds the current one
+    Conflict(Option<Translation>),
+}
+
+impl DatabaseService {
+    /// Replaces the extraction of every file present in `units` in one
+    /// transaction. Translations of units whose source text changed are
+    /// flagged as fuzzy. Also returns the units this upload made obsolete.
+    pub async fn upload_extraction(
+        &self,
+        project_id: Uuid,
+        units: &[ExtractedUnit],
+    ) -> Result<(UploadExtractionResponse, Vec<Uuid>), sqlx::Error> {
+        let files: Vec<&str> = units.iter().map(|u| u.file.as_str()).collect();
+        let keys: Vec<&str> = units.iter().map(|u| u.key.as_str()).collect();
+        let texts: Vec<&str> = units.iter().map(|u| u.source_text.as_str()).collect();
+        let contexts: Vec<Option<&str>> = units.iter().map(|u| u.context.as_deref()).collect();
+        let words: Vec<i32> = units.iter().map(|u| word_count(&u.source_text)).collect();
+        // Position inside its own file, in upload order
+        let mut counters = std::collections::HashMap::<&str, i32>::new();
+        let positions: Vec<i32> = units
+            .iter()
+            .map(|u| {
+                let counter = counters.entry(u.file.as_str()).or_default();
+                *counter += 1;
+                *counter
+            })
+            .collect();
+
+        let mut transaction = self.pool.begin().await?;
+        lock_project_stats(&mut transaction, project_id, true).await?;
+
+        sqlx::query(
+            r#"
+            UPDATE translations t
+            SET fuzzy = true
+            FROM source_units u, UNNEST($2::text[], $3::text[], $4::text[]) AS n(file, key, source_text)
+            WHERE t.unit_id = u.id
+                AND u.project_id = $1 AND u.file = n.file AND u.key = n.key
+                AND u.source_text <> n.source_text
+        "#,
+        )
+        .bind(project_id)
+        .bind(&files)
+        .bind(&keys)
+        .bind(&texts)
+        .execute(&mut *transaction)
+        .await?;
+
+        let active: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE source_units SET obsolete = true
+            WHERE project_id = $1 AND file = ANY($2) AND NOT obsolete
+            RETURNING id
+        "#,
+        )
+        .bind(project_id)
+        .bind(&files)
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        let (inserted, updated): (i64, i64) = sqlx::query_as(
+            r#"
+            WITH upserted AS (
+                INSERT INTO source_units (project_id, file, key, position, source_text, context, words)
+                SELECT $1, * FROM UNNEST($2::text[], $3::text[], $4::int[], $5::text[], $6::text[], $7::int[])
+                ON CONFLICT (project_id, file, key) DO UPDATE
+                SET position = EXCLUDED.position,
+                    source_text = EXCLUDED.source_text,
+                    context = EXCLUDED.context,
+                    words = EXCLUDED.words,
+                    obsolete = false
+                RETURNING (xmax = 0) AS inserted
+            )
+            SELECT
+                COUNT(*) FILTER (WHERE inserted),
+                COUNT(*) FILTER (WHERE NOT inserted)
+            FROM upserted
+        "#,
+        )
+        .bind(project_id)
+        .bind(&files)
+        .bind(&keys)
+        .bind(&positions)
+        .bind(&texts)
+        .bind(&contexts)
+        .bind(&words)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        let obsolete: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM source_units WHERE project_id = $1 AND file = ANY($2) AND obsolete",
+        )
+        .bind(project_id)
+        .bind(&files)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        let obsoleted: Vec<Uuid> =
+            sqlx::query_scalar("SELECT id FROM source_units WHERE id = ANY($1) AND obsolete")
+                .bind(&active)
+                .fetch_all(&mut *transaction)
+                .await?;
+
+        let mut uploaded_files = files.clone();
+        uploaded_files.sort_unstable();
+        uploaded_files.dedup();
+        refresh_file_stats(&mut transaction, project_id, Some(&uploaded_files)).await?;
+
+        transaction.commit().await?;
+        Ok((
+            UploadExtractionResponse {
+                inserted,
+                updated,
+                obsolete,
+            },
+            obsoleted,
+        ))
+    }
+
+    pub async fn get_project_files(
+        &self,
+        project_id: Uuid,
+    ) -> Result<Vec<ProjectFile>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT file, COUNT(*) AS units
+            FROM source_units
+            WHERE project_id = $1 AND NOT obsolete
+            GROUP BY file
+            ORDER BY file
+        "#,
+        )
+        .bind(project_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Pages through the units of a project in file order, `query` searches
+    /// the source text.
+    pub async fn get_units(
+        &self,
+        project_id: Uuid,
+        pagination: ApiPagination,
+        filter: UnitFilter,
+    ) -> Result<Vec<TranslationUnit>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT u.id, u.file, u.key, u.position, u.source_text, u.context, u.obsolete,
+                t.text AS translation,
+                COALESCE(t.revision, 0) AS revision,
+                COALESCE(t.fuzzy, false) AS fuzzy,
+                t.review_state
+            FROM source_units u
+            LEFT JOIN translations t ON t.unit_id = u.id AND t.language = $3
+            WHERE u.project_id = $1
+                AND ($2::text IS NULL OR u.file = $2)
+                AND ($4 OR NOT u.obsolete)
+                AND u.source_text ILIKE $5
+            ORDER BY u.file, u.position
+            LIMIT $6 OFFSET $7
+        "#,
+        )
+        .bind(project_id)
+        .bind(filter.file)
+        .bind(filter.language)
+        .bind(filter.include_obsolete)
+        .bind(format!("%{}%", pagination.query))
+        .bind(pagination.limit())
+        .bind(pagination.offset())
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn unit_belongs_to_project(
+        &self,
+        unit_id: Uuid,
+        project_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM source_units WHERE id = $1 AND project_id = $2)",
+        )
+        .bind(unit_id)
+        .bind(project_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    pub async fn get_translation(
+        &self,
+        unit_id: Uuid,
+        language: &str,
+    ) -> Result<Option<Translation>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM translations WHERE unit_id = $1 AND language = $2")
+            .bind(unit_id)
+            .bind(language)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// Saves a new revision if `expected_revision` is still the current one
+    /// and records it in the history.
+    pub async fn update_translation(
+        &self,
+        unit_id: Uuid,
+        language: &str,
+        text: &str,
+        expected_revision: i32,
+        author_id: Uuid,
+    ) -> Result<TranslationUpdate, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+        let project_id = uncount_translation(&mut transaction, unit_id, language).await?;
+
+        let saved: Option<Translation> = if expected_revision == 0 {
+            sqlx::query_as(
+                r#"
+                INSERT INTO translations (unit_id, language, text, revision, updated_by)
+                VALUES ($1, $2, $3, 1, $4)
+                ON CONFLICT (unit_id, language) DO NOTHING
+                RETURNING *
+            "#,
+            )
+            .bind(unit_id)
+            .bind(language)
+            .bind(text)
+            .bind(author_id)
+            .fetch_optional(&mut *transaction)
+            .await?
+        } else {
+            sqlx::query_as(
+                r#"
+                UPDATE translations
+                SET text = $3, revision = revision + 1, fuzzy = false,
+                    review_state = 'translated', updated_by = $4, updated_at = NOW()
+                WHERE unit_id = $1 AND language = $2 AND revision = $5
+                RETURNING *
+            "#,
+            )
+            .bind(unit_id)
+            .bind(language)
+            .bind(text)
+            .bind(author_id)
+            .bind(expected_revision)
+            .fetch_optional(&mut *transaction)
+            .await?
+        };
+
+        let Some(translation) = saved else {
+            transaction.rollback().await?;
+            return Ok(TranslationUpdate::Conflict(
+                self.get_translation(unit_id, language).await?,
+            ));
+        };
+
+        record_history(&mut transaction, &translation, author_id).await?;
+        record_translation(&mut transaction, unit_id, language, author_id).await?;
+        count_translation(&mut transaction, unit_id, language).await?;
+        enqueue_milestones(&mut transaction, project_id, language).await?;
+
+        transaction.commit().await?;
+        Ok(TranslationUpdate::Saved(translation))
+    }
+
+    pub async fn get_translation_history(
+        &self,
+        unit_id: Uuid,
+        language: &str,
+    ) -> Result<Vec<TranslationRevision>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT h.revision, h.text, h.author_id, u.username AS author, h.created_at
+            FROM translation_history h
+            LEFT JOIN users u ON u.id = h.author_id
+            WHERE h.unit_id = $1 AND h.language = $2
+            ORDER BY h.revision DESC
+        "#,
+        )
+        .bind(unit_id)
+        .bind(language)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
+
+pub(super) async fn record_history(
+    connection: &mut PgConnection,
+    translation: &Translation,
+    author_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO translation_history (unit_id, language, revision, text, author_id)
+        VALUES ($1, $2, $3, $4, $5)
+    "#,
+    )
+    .bind(translation.unit_id)
+    .bind(&translation.language)
+    .bind(translation.revision)
+    .bind(&translation.text)
+    .bind(author_id)
+    .execute(connection)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use tsukimi_core::models::User;
+use uuid::Uuid;
+
+use super::DatabaseService;
+
+impl DatabaseService {
+    pub async fn get_user(&self, id: Uuid) -> Result<Option<User>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+}
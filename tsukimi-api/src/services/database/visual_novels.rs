@@ -0,0 +1,126 @@
+use serde::Deserialize;
+use tsukimi_core::models::{VisualNovel, VisualNovelRequest};
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use super::{ApiPagination, DatabaseService};
+
+/// Filters applied on top of the full-text `query` of [`ApiPagination`].
+#[derive(Deserialize, Debug, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct VisualNovelFilter {
+    pub engine_id: Option<Uuid>,
+    pub original_language: Option<String>,
+}
+
+impl DatabaseService {
+    /// Ranks by relevance when a query is given, alphabetically otherwise.
+    pub async fn get_visual_novels(
+        &self,
+        pagination: ApiPagination,
+        filter: VisualNovelFilter,
+    ) -> Result<Vec<VisualNovel>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT *
+            FROM visual_novels
+            WHERE ($1 = '' OR search_vector @@ websearch_to_tsquery('simple', $1))
+                AND ($2::uuid IS NULL OR engine_id = $2)
+                AND ($3::text IS NULL OR original_language = $3)
+            ORDER BY
+                CASE WHEN $1 = '' THEN 0
+                ELSE ts_rank(search_vector, websearch_to_tsquery('simple', $1)) END DESC,
+                title ASC
+            LIMIT $4 OFFSET $5
+        "#,
+        )
+        .bind(pagination.query.trim())
+        .bind(filter.engine_id)
+        .bind(filter.original_language)
+        .bind(pagination.limit())
+        .bind(pagination.offset())
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn get_visual_novel(&self, id: Uuid) -> Result<Option<VisualNovel>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM visual_novels WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    pub async fn get_visual_novel_creator(&self, id: Uuid) -> Result<Option<Uuid>, sqlx::Error> {
+        sqlx::query_scalar("SELECT created_by FROM visual_novels WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map(Option::flatten)
+    }
+
+    pub async fn create_visual_novel(
+        &self,
+        request: &VisualNovelRequest,
+        created_by: Uuid,
+    ) -> Result<VisualNovel, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            INSERT INTO visual_novels (
+                title, original_title, developer, release_date, original_language,
+                engine_id, cover_image_url, vndb_id, external_ids, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING *
+        "#,
+        )
+        .bind(&request.title)
+        .bind(&request.original_title)
+        .bind(&request.developer)
+        .bind(request.release_date)
+        .bind(&request.original_language)
+        .bind(request.engine_id)
+        .bind(&request.cover_image_url)
+        .bind(&request.vndb_id)
+        .bind(sqlx::types::Json(&request.external_ids))
+        .bind(created_by)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    pub async fn update_visual_novel(
+        &self,
+        id: Uuid,
+        request: &VisualNovelRequest,
+    ) -> Result<Option<VisualNovel>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            UPDATE visual_novels
+            SET title = $2, original_title = $3, developer = $4, release_date = $5,
+                original_language = $6, engine_id = $7, cover_image_url = $8,
+                vndb_id = $9, external_ids = $10
+            WHERE id = $1
+            RETURNING *
+        "#,
+        )
+        .bind(id)
+        .bind(&request.title)
+        .bind(&request.original_title)
+        .bind(&request.developer)
+        .bind(request.release_date)
+        .bind(&request.original_language)
+        .bind(request.engine_id)
+        .bind(&request.cover_image_url)
+        .bind(&request.vndb_id)
+        .bind(sqlx::types::Json(&request.external_ids))
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    pub async fn delete_visual_novel(&self, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM visual_novels WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
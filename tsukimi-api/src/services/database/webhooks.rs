@@ -0,0 +1,330 @@
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::PgConnection;
+use time::OffsetDateTime;
+use tsukimi_core::models::{
+    DeliveryStatus, UpdateWebhookRequest, Webhook, WebhookDelivery, WebhookEvent,
+};
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use super::{ApiPagination, DatabaseService};
+
+/// Progress percentages announced by `project.milestone_reached`
+static MILESTONES: [i16; 4] = [25, 50, 75, 100];
+
+/// Columns of [`Webhook`], the secret is left out.
+static WEBHOOK_COLUMNS: &str =
+    "id, engine_id, project_id, url, events, active, created_by, created_at, updated_at";
+
+/// What a webhook is registered on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookScope {
+    Engine(Uuid),
+    Project(Uuid),
+}
+
+impl WebhookScope {
+    fn ids(self) -> (Option<Uuid>, Option<Uuid>) {
+        match self {
+            WebhookScope::Engine(id) => (Some(id), None),
+            WebhookScope::Project(id) => (None, Some(id)),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WebhookFilter {
+    /// Exactly one of `engine_id` and `project_id` must be given
+    pub engine_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+}
+
+/// A delivery claimed by the dispatcher, with what is needed to send it.
+#[derive(sqlx::FromRow, Debug, Clone)]
+pub struct PendingDelivery {
+    pub id: Uuid,
+    pub event: WebhookEvent,
+    pub payload: sqlx::types::Json<serde_json::Value>,
+    pub attempts: i32,
+    pub url: String,
+    pub secret: String,
+}
+
+/// Outcome of one delivery attempt.
+pub struct DeliveryAttempt<'a> {
+    pub status: DeliveryStatus,
+    /// Retry time while the delivery is still pending
+    pub next_attempt_at: Option<OffsetDateTime>,
+    pub response_status: Option<i32>,
+    pub response_body: Option<&'a str>,
+    pub error: Option<&'a str>,
+}
+
+impl DatabaseService {
+    pub async fn create_webhook(
+        &self,
+        scope: WebhookScope,
+        url: &str,
+        secret: &str,
+        events: &[WebhookEvent],
+        created_by: Uuid,
+    ) -> Result<Webhook, sqlx::Error> {
+        let (engine_id, project_id) = scope.ids();
+        sqlx::query_as(&format!(
+            r#"
+            INSERT INTO webhooks (engine_id, project_id, url, secret, events, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING {}
+        "#,
+            WEBHOOK_COLUMNS
+        ))
+        .bind(engine_id)
+        .bind(project_id)
+        .bind(url)
+        .bind(secret)
+        .bind(events)
+        .bind(created_by)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    pub async fn get_webhooks(&self, scope: WebhookScope) -> Result<Vec<Webhook>, sqlx::Error> {
+        let (engine_id, project_id) = scope.ids();
+        sqlx::query_as(&format!(
+            r#"
+            SELECT {}
+            FROM webhooks
+            WHERE engine_id = $1 OR project_id = $2
+            ORDER BY created_at
+        "#,
+            WEBHOOK_COLUMNS
+        ))
+        .bind(engine_id)
+        .bind(project_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn get_webhook(&self, id: Uuid) -> Result<Option<Webhook>, sqlx::Error> {
+        sqlx::query_as(&format!(
+            "SELECT {} FROM webhooks WHERE id = $1",
+            WEBHOOK_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    pub async fn update_webhook(
+        &self,
+        id: Uuid,
+        request: &UpdateWebhookRequest,
+    ) -> Result<Option<Webhook>, sqlx::Error> {
+        sqlx::query_as(&format!(
+            r#"
+            UPDATE webhooks
+            SET url = COALESCE($2, url),
+                events = COALESCE($3, events),
+                active = COALESCE($4, active)
+            WHERE id = $1
+            RETURNING {}
+        "#,
+            WEBHOOK_COLUMNS
+        ))
+        .bind(id)
+        .bind(&request.url)
+        .bind(&request.events)
+        .bind(request.active)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    pub async fn delete_webhook(&self, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM webhooks WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delivery log of a webhook, most recent first.
+    pub async fn get_webhook_deliveries(
+        &self,
+        webhook_id: Uuid,
+        pagination: ApiPagination,
+    ) -> Result<Vec<WebhookDelivery>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT *
+            FROM webhook_deliveries
+            WHERE webhook_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2 OFFSET $3
+        "#,
+        )
+        .bind(webhook_id)
+        .bind(pagination.limit())
+        .bind(pagination.offset())
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Queues a copy of a past delivery, `None` if it is not one of the
+    /// webhook's.
+    pub async fn redeliver_webhook_delivery(
+        &self,
+        webhook_id: Uuid,
+        delivery_id: Uuid,
+    ) -> Result<Option<WebhookDelivery>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            INSERT INTO webhook_deliveries (webhook_id, event, payload, redelivery_of)
+            SELECT webhook_id, event, payload, id
+            FROM webhook_deliveries
+            WHERE id = $2 AND webhook_id = $1
+            RETURNING *
+        "#,
+        )
+        .bind(webhook_id)
+        .bind(delivery_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Takes up to `limit` due deliveries and pushes their next attempt back
+    /// by `lease`, so another instance only picks them up again if this one
+    /// dies before recording the outcome.
+    pub async fn claim_webhook_deliveries(
+        &self,
+        limit: i64,
+        lease: std::time::Duration,
+    ) -> Result<Vec<PendingDelivery>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            WITH due AS (
+                SELECT id
+                FROM webhook_deliveries
+                WHERE status = 'pending' AND next_attempt_at <= NOW()
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE webhook_deliveries d
+            SET next_attempt_at = NOW() + make_interval(secs => $2)
+            FROM due, webhooks w
+            WHERE d.id = due.id AND w.id = d.webhook_id
+            RETURNING d.id, d.event, d.payload, d.attempts, w.url, w.secret
+        "#,
+        )
+        .bind(limit)
+        .bind(lease.as_secs_f64())
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn record_webhook_attempt(
+        &self,
+        delivery_id: Uuid,
+        attempt: DeliveryAttempt<'_>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET attempts = attempts + 1,
+                last_attempt_at = NOW(),
+                status = $2,
+                next_attempt_at = $3,
+                response_status = $4,
+                response_body = $5,
+                error = $6
+            WHERE id = $1
+        "#,
+        )
+        .bind(delivery_id)
+        .bind(attempt.status)
+        .bind(attempt.next_attempt_at)
+        .bind(attempt.response_status)
+        .bind(attempt.response_body)
+        .bind(attempt.error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+/// Queues `event` for every active webhook of `scope` subscribed to it. Runs
+/// in the transaction of the change so events are only sent once committed.
+pub(super) async fn enqueue_event(
+    connection: &mut PgConnection,
+    scope: WebhookScope,
+    event: WebhookEvent,
+    data: serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    let (engine_id, project_id) = scope.ids();
+    let payload = json!({
+        "event": event,
+        "occurred_at": OffsetDateTime::now_utc()
+            .format(&time::format_description::well_known::Rfc3339)
+            .ok(),
+        "engine_id": engine_id,
+        "project_id": project_id,
+        "data": data,
+    });
+    sqlx::query(
+        r#"
+        INSERT INTO webhook_deliveries (webhook_id, event, payload)
+        SELECT id, $3, $4
+        FROM webhooks
+        WHERE active AND $3 = ANY(events) AND (engine_id = $1 OR project_id = $2)
+    "#,
+    )
+    .bind(engine_id)
+    .bind(project_id)
+    .bind(event)
+    .bind(sqlx::types::Json(payload))
+    .execute(&mut *connection)
+    .await?;
+    Ok(())
+}
+
+/// Announces the milestones `language` just reached in the project, each one
+/// only the first time. Expects the file stats to be up to date.
+pub(super) async fn enqueue_milestones(
+    connection: &mut PgConnection,
+    project_id: Uuid,
+    language: &str,
+) -> Result<(), sqlx::Error> {
+    let reached: Vec<i16> = sqlx::query_scalar(
+        r#"
+        WITH progress AS (
+            SELECT SUM(translated_units) * 100 / NULLIF(SUM(units), 0) AS percent
+            FROM project_file_stats
+            WHERE project_id = $1 AND language = $2
+        )
+        INSERT INTO project_milestones (project_id, language, milestone)
+        SELECT $1, $2, milestone
+        FROM progress, unnest($3::smallint[]) AS milestone
+        WHERE milestone <= progress.percent
+        ON CONFLICT DO NOTHING
+        RETURNING milestone
+    "#,
+    )
+    .bind(project_id)
+    .bind(language)
+    .bind(&MILESTONES[..])
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for milestone in reached {
+        enqueue_event(
+            connection,
+            WebhookScope::Project(project_id),
+            WebhookEvent::ProjectMilestoneReached,
+            json!({ "language": language, "milestone": milestone }),
+        )
+        .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+/// Tracks whether the instance should receive traffic.
+#[derive(Clone, Default)]
+pub struct HealthService {
+    shutting_down: Arc<AtomicBool>,
+}
+
+impl HealthService {
+    /// Makes readiness fail so the load balancer drains the instance before
+    /// it stops.
+    pub fn start_shutdown(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,231 @@
+use std::time::Duration;
+
+use rand::RngCore;
+use time::OffsetDateTime;
+use tokio::task::{JoinHandle, JoinSet};
+use tracing::{error, info, warn};
+
+use crate::{
+    AppState,
+    config::JobConfiguration,
+    services::{
+        database::{ClaimedJob, Job},
+        metrics,
+    },
+};
+
+/// Delay before the first retry, doubled after every failed attempt
+const RETRY_BASE: Duration = Duration::from_secs(30);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+/// Time between two prunings of the succeeded jobs
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Runs the jobs of the Postgres queue. Every process running workers claims
+/// jobs with `SKIP LOCKED`, so each is run by one of them.
+#[derive(Clone)]
+pub struct JobService {
+    /// Identifies the process in `locked_by`
+    worker_id: String,
+    embedded: bool,
+    concurrency: u32,
+    poll_interval: Duration,
+    lease: Duration,
+    retention: Duration,
+}
+
+impl TryFrom<&JobConfiguration> for JobService {
+    type Error = String;
+
+    fn try_from(job_config: &JobConfiguration) -> Result<Self, Self::Error> {
+        let mut suffix = [0u8; 4];
+        rand::rng().fill_bytes(&mut suffix);
+        Ok(JobService {
+            worker_id: format!("{}-{}", std::process::id(), hex::encode(suffix)),
+            embedded: job_config.embedded(),
+            concurrency: job_config.concurrency(),
+            poll_interval: job_config.poll_interval(),
+            lease: job_config.lease(),
+            retention: job_config.retention(),
+        })
+    }
+}
+
+impl JobService {
+    /// Whether the API process runs the workers itself.
+    pub fn is_embedded(&self) -> bool {
+        self.embedded
+    }
+
+    /// Runs due jobs until the instance starts shutting down, the handle
+    /// resolves once the running ones are done.
+    pub fn spawn_workers(self, app_state: AppState) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            info!(
+                "Starting {} job workers as {}",
+                self.concurrency, self.worker_id
+            );
+            schedule_pruning(&app_state).await;
+
+            while !app_state.health.is_shutting_down() {
+                let claimed = app_state
+                    .database
+                    .claim_jobs(&self.worker_id, self.concurrency.into(), self.lease)
+                    .await
+                    .unwrap_or_else(|e| {
+                        error!("Failed to claim jobs: {}", e);
+                        Vec::new()
+                    });
+                let backlog = claimed.len() == self.concurrency as usize;
+
+                let mut running = JoinSet::new();
+                for job in claimed {
+                    let service = self.clone();
+                    let app_state = app_state.clone();
+                    running.spawn(async move { service.run(&app_state, job).await });
+                }
+                while running.join_next().await.is_some() {}
+
+                if !backlog {
+                    tokio::time::sleep(self.poll_interval).await;
+                }
+            }
+        })
+    }
+
+    async fn run(&self, app_state: &AppState, claimed: ClaimedJob) {
+        let (result, retryable) = match serde_json::from_value::<Job>(claimed.payload.0) {
+            Ok(job) => (self.execute(app_state, job).await, true),
+            Err(e) => (Err(format!("Invalid payload: {}", e)), false),
+        };
+
+        let (outcome, recorded) = match result {
+            Ok(()) => ("success", app_state.database.complete_job(claimed.id).await),
+            Err(e) => {
+                let retry_at = (retryable && claimed.attempts < claimed.max_attempts).then(|| {
+                    OffsetDateTime::now_utc()
+                        + retry_delay(claimed.attempts as u32, RETRY_BASE, MAX_RETRY_DELAY)
+                });
+                warn!(
+                    "Job {} ({}) failed on attempt {}: {}",
+                    claimed.id, claimed.kind, claimed.attempts, e
+                );
+                let outcome = match retry_at {
+                    Some(_) => "retry",
+                    None => "dead",
+                };
+                (
+                    outcome,
+                    app_state.database.fail_job(claimed.id, &e, retry_at).await,
+                )
+            }
+        };
+        metrics::record_job(&claimed.kind, outcome);
+        if let Err(e) = recorded {
+            error!("Failed to record outcome of job {}: {}", claimed.id, e);
+        }
+    }
+
+    async fn execute(&self, app_state: &AppState, job: Job) -> Result<(), String> {
+        match job {
+            Job::ValidateArtifact { version_id } => {
+                let Some(artifact_key) = app_state
+                    .database
+                    .get_pending_artifact_key(version_id)
+                    .await
+                    .map_err(|e| e.to_string())?
+                else {
+                    // Already validated, or deleted by a new upload
+                    return Ok(());
+                };
+                let artifact = app_state
+                    .storage
+                    .get(&artifact_key)
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .ok_or_else(|| format!("Artifact {} is missing", artifact_key))?;
+                let report = app_state
+                    .components
+                    .validate(artifact)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                app_state
+                    .database
+                    .finish_version_validation(version_id, &report)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if !report.valid {
+                    info!(
+                        "Rejected artifact of engine version {}: {}",
+                        version_id,
+                        report.errors.join("; ")
+                    );
+                }
+                Ok(())
+            }
+            Job::RefreshProjectStats { project_id } => app_state
+                .database
+                .refresh_project_stats(project_id)
+                .await
+                .map_err(|e| e.to_string()),
+            Job::PruneJobs => {
+                let pruned = app_state
+                    .database
+                    .prune_jobs(OffsetDateTime::now_utc() - self.retention)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                info!("Pruned {} succeeded jobs", pruned);
+                schedule_pruning(app_state).await;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Queues the next pruning, a no-op when one is already queued.
+async fn schedule_pruning(app_state: &AppState) {
+    let run_at = OffsetDateTime::now_utc() + PRUNE_INTERVAL;
+    if let Err(e) = app_state
+        .database
+        .enqueue_job(&Job::PruneJobs, Some(run_at))
+        .await
+    {
+        error!("Failed to schedule job pruning: {}", e);
+    }
+}
+
+/// Exponential backoff: `base` after the first failed attempt, doubled after
+/// every other one, at most `max`. Also spaces webhook deliveries.
+pub fn retry_delay(attempts: u32, base: Duration, max: Duration) -> Duration {
+    base.saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+        .min(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: Duration = Duration::from_secs(30);
+    const MAX: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn delay_doubles_after_each_attempt() {
+        assert_eq!(retry_delay(0, BASE, MAX), BASE);
+        assert_eq!(retry_delay(1, BASE, MAX), BASE);
+        assert_eq!(retry_delay(2, BASE, MAX), BASE * 2);
+        assert_eq!(retry_delay(5, BASE, MAX), BASE * 16);
+        assert_eq!(retry_delay(8, BASE, MAX), MAX);
+    }
+
+    #[test]
+    fn delay_never_overflows() {
+        for attempts in [33, 64, 1_000, u32::MAX] {
+            assert_eq!(retry_delay(attempts, BASE, MAX), MAX);
+        }
+        assert_eq!(
+            retry_delay(u32::MAX, Duration::MAX, Duration::MAX),
+            Duration::MAX
+        );
+        // A negative attempt count read from the database
+        assert_eq!(retry_delay(-1i32 as u32, BASE, MAX), MAX);
+    }
+}
@@ -0,0 +1,165 @@
+use std::{sync::Mutex, time::Duration};
+
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use tracing::{
+    Event, Subscriber,
+    field::{Field, Visit},
+};
+use tracing_subscriber::layer::{Context, Layer};
+
+static HTTP_REQUESTS: &str = "http_requests_total";
+static HTTP_REQUEST_DURATION: &str = "http_request_duration_seconds";
+static DB_POOL_CONNECTIONS: &str = "db_pool_connections";
+static DB_POOL_MAX_CONNECTIONS: &str = "db_pool_max_connections";
+static DB_QUERY_DURATION: &str = "db_query_duration_seconds";
+static OAUTH_EXCHANGES: &str = "oauth_exchanges_total";
+static ARTIFACT_BYTES_SERVED: &str = "artifact_bytes_served_total";
+static ENGINE_DOWNLOADS: &str = "engine_downloads_total";
+static WEBHOOK_DELIVERIES: &str = "webhook_deliveries_total";
+static JOBS: &str = "jobs_total";
+
+/// Latency buckets in seconds, from a cached query to a slow upload
+static DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// The recorder is process wide, every `AppState` built in the process
+/// shares it
+static INSTALLED: Mutex<Option<PrometheusHandle>> = Mutex::new(None);
+
+/// Prometheus recorder, rendered by `/metrics`.
+#[derive(Clone)]
+pub struct MetricsService {
+    handle: PrometheusHandle,
+}
+
+impl MetricsService {
+    /// Installs the process wide recorder on first call.
+    pub fn install() -> Result<Self, String> {
+        let mut installed = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+        let handle = match &*installed {
+            Some(handle) => handle.clone(),
+            None => {
+                let handle = PrometheusBuilder::new()
+                    .set_buckets_for_metric(
+                        Matcher::Suffix("_seconds".to_string()),
+                        DURATION_BUCKETS,
+                    )
+                    .map_err(|e| e.to_string())?
+                    .install_recorder()
+                    .map_err(|e| e.to_string())?;
+                *installed = Some(handle.clone());
+                handle
+            }
+        };
+        Ok(MetricsService { handle })
+    }
+
+    pub fn render(&self) -> String {
+        self.handle.run_upkeep();
+        self.handle.render()
+    }
+}
+
+pub fn record_request(method: &str, route: &str, status: u16, elapsed: Duration) {
+    let labels = [
+        ("method", method.to_string()),
+        ("route", route.to_string()),
+        ("status", status.to_string()),
+    ];
+    counter!(HTTP_REQUESTS, &labels).increment(1);
+    histogram!(HTTP_REQUEST_DURATION, &labels).record(elapsed.as_secs_f64());
+}
+
+pub fn record_pool(size: u32, idle: usize, max: u32) {
+    let in_use = (size as usize).saturating_sub(idle);
+    gauge!(DB_POOL_CONNECTIONS, "state" => "idle").set(idle as f64);
+    gauge!(DB_POOL_CONNECTIONS, "state" => "in_use").set(in_use as f64);
+    gauge!(DB_POOL_MAX_CONNECTIONS).set(max as f64);
+}
+
+pub fn record_oauth_exchange(provider: &str, success: bool) {
+    let result = match success {
+        true => "success",
+        false => "failure",
+    };
+    counter!(OAUTH_EXCHANGES, "provider" => provider.to_string(), "result" => result).increment(1);
+}
+
+pub fn record_download(engine: &str, bytes: usize) {
+    counter!(ENGINE_DOWNLOADS, "engine" => engine.to_string()).increment(1);
+    counter!(ARTIFACT_BYTES_SERVED, "engine" => engine.to_string()).increment(bytes as u64);
+}
+
+pub fn record_webhook_delivery(event: &'static str, success: bool) {
+    let result = match success {
+        true => "success",
+        false => "failure",
+    };
+    counter!(WEBHOOK_DELIVERIES, "event" => event, "result" => result).increment(1);
+}
+
+/// `outcome` is `success`, `retry` or `dead`.
+pub fn record_job(kind: &str, outcome: &'static str) {
+    counter!(JOBS, "kind" => kind.to_string(), "outcome" => outcome).increment(1);
+}
+
+/// Times database queries from the `sqlx::query` events sqlx emits once a
+/// statement completes, labelled by statement kind (`select`, `insert`...).
+pub struct QueryTimingLayer;
+
+impl<S: Subscriber> Layer<S> for QueryTimingLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if event.metadata().target() != "sqlx::query" {
+            return;
+        }
+        let mut visitor = QueryVisitor::default();
+        event.record(&mut visitor);
+        if let Some(elapsed) = visitor.elapsed_secs {
+            histogram!(DB_QUERY_DURATION, "statement" => visitor.statement).record(elapsed);
+        }
+    }
+}
+
+#[derive(Default)]
+struct QueryVisitor {
+    statement: String,
+    elapsed_secs: Option<f64>,
+}
+
+impl Visit for QueryVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.elapsed_secs = Some(value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "summary" {
+            self.statement = statement_kind(value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "summary" {
+            self.statement = statement_kind(&format!("{:?}", value));
+        }
+    }
+}
+
+/// First keyword of the statement, keeps the label cardinality bounded.
+fn statement_kind(summary: &str) -> String {
+    let keyword = summary
+        .trim_matches('"')
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    match keyword.as_str() {
+        "select" | "insert" | "update" | "delete" | "with" | "begin" | "commit" | "rollback" => {
+            keyword
+        }
+        _ => "other".to_string(),
+    }
+}
@@ -1,19 +1,69 @@
-use crate::config::Configuration;
+use std::sync::Arc;
 
+use crate::{AppState, config::Configuration};
+
+pub mod components;
 pub mod database;
+pub mod health;
+pub mod jobs;
+pub mod metrics;
 pub mod oauth;
+pub mod rate_limit;
+pub mod repositories;
+pub mod session;
+pub mod storage;
+pub mod webhooks;
 
-pub async fn get_services(
-    config: &Configuration,
-) -> Result<(database::DatabaseService, oauth::OAuthService), String> {
-    let database_service = database::DatabaseService::new(&config.database().connection_string())
-        .await
-        .map_err(|e| format!("Failed to create database service: {}", e))?;
+pub async fn get_services(config: &Configuration) -> Result<AppState, String> {
+    let database_service = database::DatabaseService::new(config.database());
 
-    let github_config = config.github();
-    let oauth_service = github_config
+    let oauth_service = config
+        .oauth()
         .try_into()
         .map_err(|e| format!("Failed to create OAuth service: {}", e))?;
 
-    Ok((database_service, oauth_service))
+    let session_service = config
+        .session()
+        .try_into()
+        .map_err(|e| format!("Failed to create session service: {}", e))?;
+
+    let storage_service = config
+        .storage()
+        .try_into()
+        .map_err(|e| format!("Failed to create storage service: {}", e))?;
+
+    let webhook_service = config
+        .webhooks()
+        .try_into()
+        .map_err(|e| format!("Failed to create webhook service: {}", e))?;
+
+    let job_service = config
+        .jobs()
+        .try_into()
+        .map_err(|e| format!("Failed to create job service: {}", e))?;
+
+    let component_service = components::ComponentService::new()
+        .map_err(|e| format!("Failed to create component service: {}", e))?;
+
+    let metrics_service = metrics::MetricsService::install()
+        .map_err(|e| format!("Failed to install metrics recorder: {}", e))?;
+
+    let repository = Arc::new(database_service.clone());
+
+    Ok(AppState {
+        components: component_service,
+        database: database_service,
+        engines: repository.clone(),
+        engine_versions: repository.clone(),
+        users: repository.clone(),
+        projects: repository,
+        health: health::HealthService::default(),
+        jobs: job_service,
+        metrics: metrics_service,
+        oauth: oauth_service,
+        rate_limit: config.ratelimit().into(),
+        session: session_service,
+        storage: storage_service,
+        webhooks: webhook_service,
+    })
 }
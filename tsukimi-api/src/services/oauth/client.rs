@@ -0,0 +1,192 @@
+use oauth2::{
+    AccessToken, AuthUrl, AuthorizationCode, Client, ClientId, ClientSecret, CsrfToken,
+    ExtraTokenFields, PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, RefreshToken,
+    RevocationUrl, Scope, StandardRevocableToken, StandardTokenResponse, TokenResponse, TokenUrl,
+    basic::{
+        BasicErrorResponse, BasicRevocationErrorResponse, BasicTokenIntrospectionResponse,
+        BasicTokenType,
+    },
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use tsukimi_core::auth::OauthExchangeCodeResponse;
+
+use super::{Authorization, ProviderTokens};
+use crate::{config::IdentityProviderConfiguration, services::metrics};
+
+/// OpenID Connect providers return the id token along the access token.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct IdTokenFields {
+    id_token: Option<String>,
+}
+
+impl ExtraTokenFields for IdTokenFields {}
+
+type ProviderTokenResponse = StandardTokenResponse<IdTokenFields, BasicTokenType>;
+
+type ProviderClient = Client<
+    BasicErrorResponse,
+    ProviderTokenResponse,
+    BasicTokenIntrospectionResponse,
+    StandardRevocableToken,
+    BasicRevocationErrorResponse,
+>;
+
+/// Credentials of the API at a provider, and the OAuth 2 requests every
+/// provider answers the same way. Endpoints are given per call since OpenID
+/// Connect providers only know them once discovered.
+#[derive(Clone)]
+pub struct OAuthClient {
+    /// Id of the provider, for the metrics
+    provider: String,
+    client_id: ClientId,
+    client_secret: ClientSecret,
+    redirect_url: RedirectUrl,
+    scopes: Vec<Scope>,
+    http_client: reqwest::Client,
+}
+
+impl OAuthClient {
+    pub fn new(
+        provider_config: &IdentityProviderConfiguration,
+        default_scopes: &[&str],
+    ) -> Result<Self, String> {
+        let scopes = match provider_config.scopes() {
+            Some(scopes) => scopes.clone(),
+            None => default_scopes
+                .iter()
+                .map(|scope| scope.to_string())
+                .collect(),
+        };
+        let http_client = reqwest::Client::builder()
+            // Following redirects opens the client up to SSRF vulnerabilities
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(OAuthClient {
+            provider: provider_config.id().clone(),
+            client_id: ClientId::new(provider_config.client_id().clone()),
+            client_secret: ClientSecret::new(provider_config.client_secret().clone()),
+            redirect_url: RedirectUrl::new(provider_config.redirect_url().clone())
+                .map_err(|e| format!("Invalid redirect_url: {}", e))?,
+            scopes: scopes.into_iter().map(Scope::new).collect(),
+            http_client,
+        })
+    }
+
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    pub fn client_secret(&self) -> &ClientSecret {
+        &self.client_secret
+    }
+
+    pub fn http_client(&self) -> &reqwest::Client {
+        &self.http_client
+    }
+
+    fn client(&self) -> ProviderClient {
+        Client::new(self.client_id.clone())
+            .set_client_secret(self.client_secret.clone())
+            .set_redirect_uri(self.redirect_url.clone())
+    }
+
+    /// Builds the authorization URL with a fresh state and PKCE challenge.
+    pub fn authorize(
+        &self,
+        authorize_url: &str,
+        nonce: Option<String>,
+    ) -> Result<Authorization, String> {
+        let authorize_url = AuthUrl::new(authorize_url.to_string()).map_err(|e| e.to_string())?;
+        let (pkce_code_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+        let client = self.client().set_auth_uri(authorize_url);
+        let mut request = client
+            .authorize_url(CsrfToken::new_random)
+            .set_pkce_challenge(pkce_code_challenge)
+            .add_scopes(self.scopes.iter().cloned());
+        if let Some(nonce) = &nonce {
+            request = request.add_extra_param("nonce", nonce);
+        }
+        let (url, state) = request.url();
+        Ok(Authorization {
+            url: url.to_string(),
+            state,
+            pkce_verifier,
+            nonce,
+        })
+    }
+
+    pub async fn exchange_code(
+        &self,
+        token_url: &str,
+        code: AuthorizationCode,
+        pkce_verifier: PkceCodeVerifier,
+    ) -> Result<ProviderTokens, String> {
+        let token_url = TokenUrl::new(token_url.to_string()).map_err(|e| e.to_string())?;
+        let token_result = self
+            .client()
+            .set_token_uri(token_url)
+            .exchange_code(code)
+            .set_pkce_verifier(pkce_verifier)
+            .request_async(&self.http_client)
+            .await
+            .map_err(|e| {
+                error!("Failed to exchange code for token: {:?}", e);
+                metrics::record_oauth_exchange(&self.provider, false);
+                e.to_string()
+            })?;
+        metrics::record_oauth_exchange(&self.provider, true);
+        Ok(ProviderTokens {
+            id_token: token_result.extra_fields().id_token.clone(),
+            tokens: into_response(token_result),
+        })
+    }
+
+    pub async fn refresh(
+        &self,
+        token_url: &str,
+        refresh_token: &RefreshToken,
+    ) -> Result<OauthExchangeCodeResponse, String> {
+        let token_url = TokenUrl::new(token_url.to_string()).map_err(|e| e.to_string())?;
+        self.client()
+            .set_token_uri(token_url)
+            .exchange_refresh_token(refresh_token)
+            .request_async(&self.http_client)
+            .await
+            .map(into_response)
+            .map_err(|e| {
+                error!("Failed to refresh token: {:?}", e);
+                e.to_string()
+            })
+    }
+
+    /// Revokes the token at an RFC 7009 revocation endpoint.
+    pub async fn revoke(
+        &self,
+        revocation_url: &str,
+        access_token: &AccessToken,
+    ) -> Result<(), String> {
+        let revocation_url =
+            RevocationUrl::new(revocation_url.to_string()).map_err(|e| e.to_string())?;
+        self.client()
+            .set_revocation_url(revocation_url)
+            .revoke_token(StandardRevocableToken::AccessToken(access_token.clone()))
+            .map_err(|e| e.to_string())?
+            .request_async(&self.http_client)
+            .await
+            .map_err(|e| {
+                error!("Failed to revoke token: {:?}", e);
+                e.to_string()
+            })
+    }
+}
+
+fn into_response(token_result: ProviderTokenResponse) -> OauthExchangeCodeResponse {
+    OauthExchangeCodeResponse {
+        access_token: token_result.access_token().to_owned(),
+        refresh_token: token_result.refresh_token().map(|s| s.to_owned()),
+        expires_in: token_result.expires_in(),
+        scopes: token_result.scopes().map(|s| s.to_owned()),
+    }
+}
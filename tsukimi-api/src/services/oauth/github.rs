@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use oauth2::{AccessToken, AuthorizationCode, PkceCodeVerifier, RefreshToken};
+use serde::Deserialize;
+use tracing::error;
+use tsukimi_core::auth::{IdentityProviderInfo, OauthExchangeCodeResponse};
+
+use super::{Authorization, IdentityProvider, ProviderTokens, client::OAuthClient};
+use crate::{config::IdentityProviderConfiguration, services::database::NewIdentity};
+
+static GITHUB_AUTHORIZE_URL: &str = "https://github.com/login/oauth/authorize";
+static GITHUB_TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
+static GITHUB_API_URL: &str = "https://api.github.com";
+static DEFAULT_SCOPES: [&str; 2] = ["public_repo", "user:email"];
+
+/// Subset of the GitHub `/user` response needed to identify a Tsukimi user.
+#[derive(Debug, Deserialize)]
+struct GithubUser {
+    id: i64,
+    login: String,
+    email: Option<String>,
+}
+
+/// GitHub response when checking a token issued to the application.
+#[derive(Debug, Deserialize)]
+struct GithubTokenCheck {
+    user: GithubUser,
+}
+
+/// GitHub OAuth or GitHub App. GitHub is not an OpenID Connect provider, the
+/// account is read from the REST API.
+pub struct GithubProvider {
+    info: IdentityProviderInfo,
+    client: OAuthClient,
+}
+
+impl TryFrom<&IdentityProviderConfiguration> for GithubProvider {
+    type Error = String;
+
+    fn try_from(provider_config: &IdentityProviderConfiguration) -> Result<Self, Self::Error> {
+        Ok(GithubProvider {
+            info: IdentityProviderInfo {
+                id: provider_config.id().clone(),
+                name: provider_config
+                    .name()
+                    .clone()
+                    .unwrap_or_else(|| "GitHub".to_string()),
+                client_id: provider_config.client_id().clone(),
+            },
+            client: OAuthClient::new(provider_config, &DEFAULT_SCOPES)?,
+        })
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for GithubProvider {
+    fn info(&self) -> &IdentityProviderInfo {
+        &self.info
+    }
+
+    fn client_id(&self) -> &str {
+        self.client.client_id()
+    }
+
+    async fn authorize(&self) -> Result<Authorization, String> {
+        self.client.authorize(GITHUB_AUTHORIZE_URL, None)
+    }
+
+    async fn exchange_code(
+        &self,
+        code: AuthorizationCode,
+        pkce_verifier: PkceCodeVerifier,
+    ) -> Result<ProviderTokens, String> {
+        self.client
+            .exchange_code(GITHUB_TOKEN_URL, code, pkce_verifier)
+            .await
+    }
+
+    async fn identify(
+        &self,
+        tokens: &ProviderTokens,
+        _nonce: Option<&str>,
+    ) -> Result<NewIdentity, String> {
+        let response = self
+            .client
+            .http_client()
+            .get(format!("{}/user", GITHUB_API_URL))
+            .bearer_auth(tokens.tokens.access_token.secret())
+            // GitHub rejects requests without a User-Agent
+            .header(reqwest::header::USER_AGENT, env!("CARGO_PKG_NAME"))
+            .send()
+            .await
+            .map_err(|e| {
+                error!("Failed to fetch GitHub user: {:?}", e);
+                e.to_string()
+            })?;
+
+        if !response.status().is_success() {
+            return Err(format!("GitHub returned {}", response.status()));
+        }
+
+        let user: GithubUser = response.json().await.map_err(|e| e.to_string())?;
+        Ok(NewIdentity {
+            provider: self.info.id.clone(),
+            subject: user.id.to_string(),
+            username: user.login,
+            email: user.email,
+        })
+    }
+
+    /// Checks the token against the application, GitHub answers 404 for a
+    /// token issued to another one.
+    async fn identify_device_token(
+        &self,
+        access_token: &AccessToken,
+    ) -> Result<NewIdentity, String> {
+        let response = self
+            .client
+            .http_client()
+            .post(format!(
+                "{}/applications/{}/token",
+                GITHUB_API_URL,
+                self.client.client_id()
+            ))
+            .basic_auth(
+                self.client.client_id(),
+                Some(self.client.client_secret().secret()),
+            )
+            .header(reqwest::header::USER_AGENT, env!("CARGO_PKG_NAME"))
+            .json(&serde_json::json!({ "access_token": access_token.secret() }))
+            .send()
+            .await
+            .map_err(|e| {
+                error!("Failed to check GitHub token: {:?}", e);
+                e.to_string()
+            })?;
+
+        if !response.status().is_success() {
+            return Err(format!("GitHub returned {}", response.status()));
+        }
+
+        let check: GithubTokenCheck = response.json().await.map_err(|e| e.to_string())?;
+        Ok(NewIdentity {
+            provider: self.info.id.clone(),
+            subject: check.user.id.to_string(),
+            username: check.user.login,
+            email: check.user.email,
+        })
+    }
+
+    async fn refresh(
+        &self,
+        refresh_token: &RefreshToken,
+    ) -> Result<OauthExchangeCodeResponse, String> {
+        // Only GitHub apps with expiring user tokens issue refresh tokens
+        self.client.refresh(GITHUB_TOKEN_URL, refresh_token).await
+    }
+
+    /// GitHub has no RFC 7009 endpoint, tokens are revoked through the REST
+    /// API. A token GitHub doesn't know anymore is already revoked.
+    async fn revoke(&self, access_token: &AccessToken) -> Result<(), String> {
+        let response = self
+            .client
+            .http_client()
+            .delete(format!(
+                "{}/applications/{}/token",
+                GITHUB_API_URL,
+                self.client.client_id()
+            ))
+            .basic_auth(
+                self.client.client_id(),
+                Some(self.client.client_secret().secret()),
+            )
+            .header(reqwest::header::USER_AGENT, env!("CARGO_PKG_NAME"))
+            .json(&serde_json::json!({ "access_token": access_token.secret() }))
+            .send()
+            .await
+            .map_err(|e| {
+                error!("Failed to revoke GitHub token: {:?}", e);
+                e.to_string()
+            })?;
+
+        match response.status() {
+            status if status.is_success() || status == reqwest::StatusCode::NOT_FOUND => Ok(()),
+            status => Err(format!("GitHub returned {}", status)),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use async_trait::async_trait;
+use oauth2::{AccessToken, AuthorizationCode, PkceCodeVerifier, RefreshToken};
+use serde::Deserialize;
+use tracing::error;
+use tsukimi_core::auth::{IdentityProviderInfo, OauthExchangeCodeResponse};
+
+use super::{Authorization, IdentityProvider, ProviderTokens, client::OAuthClient};
+use crate::{config::IdentityProviderConfiguration, services::database::NewIdentity};
+
+static DEFAULT_SCOPES: [&str; 1] = ["read_user"];
+
+/// Subset of the GitLab `/api/v4/user` response.
+#[derive(Debug, Deserialize)]
+struct GitlabUser {
+    id: i64,
+    username: String,
+    email: Option<String>,
+}
+
+/// gitlab.com or a self-managed instance.
+pub struct GitlabProvider {
+    info: IdentityProviderInfo,
+    client: OAuthClient,
+    base_url: String,
+}
+
+impl GitlabProvider {
+    pub fn new(
+        provider_config: &IdentityProviderConfiguration,
+        base_url: &str,
+    ) -> Result<Self, String> {
+        Ok(GitlabProvider {
+            info: IdentityProviderInfo {
+                id: provider_config.id().clone(),
+                name: provider_config
+                    .name()
+                    .clone()
+                    .unwrap_or_else(|| "GitLab".to_string()),
+                client_id: provider_config.client_id().clone(),
+            },
+            client: OAuthClient::new(provider_config, &DEFAULT_SCOPES)?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for GitlabProvider {
+    fn info(&self) -> &IdentityProviderInfo {
+        &self.info
+    }
+
+    fn client_id(&self) -> &str {
+        self.client.client_id()
+    }
+
+    async fn authorize(&self) -> Result<Authorization, String> {
+        self.client.authorize(&self.url("/oauth/authorize"), None)
+    }
+
+    async fn exchange_code(
+        &self,
+        code: AuthorizationCode,
+        pkce_verifier: PkceCodeVerifier,
+    ) -> Result<ProviderTokens, String> {
+        self.client
+            .exchange_code(&self.url("/oauth/token"), code, pkce_verifier)
+            .await
+    }
+
+    async fn identify(
+        &self,
+        tokens: &ProviderTokens,
+        _nonce: Option<&str>,
+    ) -> Result<NewIdentity, String> {
+        let response = self
+            .client
+            .http_client()
+            .get(self.url("/api/v4/user"))
+            .bearer_auth(tokens.tokens.access_token.secret())
+            .send()
+            .await
+            .map_err(|e| {
+                error!("Failed to fetch GitLab user: {:?}", e);
+                e.to_string()
+            })?;
+
+        if !response.status().is_success() {
+            return Err(format!("GitLab returned {}", response.status()));
+        }
+
+        let user: GitlabUser = response.json().await.map_err(|e| e.to_string())?;
+        Ok(NewIdentity {
+            provider: self.info.id.clone(),
+            subject: user.id.to_string(),
+            username: user.username,
+            email: user.email,
+        })
+    }
+
+    async fn refresh(
+        &self,
+        refresh_token: &RefreshToken,
+    ) -> Result<OauthExchangeCodeResponse, String> {
+        self.client
+            .refresh(&self.url("/oauth/token"), refresh_token)
+            .await
+    }
+
+    async fn revoke(&self, access_token: &AccessToken) -> Result<(), String> {
+        self.client
+            .revoke(&self.url("/oauth/revoke"), access_token)
+            .await
+    }
+}
@@ -0,0 +1,137 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use oauth2::{AccessToken, AuthorizationCode, CsrfToken, PkceCodeVerifier, RefreshToken};
+use tsukimi_core::auth::{IdentityProviderInfo, OauthExchangeCodeResponse};
+
+use crate::{
+    config::{IdentityProviderKind, OAuthConfiguration},
+    services::database::NewIdentity,
+};
+
+mod client;
+mod github;
+mod gitlab;
+mod oidc;
+
+/// An authorization started by the API, the verifier and nonce are kept
+/// until the code comes back.
+pub struct Authorization {
+    pub url: String,
+    pub state: CsrfToken,
+    pub pkce_verifier: PkceCodeVerifier,
+    pub nonce: Option<String>,
+}
+
+/// Tokens returned by a provider for an authorization code.
+pub struct ProviderTokens {
+    pub tokens: OauthExchangeCodeResponse,
+    /// Only set by OpenID Connect providers
+    pub id_token: Option<String>,
+}
+
+/// A service users can log in with.
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    fn info(&self) -> &IdentityProviderInfo;
+
+    fn client_id(&self) -> &str;
+
+    async fn authorize(&self) -> Result<Authorization, String>;
+
+    async fn exchange_code(
+        &self,
+        code: AuthorizationCode,
+        pkce_verifier: PkceCodeVerifier,
+    ) -> Result<ProviderTokens, String>;
+
+    /// Reads the account the tokens were issued for. `nonce` is the one
+    /// generated by `authorize`, if any.
+    async fn identify(
+        &self,
+        tokens: &ProviderTokens,
+        nonce: Option<&str>,
+    ) -> Result<NewIdentity, String>;
+
+    /// Reads the account of an access token the client obtained from the
+    /// provider itself, through the device flow. The token must have been
+    /// issued to the API's client, not to another application of the user.
+    async fn identify_device_token(
+        &self,
+        _access_token: &AccessToken,
+    ) -> Result<NewIdentity, String> {
+        Err(format!(
+            "{} doesn't support the device flow",
+            self.info().name
+        ))
+    }
+
+    async fn refresh(
+        &self,
+        refresh_token: &RefreshToken,
+    ) -> Result<OauthExchangeCodeResponse, String>;
+
+    async fn revoke(&self, access_token: &AccessToken) -> Result<(), String>;
+}
+
+#[derive(Clone)]
+pub struct OAuthService {
+    providers: Vec<Arc<dyn IdentityProvider>>,
+    state_ttl: Duration,
+}
+
+impl TryFrom<&OAuthConfiguration> for OAuthService {
+    type Error = String;
+
+    fn try_from(oauth_config: &OAuthConfiguration) -> Result<Self, Self::Error> {
+        let providers = oauth_config
+            .providers()
+            .iter()
+            .map(|provider_config| {
+                let provider: Arc<dyn IdentityProvider> = match provider_config.kind() {
+                    IdentityProviderKind::Github => {
+                        Arc::new(github::GithubProvider::try_from(provider_config)?)
+                    }
+                    IdentityProviderKind::Gitlab { base_url } => {
+                        Arc::new(gitlab::GitlabProvider::new(provider_config, base_url)?)
+                    }
+                    IdentityProviderKind::Oidc { issuer } => {
+                        Arc::new(oidc::OidcProvider::new(provider_config, issuer)?)
+                    }
+                };
+                Ok(provider)
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(OAuthService {
+            providers,
+            state_ttl: oauth_config.state_ttl(),
+        })
+    }
+}
+
+impl OAuthService {
+    pub fn provider(&self, id: &str) -> Option<Arc<dyn IdentityProvider>> {
+        self.providers
+            .iter()
+            .find(|provider| provider.info().id == id)
+            .cloned()
+    }
+
+    pub fn providers(&self) -> Vec<IdentityProviderInfo> {
+        self.providers
+            .iter()
+            .map(|provider| provider.info().clone())
+            .collect()
+    }
+
+    /// Providers are only usable with a client id.
+    pub fn is_configured(&self) -> bool {
+        self.providers
+            .iter()
+            .all(|provider| !provider.client_id().is_empty())
+    }
+
+    pub fn state_ttl(&self) -> Duration {
+        self.state_ttl
+    }
+}
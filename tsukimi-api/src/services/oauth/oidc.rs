@@ -0,0 +1,233 @@
+use async_trait::async_trait;
+use jsonwebtoken::{
+    Algorithm, DecodingKey, Validation,
+    jwk::{AlgorithmParameters, Jwk, JwkSet},
+};
+use oauth2::{AccessToken, AuthorizationCode, CsrfToken, PkceCodeVerifier, RefreshToken};
+use serde::{Deserialize, de::DeserializeOwned};
+use tokio::sync::{OnceCell, RwLock};
+use tracing::error;
+use tsukimi_core::auth::{IdentityProviderInfo, OauthExchangeCodeResponse};
+
+use super::{Authorization, IdentityProvider, ProviderTokens, client::OAuthClient};
+use crate::{config::IdentityProviderConfiguration, services::database::NewIdentity};
+
+static DEFAULT_SCOPES: [&str; 3] = ["openid", "profile", "email"];
+
+/// Subset of the discovery document the login needs.
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    revocation_endpoint: Option<String>,
+}
+
+/// Claims of the id token identifying the user. `aud`, `iss` and `exp` are
+/// checked by the validation.
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    nonce: Option<String>,
+    preferred_username: Option<String>,
+    email: Option<String>,
+    email_verified: Option<bool>,
+}
+
+/// Any OpenID Connect provider. Endpoints come from the discovery document,
+/// fetched on first use so an unreachable provider doesn't prevent the API
+/// from starting.
+pub struct OidcProvider {
+    info: IdentityProviderInfo,
+    client: OAuthClient,
+    issuer: String,
+    metadata: OnceCell<ProviderMetadata>,
+    /// Refetched when an id token is signed by an unknown key
+    jwks: RwLock<Option<JwkSet>>,
+}
+
+impl OidcProvider {
+    pub fn new(
+        provider_config: &IdentityProviderConfiguration,
+        issuer: &str,
+    ) -> Result<Self, String> {
+        Ok(OidcProvider {
+            info: IdentityProviderInfo {
+                id: provider_config.id().clone(),
+                name: provider_config
+                    .name()
+                    .clone()
+                    .unwrap_or_else(|| "OpenID Connect".to_string()),
+                client_id: provider_config.client_id().clone(),
+            },
+            client: OAuthClient::new(provider_config, &DEFAULT_SCOPES)?,
+            issuer: issuer.trim_end_matches('/').to_string(),
+            metadata: OnceCell::new(),
+            jwks: RwLock::new(None),
+        })
+    }
+
+    async fn get<T: DeserializeOwned>(&self, url: &str) -> Result<T, String> {
+        let response = self
+            .client
+            .http_client()
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| {
+                error!("Failed to fetch {}: {:?}", url, e);
+                e.to_string()
+            })?;
+        if !response.status().is_success() {
+            return Err(format!("{} returned {}", url, response.status()));
+        }
+        response.json().await.map_err(|e| e.to_string())
+    }
+
+    async fn metadata(&self) -> Result<&ProviderMetadata, String> {
+        self.metadata
+            .get_or_try_init(|| async {
+                let metadata: ProviderMetadata = self
+                    .get(&format!("{}/.well-known/openid-configuration", self.issuer))
+                    .await?;
+                // The issuer of the document is the one the id tokens carry
+                if metadata.issuer.trim_end_matches('/') != self.issuer {
+                    return Err(format!(
+                        "Discovery document issued for {} instead of {}",
+                        metadata.issuer, self.issuer
+                    ));
+                }
+                Ok(metadata)
+            })
+            .await
+    }
+
+    /// Finds the key an id token is signed with, refetching the key set once
+    /// if the provider rotated its keys.
+    async fn signing_key(&self, kid: Option<&str>) -> Result<Jwk, String> {
+        let find = |jwks: &JwkSet| match kid {
+            Some(kid) => jwks.find(kid).cloned(),
+            None if jwks.keys.len() == 1 => jwks.keys.first().cloned(),
+            None => None,
+        };
+
+        if let Some(jwk) = self.jwks.read().await.as_ref().and_then(find) {
+            return Ok(jwk);
+        }
+
+        let metadata = self.metadata().await?;
+        let jwks: JwkSet = self.get(&metadata.jwks_uri).await?;
+        let jwk = find(&jwks);
+        *self.jwks.write().await = Some(jwks);
+        jwk.ok_or_else(|| "Id token signed by an unknown key".to_string())
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for OidcProvider {
+    fn info(&self) -> &IdentityProviderInfo {
+        &self.info
+    }
+
+    fn client_id(&self) -> &str {
+        self.client.client_id()
+    }
+
+    async fn authorize(&self) -> Result<Authorization, String> {
+        let metadata = self.metadata().await?;
+        let nonce = CsrfToken::new_random().secret().clone();
+        self.client
+            .authorize(&metadata.authorization_endpoint, Some(nonce))
+    }
+
+    async fn exchange_code(
+        &self,
+        code: AuthorizationCode,
+        pkce_verifier: PkceCodeVerifier,
+    ) -> Result<ProviderTokens, String> {
+        let metadata = self.metadata().await?;
+        self.client
+            .exchange_code(&metadata.token_endpoint, code, pkce_verifier)
+            .await
+    }
+
+    async fn identify(
+        &self,
+        tokens: &ProviderTokens,
+        nonce: Option<&str>,
+    ) -> Result<NewIdentity, String> {
+        let id_token = tokens
+            .id_token
+            .as_deref()
+            .ok_or_else(|| "No id token returned, is the openid scope requested?".to_string())?;
+
+        let header = jsonwebtoken::decode_header(id_token).map_err(|e| e.to_string())?;
+        // Shared secret algorithms would let the key set choose the key
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(format!("Unsupported id token algorithm {:?}", header.alg));
+        }
+        let jwk = self.signing_key(header.kid.as_deref()).await?;
+        if matches!(jwk.algorithm, AlgorithmParameters::OctetKey(_)) {
+            return Err("Unsupported id token key".to_string());
+        }
+        let decoding_key = DecodingKey::from_jwk(&jwk).map_err(|e| e.to_string())?;
+
+        let metadata = self.metadata().await?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_audience(&[self.client.client_id()]);
+        validation.set_issuer(&[&metadata.issuer]);
+        let claims = jsonwebtoken::decode::<IdTokenClaims>(id_token, &decoding_key, &validation)
+            .map_err(|e| format!("Invalid id token: {}", e))?
+            .claims;
+
+        if claims.nonce.as_deref() != nonce {
+            return Err("Id token nonce mismatch".to_string());
+        }
+
+        // Unverified addresses are not stored, a provider that doesn't say
+        // whether the address is verified is assumed not to verify it
+        let email = claims.email.filter(|_| claims.email_verified == Some(true));
+        let username = claims
+            .preferred_username
+            .or_else(|| {
+                email
+                    .as_deref()
+                    .and_then(|email| email.split('@').next())
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| claims.sub.clone());
+        Ok(NewIdentity {
+            provider: self.info.id.clone(),
+            subject: claims.sub,
+            username,
+            email,
+        })
+    }
+
+    async fn refresh(
+        &self,
+        refresh_token: &RefreshToken,
+    ) -> Result<OauthExchangeCodeResponse, String> {
+        let metadata = self.metadata().await?;
+        self.client
+            .refresh(&metadata.token_endpoint, refresh_token)
+            .await
+    }
+
+    async fn revoke(&self, access_token: &AccessToken) -> Result<(), String> {
+        let metadata = self.metadata().await?;
+        match &metadata.revocation_endpoint {
+            Some(revocation_endpoint) => {
+                self.client.revoke(revocation_endpoint, access_token).await
+            }
+            None => Err(format!(
+                "{} doesn't support token revocation",
+                self.info.name
+            )),
+        }
+    }
+}
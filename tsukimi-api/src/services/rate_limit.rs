@@ -0,0 +1,234 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::config::{RateLimitBudget, RateLimitConfiguration};
+
+/// How often full buckets are forgotten, so idle clients don't pile up
+static PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Routes sharing a budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitClass {
+    Search,
+    Download,
+    OAuth,
+    Publish,
+}
+
+/// Outcome of a request against its bucket, enough to fill the `RateLimit-*`
+/// headers.
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Time until the bucket is full again
+    pub reset: Duration,
+    /// Time until the next request is allowed, zero when allowed
+    pub retry_after: Duration,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+struct Buckets {
+    buckets: HashMap<(RateLimitClass, String), Bucket>,
+    pruned: Instant,
+}
+
+/// In-memory token buckets, one per class and client. Each instance counts
+/// on its own, budgets are per instance behind a load balancer.
+#[derive(Clone)]
+pub struct RateLimitService {
+    enabled: bool,
+    search: RateLimitBudget,
+    download: RateLimitBudget,
+    oauth: RateLimitBudget,
+    publish: RateLimitBudget,
+    trust_forwarded_for: bool,
+    trusted_proxies: usize,
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+impl From<&RateLimitConfiguration> for RateLimitService {
+    fn from(rate_limit_config: &RateLimitConfiguration) -> Self {
+        RateLimitService {
+            enabled: rate_limit_config.enabled(),
+            search: rate_limit_config.search(),
+            download: rate_limit_config.download(),
+            oauth: rate_limit_config.oauth(),
+            publish: rate_limit_config.publish(),
+            trust_forwarded_for: rate_limit_config.trust_forwarded_for(),
+            trusted_proxies: rate_limit_config.trusted_proxies(),
+            buckets: Arc::new(Mutex::new(Buckets {
+                buckets: HashMap::new(),
+                pruned: Instant::now(),
+            })),
+        }
+    }
+}
+
+impl RateLimitService {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Whether `X-Forwarded-For` can be used to find the client address.
+    pub fn trust_forwarded_for(&self) -> bool {
+        self.trust_forwarded_for
+    }
+
+    /// Proxies appending to `X-Forwarded-For` in front of the API.
+    pub fn trusted_proxies(&self) -> usize {
+        self.trusted_proxies
+    }
+
+    fn budget(&self, class: RateLimitClass) -> RateLimitBudget {
+        match class {
+            RateLimitClass::Search => self.search,
+            RateLimitClass::Download => self.download,
+            RateLimitClass::OAuth => self.oauth,
+            RateLimitClass::Publish => self.publish,
+        }
+    }
+
+    /// Takes a token from the bucket of `client` for `class`.
+    pub fn check(&self, class: RateLimitClass, client: &str) -> RateLimitDecision {
+        self.check_at(class, client, Instant::now())
+    }
+
+    fn check_at(&self, class: RateLimitClass, client: &str, now: Instant) -> RateLimitDecision {
+        let budget = self.budget(class);
+        let capacity = budget.burst() as f64;
+        let per_second = budget.per_minute() / 60.0;
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if now.duration_since(buckets.pruned) >= PRUNE_INTERVAL {
+            // A bucket untouched for as long as it takes to refill is full
+            buckets.buckets.retain(|(class, _), bucket| {
+                let budget = self.budget(*class);
+                now.duration_since(bucket.updated).as_secs_f64()
+                    < budget.burst() as f64 * 60.0 / budget.per_minute()
+            });
+            buckets.pruned = now;
+        }
+
+        let bucket = buckets
+            .buckets
+            .entry((class, client.to_string()))
+            .or_insert(Bucket {
+                tokens: capacity,
+                updated: now,
+            });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second).min(capacity);
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        let seconds_until = |tokens: f64| Duration::from_secs_f64((tokens / per_second).max(0.0));
+
+        RateLimitDecision {
+            allowed,
+            limit: budget.burst(),
+            remaining: bucket.tokens.floor() as u32,
+            reset: seconds_until(capacity - bucket.tokens),
+            retry_after: match allowed {
+                true => Duration::ZERO,
+                false => seconds_until(1.0 - bucket.tokens),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::config::tests::configuration;
+
+    /// Two requests at once, then one per second.
+    fn service() -> RateLimitService {
+        let config = configuration(json!({
+            "ratelimit": { "search_burst": 2, "search_per_minute": 60.0 },
+        }))
+        .unwrap();
+        RateLimitService::from(config.ratelimit())
+    }
+
+    #[test]
+    fn bucket_allows_the_burst_then_refills() {
+        let service = service();
+        let start = Instant::now();
+
+        let first = service.check_at(RateLimitClass::Search, "ip:a", start);
+        assert!(first.allowed);
+        assert_eq!((first.limit, first.remaining), (2, 1));
+        assert!(
+            service
+                .check_at(RateLimitClass::Search, "ip:a", start)
+                .allowed
+        );
+
+        let denied = service.check_at(RateLimitClass::Search, "ip:a", start);
+        assert!(!denied.allowed);
+        assert_eq!(denied.remaining, 0);
+        assert_eq!(denied.retry_after, Duration::from_secs(1));
+        assert_eq!(denied.reset, Duration::from_secs(2));
+
+        let later = start + Duration::from_secs(1);
+        assert!(
+            service
+                .check_at(RateLimitClass::Search, "ip:a", later)
+                .allowed
+        );
+        assert!(
+            !service
+                .check_at(RateLimitClass::Search, "ip:a", later)
+                .allowed
+        );
+    }
+
+    #[test]
+    fn buckets_are_per_client_and_class() {
+        let service = service();
+        let now = Instant::now();
+        for _ in 0..2 {
+            service.check_at(RateLimitClass::Search, "ip:a", now);
+        }
+        assert!(
+            !service
+                .check_at(RateLimitClass::Search, "ip:a", now)
+                .allowed
+        );
+        assert!(
+            service
+                .check_at(RateLimitClass::Search, "ip:b", now)
+                .allowed
+        );
+        assert!(
+            service
+                .check_at(RateLimitClass::Download, "ip:a", now)
+                .allowed
+        );
+    }
+
+    #[test]
+    fn idle_buckets_stay_capped() {
+        let service = service();
+        let start = Instant::now();
+        service.check_at(RateLimitClass::Search, "ip:a", start);
+
+        let later = start + Duration::from_secs(3600);
+        let decision = service.check_at(RateLimitClass::Search, "ip:a", later);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 1);
+    }
+}
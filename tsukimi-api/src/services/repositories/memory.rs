@@ -0,0 +1,1370 @@
+//! In-memory storage for the route tests. It keeps what the Postgres
+//! constraints and triggers guarantee (unique versions, one current version
+//! per engine...) but records no webhook event and queues no job.
+
+use std::sync::{Mutex, MutexGuard};
+
+use async_trait::async_trait;
+use sqlx::error::{DatabaseError, ErrorKind};
+use time::OffsetDateTime;
+use tsukimi_core::{
+    auth::{PersonalAccessToken, TokenScope, UserIdentity},
+    models::{
+        CreateProjectRequest, Engine, EngineMember, EngineRole, EngineVersion, GlobalRole, Project,
+        ProjectMember, ProjectRole, ProjectVisibility, PublisherKey, UpdateProjectRequest, User,
+        ValidationStatus, Version, VisualNovel,
+    },
+};
+use uuid::Uuid;
+
+use super::{EngineRepository, EngineVersionRepository, ProjectRepository, UserRepository};
+use crate::services::{
+    database::{
+        ApiPagination, EngineFilter, EngineSort, MemberUpdate, NewEngineVersion, NewIdentity,
+        OauthState, ProjectAccess, ProjectFilter, VersionArtifact,
+    },
+    session::SessionService,
+};
+
+#[derive(Default)]
+pub struct MemoryRepository {
+    state: Mutex<MemoryState>,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    users: Vec<User>,
+    identities: Vec<StoredIdentity>,
+    sessions: Vec<Session>,
+    tokens: Vec<Token>,
+    oauth_states: Vec<StoredOauthState>,
+    engines: Vec<Engine>,
+    engine_members: Vec<Member<EngineRole>>,
+    versions: Vec<StoredVersion>,
+    publisher_keys: Vec<PublisherKey>,
+    visual_novels: Vec<VisualNovel>,
+    projects: Vec<Project>,
+    project_members: Vec<Member<ProjectRole>>,
+}
+
+struct Session {
+    id: Uuid,
+    user_id: Uuid,
+    refresh_token_hash: String,
+    expires_at: OffsetDateTime,
+    revoked: bool,
+}
+
+struct Token {
+    user_id: Uuid,
+    token_hash: String,
+    token: PersonalAccessToken,
+    revoked: bool,
+}
+
+impl Token {
+    fn is_live(&self) -> bool {
+        !self.revoked
+            && self
+                .token
+                .expires_at
+                .is_none_or(|expires_at| expires_at > OffsetDateTime::now_utc())
+    }
+}
+
+struct StoredOauthState {
+    state: String,
+    oauth_state: OauthState,
+    expires_at: OffsetDateTime,
+}
+
+struct Member<R> {
+    target_id: Uuid,
+    user_id: Uuid,
+    role: R,
+    created_at: OffsetDateTime,
+}
+
+struct StoredIdentity {
+    user_id: Uuid,
+    identity: UserIdentity,
+}
+
+struct StoredVersion {
+    version: EngineVersion,
+    artifact_key: Option<String>,
+}
+
+/// Constraint violation, reported like the ones of Postgres.
+#[derive(Debug)]
+struct Violation {
+    kind: ViolationKind,
+    constraint: &'static str,
+}
+
+#[derive(Debug)]
+enum ViolationKind {
+    Unique,
+    ForeignKey,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (constraint {})", self.message(), self.constraint)
+    }
+}
+
+impl std::error::Error for Violation {}
+
+impl DatabaseError for Violation {
+    fn message(&self) -> &str {
+        match self.kind {
+            ViolationKind::Unique => "duplicate key value violates unique constraint",
+            ViolationKind::ForeignKey => "insert or update violates foreign key constraint",
+        }
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn constraint(&self) -> Option<&str> {
+        Some(self.constraint)
+    }
+
+    fn kind(&self) -> ErrorKind {
+        match self.kind {
+            ViolationKind::Unique => ErrorKind::UniqueViolation,
+            ViolationKind::ForeignKey => ErrorKind::ForeignKeyViolation,
+        }
+    }
+}
+
+fn violation(kind: ViolationKind, constraint: &'static str) -> sqlx::Error {
+    sqlx::Error::Database(Box::new(Violation { kind, constraint }))
+}
+
+/// Position of a role in its Postgres enum, members are ordered by it.
+fn engine_role_rank(role: EngineRole) -> u8 {
+    match role {
+        EngineRole::Owner => 0,
+        EngineRole::Maintainer => 1,
+    }
+}
+
+fn project_role_rank(role: ProjectRole) -> u8 {
+    match role {
+        ProjectRole::Owner => 0,
+        ProjectRole::Translator => 1,
+        ProjectRole::Reviewer => 2,
+        ProjectRole::Viewer => 3,
+    }
+}
+
+fn paginate<T>(items: Vec<T>, pagination: &ApiPagination) -> Vec<T> {
+    items
+        .into_iter()
+        .skip(pagination.offset() as usize)
+        .take(pagination.limit() as usize)
+        .collect()
+}
+
+impl MemoryRepository {
+    fn state(&self) -> MutexGuard<'_, MemoryState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn insert_user(&self, username: &str, role: GlobalRole) -> User {
+        let now = OffsetDateTime::now_utc();
+        let user = User {
+            id: Uuid::new_v4(),
+            username: username.to_string(),
+            email: None,
+            role,
+            created_at: now,
+            updated_at: now,
+        };
+        self.state().users.push(user.clone());
+        user
+    }
+
+    /// Links an identity of `provider` to the user.
+    pub fn insert_identity(&self, user_id: Uuid, provider: &str, subject: &str) -> UserIdentity {
+        let now = OffsetDateTime::now_utc();
+        let identity = UserIdentity {
+            provider: provider.to_string(),
+            subject: subject.to_string(),
+            username: subject.to_string(),
+            email: None,
+            created_at: now,
+            last_login_at: None,
+        };
+        self.state().identities.push(StoredIdentity {
+            user_id,
+            identity: identity.clone(),
+        });
+        identity
+    }
+
+    /// Issues a personal access token to the user and returns its secret.
+    pub fn insert_personal_access_token(&self, user_id: Uuid, scopes: &[TokenScope]) -> String {
+        let secret = SessionService::generate_personal_access_token();
+        self.state().tokens.push(Token {
+            user_id,
+            token_hash: SessionService::hash_secret(secret.secret()),
+            token: PersonalAccessToken {
+                id: Uuid::new_v4(),
+                name: "test".to_string(),
+                token_prefix: secret.secret()[..12].to_string(),
+                scopes: scopes.to_vec(),
+                expires_at: None,
+                last_used_at: None,
+                created_at: OffsetDateTime::now_utc(),
+            },
+            revoked: false,
+        });
+        secret.secret().clone()
+    }
+
+    /// Registers a live publisher key of the user.
+    pub fn insert_publisher_key(&self, user_id: Uuid) -> PublisherKey {
+        let key = PublisherKey {
+            id: Uuid::new_v4(),
+            user_id,
+            name: "test".to_string(),
+            public_key: String::new(),
+            fingerprint: String::new(),
+            created_at: OffsetDateTime::now_utc(),
+            revoked_at: None,
+        };
+        self.state().publisher_keys.push(key.clone());
+        key
+    }
+
+    pub fn insert_visual_novel(&self, title: &str, original_language: &str) -> VisualNovel {
+        let now = OffsetDateTime::now_utc();
+        let visual_novel = VisualNovel {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            original_title: None,
+            developer: None,
+            release_date: None,
+            original_language: original_language.to_string(),
+            engine_id: None,
+            cover_image_url: None,
+            vndb_id: None,
+            external_ids: Default::default(),
+            created_at: now,
+            updated_at: now,
+        };
+        self.state().visual_novels.push(visual_novel.clone());
+        visual_novel
+    }
+
+    /// Creates an engine owned by `owner_id`, without any version.
+    pub fn insert_engine(&self, name: &str, description: &str, owner_id: Uuid) -> Engine {
+        let engine = Engine {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: description.to_string(),
+            current_version: None,
+            tags: Vec::new(),
+            supported_languages: Vec::new(),
+            wit_version: None,
+            downloads: 0,
+            deprecated_at: None,
+            deprecation_message: None,
+            replacement_engine_id: None,
+        };
+        let mut state = self.state();
+        state.engines.push(engine.clone());
+        state.engine_members.push(Member {
+            target_id: engine.id,
+            user_id: owner_id,
+            role: EngineRole::Owner,
+            created_at: OffsetDateTime::now_utc(),
+        });
+        engine
+    }
+
+    /// Adds a validated version, current unless a greater one already is.
+    pub fn insert_version(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+        artifact_key: &str,
+    ) -> EngineVersion {
+        let version: Version = version.parse().expect("valid version");
+        let mut state = self.state();
+        let current = state
+            .engines
+            .iter()
+            .find(|engine| engine.id == engine_id)
+            .and_then(|engine| engine.current_version.clone());
+        let stored = EngineVersion {
+            id: Uuid::new_v4(),
+            engine_id,
+            is_active: current.is_none_or(|current| version > current),
+            version,
+            description: None,
+            artifact_size: None,
+            artifact_sha256: None,
+            yanked_at: None,
+            yank_reason: None,
+            signature: None,
+            publisher_key_id: None,
+            wit_version: None,
+            capabilities: Vec::new(),
+            validation_status: ValidationStatus::Valid,
+            validation_report: None,
+            created_at: OffsetDateTime::now_utc(),
+        };
+        state.versions.push(StoredVersion {
+            version: stored.clone(),
+            artifact_key: Some(artifact_key.to_string()),
+        });
+        if stored.is_active {
+            state.activate(stored.id);
+        }
+        stored
+    }
+
+    /// Creates a project owned by `owner_id`, translated from Japanese.
+    pub fn insert_project(
+        &self,
+        name: &str,
+        visibility: ProjectVisibility,
+        owner_id: Uuid,
+    ) -> Project {
+        let now = OffsetDateTime::now_utc();
+        let project = Project {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: None,
+            visual_novel_id: Uuid::new_v4(),
+            engine_version_id: None,
+            source_language: "ja".to_string(),
+            target_languages: vec!["en".to_string()],
+            visibility,
+            archived_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let mut state = self.state();
+        state.projects.push(project.clone());
+        state.project_members.push(Member {
+            target_id: project.id,
+            user_id: owner_id,
+            role: ProjectRole::Owner,
+            created_at: now,
+        });
+        project
+    }
+}
+
+impl MemoryState {
+    fn is_last_engine_owner(&self, engine_id: Uuid, user_id: Uuid) -> bool {
+        let mut owners = self
+            .engine_members
+            .iter()
+            .filter(|m| m.target_id == engine_id && m.role == EngineRole::Owner);
+        owners.next().is_some_and(|m| m.user_id == user_id) && owners.next().is_none()
+    }
+
+    fn is_last_project_owner(&self, project_id: Uuid, user_id: Uuid) -> bool {
+        let mut owners = self
+            .project_members
+            .iter()
+            .filter(|m| m.target_id == project_id && m.role == ProjectRole::Owner);
+        owners.next().is_some_and(|m| m.user_id == user_id) && owners.next().is_none()
+    }
+
+    fn username(&self, user_id: Uuid) -> String {
+        self.users
+            .iter()
+            .find(|user| user.id == user_id)
+            .map(|user| user.username.clone())
+            .unwrap_or_default()
+    }
+
+    fn version_mut(&mut self, engine_id: Uuid, version: &str) -> Option<&mut StoredVersion> {
+        self.versions.iter_mut().find(|stored| {
+            stored.version.engine_id == engine_id && stored.version.version.to_string() == version
+        })
+    }
+
+    /// Makes the version current, like `update_engine_current_version()`.
+    fn activate(&mut self, version_id: Uuid) {
+        let Some(active) = self
+            .versions
+            .iter()
+            .find(|stored| stored.version.id == version_id)
+            .map(|stored| stored.version.clone())
+        else {
+            return;
+        };
+        for stored in &mut self.versions {
+            if stored.version.engine_id == active.engine_id {
+                stored.version.is_active = stored.version.id == version_id;
+            }
+        }
+        if let Some(engine) = self.engines.iter_mut().find(|e| e.id == active.engine_id) {
+            engine.current_version = Some(active.version);
+            engine.wit_version = active.wit_version.or(engine.wit_version.take());
+        }
+    }
+
+    /// Picks a current version when the engine lost it, like
+    /// `fallback_engine_current_version()`.
+    fn fallback(&mut self, engine_id: Uuid) {
+        let versions = || {
+            self.versions
+                .iter()
+                .filter(move |stored| stored.version.engine_id == engine_id)
+        };
+        if versions().any(|stored| stored.version.is_active) {
+            return;
+        }
+        let greatest = versions()
+            .filter(|stored| {
+                stored.version.yanked_at.is_none()
+                    && stored.version.validation_status == ValidationStatus::Valid
+            })
+            .max_by_key(|stored| stored.version.version.clone())
+            .map(|stored| stored.version.id);
+        match greatest {
+            Some(version_id) => self.activate(version_id),
+            None => {
+                if let Some(engine) = self.engines.iter_mut().find(|e| e.id == engine_id) {
+                    engine.current_version = None;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl EngineRepository for MemoryRepository {
+    /// Words of the query match name, description or tags as substrings.
+    /// Relevance is approximated by the name order.
+    async fn get_engines(
+        &self,
+        pagination: ApiPagination,
+        filter: EngineFilter,
+    ) -> Result<Vec<Engine>, sqlx::Error> {
+        let words: Vec<String> = pagination
+            .query
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+        let mut engines: Vec<Engine> = self
+            .state()
+            .engines
+            .iter()
+            .filter(|engine| {
+                let text = format!(
+                    "{} {} {}",
+                    engine.name,
+                    engine.description,
+                    engine.tags.join(" ")
+                )
+                .to_lowercase();
+                words.iter().all(|word| text.contains(word.as_str()))
+            })
+            .filter(|engine| {
+                filter
+                    .language
+                    .as_ref()
+                    .is_none_or(|language| engine.supported_languages.contains(language))
+            })
+            .filter(|engine| {
+                filter
+                    .wit_version
+                    .as_ref()
+                    .is_none_or(|wit_version| engine.wit_version.as_ref() == Some(wit_version))
+            })
+            .cloned()
+            .collect();
+        match filter.sort {
+            EngineSort::Downloads => engines.sort_by(|a, b| {
+                b.downloads
+                    .cmp(&a.downloads)
+                    .then_with(|| a.name.cmp(&b.name))
+            }),
+            EngineSort::Relevance | EngineSort::Name | EngineSort::RecentlyUpdated => {
+                engines.sort_by(|a, b| a.name.cmp(&b.name))
+            }
+        }
+        Ok(paginate(engines, &pagination))
+    }
+
+    async fn get_engine(&self, id: Uuid) -> Result<Option<Engine>, sqlx::Error> {
+        Ok(self.state().engines.iter().find(|e| e.id == id).cloned())
+    }
+
+    async fn deprecate_engine(
+        &self,
+        id: Uuid,
+        message: &str,
+        replacement_engine_id: Option<Uuid>,
+    ) -> Result<Option<Engine>, sqlx::Error> {
+        let mut state = self.state();
+        Ok(state.engines.iter_mut().find(|e| e.id == id).map(|engine| {
+            engine
+                .deprecated_at
+                .get_or_insert_with(OffsetDateTime::now_utc);
+            engine.deprecation_message = Some(message.to_string());
+            engine.replacement_engine_id = replacement_engine_id;
+            engine.clone()
+        }))
+    }
+
+    async fn undeprecate_engine(&self, id: Uuid) -> Result<Option<Engine>, sqlx::Error> {
+        let mut state = self.state();
+        Ok(state.engines.iter_mut().find(|e| e.id == id).map(|engine| {
+            engine.deprecated_at = None;
+            engine.deprecation_message = None;
+            engine.replacement_engine_id = None;
+            engine.clone()
+        }))
+    }
+
+    async fn increment_downloads(&self, engine_id: Uuid) -> Result<(), sqlx::Error> {
+        if let Some(engine) = self.state().engines.iter_mut().find(|e| e.id == engine_id) {
+            engine.downloads += 1;
+        }
+        Ok(())
+    }
+
+    async fn get_engine_role(
+        &self,
+        engine_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<EngineRole>, sqlx::Error> {
+        Ok(self
+            .state()
+            .engine_members
+            .iter()
+            .find(|m| m.target_id == engine_id && m.user_id == user_id)
+            .map(|m| m.role))
+    }
+
+    async fn get_engine_members(&self, engine_id: Uuid) -> Result<Vec<EngineMember>, sqlx::Error> {
+        let state = self.state();
+        let mut members: Vec<EngineMember> = state
+            .engine_members
+            .iter()
+            .filter(|m| m.target_id == engine_id)
+            .map(|m| EngineMember {
+                user_id: m.user_id,
+                username: state.username(m.user_id),
+                role: m.role,
+                created_at: m.created_at,
+            })
+            .collect();
+        members.sort_by(|a, b| {
+            engine_role_rank(a.role)
+                .cmp(&engine_role_rank(b.role))
+                .then_with(|| a.username.cmp(&b.username))
+        });
+        Ok(members)
+    }
+
+    async fn set_engine_member(
+        &self,
+        engine_id: Uuid,
+        user_id: Uuid,
+        role: EngineRole,
+    ) -> Result<MemberUpdate<EngineRole>, sqlx::Error> {
+        let mut state = self.state();
+        if !state.users.iter().any(|user| user.id == user_id) {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                "engine_members_user_id_fkey",
+            ));
+        }
+        if role != EngineRole::Owner && state.is_last_engine_owner(engine_id, user_id) {
+            return Ok(MemberUpdate::LastOwner);
+        }
+        match state
+            .engine_members
+            .iter_mut()
+            .find(|m| m.target_id == engine_id && m.user_id == user_id)
+        {
+            Some(member) => Ok(MemberUpdate::Applied(Some(std::mem::replace(
+                &mut member.role,
+                role,
+            )))),
+            None => {
+                state.engine_members.push(Member {
+                    target_id: engine_id,
+                    user_id,
+                    role,
+                    created_at: OffsetDateTime::now_utc(),
+                });
+                Ok(MemberUpdate::Applied(None))
+            }
+        }
+    }
+
+    async fn remove_engine_member(
+        &self,
+        engine_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<MemberUpdate<EngineRole>, sqlx::Error> {
+        let mut state = self.state();
+        if state.is_last_engine_owner(engine_id, user_id) {
+            return Ok(MemberUpdate::LastOwner);
+        }
+        let previous = state
+            .engine_members
+            .iter()
+            .position(|m| m.target_id == engine_id && m.user_id == user_id)
+            .map(|index| state.engine_members.remove(index).role);
+        Ok(MemberUpdate::Applied(previous))
+    }
+}
+
+#[async_trait]
+impl EngineVersionRepository for MemoryRepository {
+    async fn get_engine_versions(
+        &self,
+        engine_id: Uuid,
+    ) -> Result<Vec<EngineVersion>, sqlx::Error> {
+        let mut versions: Vec<EngineVersion> = self
+            .state()
+            .versions
+            .iter()
+            .filter(|stored| stored.version.engine_id == engine_id)
+            .map(|stored| stored.version.clone())
+            .collect();
+        versions.sort_by_key(|version| std::cmp::Reverse(version.created_at));
+        Ok(versions)
+    }
+
+    async fn get_engine_version(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+    ) -> Result<Option<EngineVersion>, sqlx::Error> {
+        Ok(self
+            .state()
+            .version_mut(engine_id, version)
+            .map(|stored| stored.version.clone()))
+    }
+
+    async fn get_version_artifact(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+    ) -> Result<Option<VersionArtifact>, sqlx::Error> {
+        let mut state = self.state();
+        let engine_name = state
+            .engines
+            .iter()
+            .find(|e| e.id == engine_id)
+            .map(|e| e.name.clone());
+        let (Some(engine_name), Some(stored)) =
+            (engine_name, state.version_mut(engine_id, version))
+        else {
+            return Ok(None);
+        };
+        if stored.version.validation_status != ValidationStatus::Valid {
+            return Ok(None);
+        }
+        Ok(stored
+            .artifact_key
+            .clone()
+            .map(|artifact_key| VersionArtifact {
+                engine_name,
+                artifact_key,
+                artifact_sha256: stored.version.artifact_sha256.clone(),
+                signature: stored.version.signature.clone(),
+                publisher_key_id: stored.version.publisher_key_id,
+                yanked: stored.version.yanked_at.is_some(),
+            }))
+    }
+
+    async fn publish_version(
+        &self,
+        version: &NewEngineVersion<'_>,
+    ) -> Result<EngineVersion, sqlx::Error> {
+        let parsed: Version = version
+            .version
+            .parse()
+            .map_err(|e: String| sqlx::Error::Decode(e.into()))?;
+        let mut state = self.state();
+        state.versions.retain(|stored| {
+            !(stored.version.engine_id == version.engine_id
+                && stored.version.version == parsed
+                && stored.version.validation_status == ValidationStatus::Invalid)
+        });
+        if state
+            .version_mut(version.engine_id, version.version)
+            .is_some()
+        {
+            return Err(violation(
+                ViolationKind::Unique,
+                "engine_versions_engine_id_version_key",
+            ));
+        }
+        let published = EngineVersion {
+            id: Uuid::new_v4(),
+            engine_id: version.engine_id,
+            version: parsed,
+            description: version.description.map(str::to_string),
+            is_active: false,
+            artifact_size: Some(version.artifact_size),
+            artifact_sha256: Some(version.artifact_sha256.to_string()),
+            yanked_at: None,
+            yank_reason: None,
+            signature: Some(version.signature.to_string()),
+            publisher_key_id: Some(version.publisher_key_id),
+            wit_version: None,
+            capabilities: Vec::new(),
+            validation_status: ValidationStatus::Pending,
+            validation_report: None,
+            created_at: OffsetDateTime::now_utc(),
+        };
+        state.versions.push(StoredVersion {
+            version: published.clone(),
+            artifact_key: Some(version.artifact_key.to_string()),
+        });
+        Ok(published)
+    }
+
+    async fn yank_version(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+        reason: Option<&str>,
+        _yanked_by: Uuid,
+    ) -> Result<Option<EngineVersion>, sqlx::Error> {
+        let mut state = self.state();
+        let Some(stored) = state.version_mut(engine_id, version) else {
+            return Ok(None);
+        };
+        if stored.version.yanked_at.is_some() {
+            return Ok(Some(stored.version.clone()));
+        }
+        stored.version.yanked_at = Some(OffsetDateTime::now_utc());
+        stored.version.yank_reason = reason.map(str::to_string);
+        stored.version.is_active = false;
+        let id = stored.version.id;
+        state.fallback(engine_id);
+        Ok(state
+            .versions
+            .iter()
+            .find(|stored| stored.version.id == id)
+            .map(|stored| stored.version.clone()))
+    }
+
+    async fn unyank_version(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+    ) -> Result<Option<EngineVersion>, sqlx::Error> {
+        let mut state = self.state();
+        let Some(stored) = state.version_mut(engine_id, version) else {
+            return Ok(None);
+        };
+        let id = stored.version.id;
+        if stored.version.yanked_at.take().is_some() {
+            stored.version.yank_reason = None;
+            state.fallback(engine_id);
+        }
+        Ok(state
+            .versions
+            .iter()
+            .find(|stored| stored.version.id == id)
+            .map(|stored| stored.version.clone()))
+    }
+
+    async fn get_publisher_key(&self, id: Uuid) -> Result<Option<PublisherKey>, sqlx::Error> {
+        Ok(self
+            .state()
+            .publisher_keys
+            .iter()
+            .find(|key| key.id == id)
+            .cloned())
+    }
+}
+
+#[async_trait]
+impl UserRepository for MemoryRepository {
+    async fn get_user(&self, id: Uuid) -> Result<Option<User>, sqlx::Error> {
+        Ok(self.state().users.iter().find(|u| u.id == id).cloned())
+    }
+
+    async fn upsert_identity_user(&self, identity: &NewIdentity) -> Result<User, sqlx::Error> {
+        let mut state = self.state();
+        let now = OffsetDateTime::now_utc();
+        if let Some(stored) = state.identities.iter_mut().find(|stored| {
+            stored.identity.provider == identity.provider
+                && stored.identity.subject == identity.subject
+        }) {
+            stored.identity.username = identity.username.clone();
+            stored.identity.email = identity.email.clone();
+            stored.identity.last_login_at = Some(now);
+            let user_id = stored.user_id;
+            return Ok(state
+                .users
+                .iter()
+                .find(|u| u.id == user_id)
+                .cloned()
+                .expect("identity of an existing user"));
+        }
+
+        let taken = |username: &str| state.users.iter().any(|u| u.username == username);
+        let username = (1..)
+            .map(|suffix| match suffix {
+                1 => identity.username.clone(),
+                _ => format!("{}-{}", identity.username, suffix),
+            })
+            .find(|username| !taken(username))
+            .expect("a free username");
+        let email = identity
+            .email
+            .clone()
+            .filter(|email| !state.users.iter().any(|u| u.email.as_ref() == Some(email)));
+        let user = User {
+            id: Uuid::new_v4(),
+            username,
+            email,
+            role: GlobalRole::User,
+            created_at: now,
+            updated_at: now,
+        };
+        state.users.push(user.clone());
+        state.identities.push(StoredIdentity {
+            user_id: user.id,
+            identity: UserIdentity {
+                provider: identity.provider.clone(),
+                subject: identity.subject.clone(),
+                username: identity.username.clone(),
+                email: identity.email.clone(),
+                created_at: now,
+                last_login_at: Some(now),
+            },
+        });
+        Ok(user)
+    }
+
+    async fn get_user_identities(&self, user_id: Uuid) -> Result<Vec<UserIdentity>, sqlx::Error> {
+        Ok(self
+            .state()
+            .identities
+            .iter()
+            .filter(|stored| stored.user_id == user_id)
+            .map(|stored| stored.identity.clone())
+            .collect())
+    }
+
+    async fn link_identity(
+        &self,
+        user_id: Uuid,
+        identity: &NewIdentity,
+    ) -> Result<Option<UserIdentity>, sqlx::Error> {
+        let mut state = self.state();
+        if let Some(stored) = state.identities.iter_mut().find(|stored| {
+            stored.identity.provider == identity.provider
+                && stored.identity.subject == identity.subject
+        }) {
+            if stored.user_id != user_id {
+                return Ok(None);
+            }
+            stored.identity.username = identity.username.clone();
+            stored.identity.email = identity.email.clone();
+            return Ok(Some(stored.identity.clone()));
+        }
+        if state.identities.iter().any(|stored| {
+            stored.user_id == user_id && stored.identity.provider == identity.provider
+        }) {
+            return Err(violation(
+                ViolationKind::Unique,
+                "user_identities_user_id_provider_key",
+            ));
+        }
+        let linked = UserIdentity {
+            provider: identity.provider.clone(),
+            subject: identity.subject.clone(),
+            username: identity.username.clone(),
+            email: identity.email.clone(),
+            created_at: OffsetDateTime::now_utc(),
+            last_login_at: None,
+        };
+        state.identities.push(StoredIdentity {
+            user_id,
+            identity: linked.clone(),
+        });
+        Ok(Some(linked))
+    }
+
+    async fn unlink_identity(&self, user_id: Uuid, provider: &str) -> Result<bool, sqlx::Error> {
+        let mut state = self.state();
+        let before = state.identities.len();
+        state
+            .identities
+            .retain(|stored| !(stored.user_id == user_id && stored.identity.provider == provider));
+        Ok(state.identities.len() < before)
+    }
+
+    async fn get_user_role(&self, user_id: Uuid) -> Result<Option<GlobalRole>, sqlx::Error> {
+        Ok(self
+            .state()
+            .users
+            .iter()
+            .find(|u| u.id == user_id)
+            .map(|u| u.role))
+    }
+
+    async fn set_user_role(&self, user_id: Uuid, role: GlobalRole) -> Result<bool, sqlx::Error> {
+        let mut state = self.state();
+        Ok(match state.users.iter_mut().find(|u| u.id == user_id) {
+            Some(user) => {
+                user.role = role;
+                true
+            }
+            None => false,
+        })
+    }
+
+    async fn create_session(
+        &self,
+        user_id: Uuid,
+        refresh_token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<Uuid, sqlx::Error> {
+        let id = Uuid::new_v4();
+        self.state().sessions.push(Session {
+            id,
+            user_id,
+            refresh_token_hash: refresh_token_hash.to_string(),
+            expires_at,
+            revoked: false,
+        });
+        Ok(id)
+    }
+
+    async fn rotate_session(
+        &self,
+        refresh_token_hash: &str,
+        new_refresh_token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<Option<(Uuid, Uuid)>, sqlx::Error> {
+        let now = OffsetDateTime::now_utc();
+        Ok(self
+            .state()
+            .sessions
+            .iter_mut()
+            .find(|s| {
+                s.refresh_token_hash == refresh_token_hash && !s.revoked && s.expires_at > now
+            })
+            .map(|session| {
+                session.refresh_token_hash = new_refresh_token_hash.to_string();
+                session.expires_at = expires_at;
+                (session.id, session.user_id)
+            }))
+    }
+
+    async fn revoke_session(&self, session_id: Uuid) -> Result<(), sqlx::Error> {
+        for session in self.state().sessions.iter_mut() {
+            if session.id == session_id {
+                session.revoked = true;
+            }
+        }
+        Ok(())
+    }
+
+    async fn revoke_user_sessions(&self, user_id: Uuid) -> Result<(), sqlx::Error> {
+        for session in self.state().sessions.iter_mut() {
+            if session.user_id == user_id {
+                session.revoked = true;
+            }
+        }
+        Ok(())
+    }
+
+    async fn is_session_active(&self, session_id: Uuid) -> Result<bool, sqlx::Error> {
+        let now = OffsetDateTime::now_utc();
+        Ok(self
+            .state()
+            .sessions
+            .iter()
+            .any(|s| s.id == session_id && !s.revoked && s.expires_at > now))
+    }
+
+    async fn use_personal_access_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<(Uuid, Uuid, Vec<TokenScope>)>, sqlx::Error> {
+        let mut state = self.state();
+        let Some(token) = state
+            .tokens
+            .iter_mut()
+            .find(|t| t.token_hash == token_hash && t.is_live())
+        else {
+            return Ok(None);
+        };
+        token.token.last_used_at = Some(OffsetDateTime::now_utc());
+        Ok(Some((
+            token.token.id,
+            token.user_id,
+            token.token.scopes.clone(),
+        )))
+    }
+
+    async fn get_personal_access_tokens(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<PersonalAccessToken>, sqlx::Error> {
+        let mut tokens: Vec<PersonalAccessToken> = self
+            .state()
+            .tokens
+            .iter()
+            .filter(|t| t.user_id == user_id && !t.revoked)
+            .map(|t| t.token.clone())
+            .collect();
+        tokens.sort_by_key(|token| std::cmp::Reverse(token.created_at));
+        Ok(tokens)
+    }
+
+    async fn create_personal_access_token(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        token_hash: &str,
+        token_prefix: &str,
+        scopes: &[TokenScope],
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<PersonalAccessToken, sqlx::Error> {
+        let token = PersonalAccessToken {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            token_prefix: token_prefix.to_string(),
+            scopes: scopes.to_vec(),
+            expires_at,
+            last_used_at: None,
+            created_at: OffsetDateTime::now_utc(),
+        };
+        self.state().tokens.push(Token {
+            user_id,
+            token_hash: token_hash.to_string(),
+            token: token.clone(),
+            revoked: false,
+        });
+        Ok(token)
+    }
+
+    async fn revoke_personal_access_token(
+        &self,
+        user_id: Uuid,
+        token_id: Uuid,
+    ) -> Result<Option<PersonalAccessToken>, sqlx::Error> {
+        Ok(self
+            .state()
+            .tokens
+            .iter_mut()
+            .find(|t| t.token.id == token_id && t.user_id == user_id && !t.revoked)
+            .map(|t| {
+                t.revoked = true;
+                t.token.clone()
+            }))
+    }
+
+    async fn create_oauth_state(
+        &self,
+        state: &str,
+        oauth_state: &OauthState,
+        expires_at: OffsetDateTime,
+    ) -> Result<(), sqlx::Error> {
+        let mut memory = self.state();
+        let now = OffsetDateTime::now_utc();
+        memory.oauth_states.retain(|stored| stored.expires_at > now);
+        memory.oauth_states.push(StoredOauthState {
+            state: state.to_string(),
+            oauth_state: oauth_state.clone(),
+            expires_at,
+        });
+        Ok(())
+    }
+
+    async fn take_oauth_state(&self, state: &str) -> Result<Option<OauthState>, sqlx::Error> {
+        let mut memory = self.state();
+        let Some(index) = memory
+            .oauth_states
+            .iter()
+            .position(|stored| stored.state == state)
+        else {
+            return Ok(None);
+        };
+        let stored = memory.oauth_states.remove(index);
+        Ok((stored.expires_at > OffsetDateTime::now_utc()).then_some(stored.oauth_state))
+    }
+}
+
+#[async_trait]
+impl ProjectRepository for MemoryRepository {
+    async fn get_projects(
+        &self,
+        pagination: ApiPagination,
+        filter: ProjectFilter,
+        user_id: Option<Uuid>,
+        is_admin: bool,
+    ) -> Result<Vec<Project>, sqlx::Error> {
+        let state = self.state();
+        let query = pagination.query.to_lowercase();
+        let mut projects: Vec<Project> = state
+            .projects
+            .iter()
+            .filter(|p| p.name.to_lowercase().contains(&query))
+            .filter(|p| {
+                filter
+                    .visual_novel_id
+                    .is_none_or(|id| p.visual_novel_id == id)
+            })
+            .filter(|p| filter.include_archived || p.archived_at.is_none())
+            .filter(|p| {
+                is_admin
+                    || p.visibility == ProjectVisibility::Public
+                    || state
+                        .project_members
+                        .iter()
+                        .any(|m| m.target_id == p.id && Some(m.user_id) == user_id)
+            })
+            .cloned()
+            .collect();
+        projects.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(paginate(projects, &pagination))
+    }
+
+    async fn get_project(&self, id: Uuid) -> Result<Option<Project>, sqlx::Error> {
+        Ok(self.state().projects.iter().find(|p| p.id == id).cloned())
+    }
+
+    async fn get_project_access(
+        &self,
+        project_id: Uuid,
+        user_id: Option<Uuid>,
+    ) -> Result<Option<ProjectAccess>, sqlx::Error> {
+        let state = self.state();
+        Ok(state
+            .projects
+            .iter()
+            .find(|p| p.id == project_id)
+            .map(|project| ProjectAccess {
+                visibility: project.visibility,
+                archived: project.archived_at.is_some(),
+                role: state
+                    .project_members
+                    .iter()
+                    .find(|m| m.target_id == project_id && Some(m.user_id) == user_id)
+                    .map(|m| m.role),
+            }))
+    }
+
+    async fn create_project(
+        &self,
+        request: &CreateProjectRequest,
+        source_language: &str,
+        owner_id: Uuid,
+    ) -> Result<Project, sqlx::Error> {
+        let mut state = self.state();
+        if let Some(version_id) = request.engine_version_id
+            && !state.versions.iter().any(|s| s.version.id == version_id)
+        {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                "projects_engine_version_id_fkey",
+            ));
+        }
+        let now = OffsetDateTime::now_utc();
+        let project = Project {
+            id: Uuid::new_v4(),
+            name: request.name.clone(),
+            description: request.description.clone(),
+            visual_novel_id: request.visual_novel_id,
+            engine_version_id: request.engine_version_id,
+            source_language: source_language.to_string(),
+            target_languages: request.target_languages.clone(),
+            visibility: request.visibility,
+            archived_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        state.projects.push(project.clone());
+        state.project_members.push(Member {
+            target_id: project.id,
+            user_id: owner_id,
+            role: ProjectRole::Owner,
+            created_at: now,
+        });
+        Ok(project)
+    }
+
+    async fn update_project(
+        &self,
+        id: Uuid,
+        request: &UpdateProjectRequest,
+    ) -> Result<Option<Project>, sqlx::Error> {
+        let mut state = self.state();
+        if let Some(version_id) = request.engine_version_id
+            && !state.versions.iter().any(|s| s.version.id == version_id)
+        {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                "projects_engine_version_id_fkey",
+            ));
+        }
+        Ok(state
+            .projects
+            .iter_mut()
+            .find(|p| p.id == id)
+            .map(|project| {
+                if let Some(name) = &request.name {
+                    project.name = name.clone();
+                }
+                if let Some(description) = &request.description {
+                    project.description = Some(description.clone());
+                }
+                if let Some(version_id) = request.engine_version_id {
+                    project.engine_version_id = Some(version_id);
+                }
+                if let Some(target_languages) = &request.target_languages {
+                    project.target_languages = target_languages.clone();
+                }
+                if let Some(visibility) = request.visibility {
+                    project.visibility = visibility;
+                }
+                project.updated_at = OffsetDateTime::now_utc();
+                project.clone()
+            }))
+    }
+
+    async fn set_project_archived(
+        &self,
+        id: Uuid,
+        archived: bool,
+    ) -> Result<Option<Project>, sqlx::Error> {
+        let mut state = self.state();
+        Ok(state
+            .projects
+            .iter_mut()
+            .find(|p| p.id == id)
+            .map(|project| {
+                project.archived_at = match archived {
+                    true => project.archived_at.or(Some(OffsetDateTime::now_utc())),
+                    false => None,
+                };
+                project.clone()
+            }))
+    }
+
+    async fn get_project_members(
+        &self,
+        project_id: Uuid,
+    ) -> Result<Vec<ProjectMember>, sqlx::Error> {
+        let state = self.state();
+        let mut members: Vec<ProjectMember> = state
+            .project_members
+            .iter()
+            .filter(|m| m.target_id == project_id)
+            .map(|m| ProjectMember {
+                user_id: m.user_id,
+                username: state.username(m.user_id),
+                role: m.role,
+                created_at: m.created_at,
+            })
+            .collect();
+        members.sort_by(|a, b| {
+            project_role_rank(a.role)
+                .cmp(&project_role_rank(b.role))
+                .then_with(|| a.username.cmp(&b.username))
+        });
+        Ok(members)
+    }
+
+    async fn get_project_role(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<ProjectRole>, sqlx::Error> {
+        Ok(self
+            .state()
+            .project_members
+            .iter()
+            .find(|m| m.target_id == project_id && m.user_id == user_id)
+            .map(|m| m.role))
+    }
+
+    async fn set_project_member(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+        role: ProjectRole,
+    ) -> Result<MemberUpdate<ProjectRole>, sqlx::Error> {
+        let mut state = self.state();
+        if !state.users.iter().any(|user| user.id == user_id) {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                "project_members_user_id_fkey",
+            ));
+        }
+        if role != ProjectRole::Owner && state.is_last_project_owner(project_id, user_id) {
+            return Ok(MemberUpdate::LastOwner);
+        }
+        match state
+            .project_members
+            .iter_mut()
+            .find(|m| m.target_id == project_id && m.user_id == user_id)
+        {
+            Some(member) => Ok(MemberUpdate::Applied(Some(std::mem::replace(
+                &mut member.role,
+                role,
+            )))),
+            None => {
+                state.project_members.push(Member {
+                    target_id: project_id,
+                    user_id,
+                    role,
+                    created_at: OffsetDateTime::now_utc(),
+                });
+                Ok(MemberUpdate::Applied(None))
+            }
+        }
+    }
+
+    async fn remove_project_member(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<MemberUpdate<ProjectRole>, sqlx::Error> {
+        let mut state = self.state();
+        if state.is_last_project_owner(project_id, user_id) {
+            return Ok(MemberUpdate::LastOwner);
+        }
+        let previous = state
+            .project_members
+            .iter()
+            .position(|m| m.target_id == project_id && m.user_id == user_id)
+            .map(|index| state.project_members.remove(index).role);
+        Ok(MemberUpdate::Applied(previous))
+    }
+
+    async fn get_visual_novel(&self, id: Uuid) -> Result<Option<VisualNovel>, sqlx::Error> {
+        Ok(self
+            .state()
+            .visual_novels
+            .iter()
+            .find(|visual_novel| visual_novel.id == id)
+            .cloned())
+    }
+}
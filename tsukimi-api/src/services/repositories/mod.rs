@@ -0,0 +1,288 @@
+//! Storage used by the engine, user and project routes. `DatabaseService` is
+//! the Postgres implementation, tests run the router against the in-memory
+//! one. The other route groups (visual novels, units, reviews, comments,
+//! stats, webhooks, publisher keys, audit log and jobs) still query
+//! `DatabaseService` directly.
+
+use async_trait::async_trait;
+use time::OffsetDateTime;
+use tsukimi_core::{
+    auth::{PersonalAccessToken, TokenScope, UserIdentity},
+    models::{
+        CreateProjectRequest, Engine, EngineMember, EngineRole, EngineVersion, GlobalRole, Project,
+        ProjectMember, ProjectRole, PublisherKey, UpdateProjectRequest, User, VisualNovel,
+    },
+};
+use uuid::Uuid;
+
+use crate::services::database::{
+    ApiPagination, EngineFilter, MemberUpdate, NewEngineVersion, NewIdentity, OauthState,
+    ProjectAccess, ProjectFilter, VersionArtifact,
+};
+
+#[cfg(test)]
+pub mod memory;
+mod postgres;
+
+/// Engines of the catalog and their members.
+#[async_trait]
+pub trait EngineRepository: Send + Sync {
+    async fn get_engines(
+        &self,
+        pagination: ApiPagination,
+        filter: EngineFilter,
+    ) -> Result<Vec<Engine>, sqlx::Error>;
+
+    async fn get_engine(&self, id: Uuid) -> Result<Option<Engine>, sqlx::Error>;
+
+    async fn deprecate_engine(
+        &self,
+        id: Uuid,
+        message: &str,
+        replacement_engine_id: Option<Uuid>,
+    ) -> Result<Option<Engine>, sqlx::Error>;
+
+    async fn undeprecate_engine(&self, id: Uuid) -> Result<Option<Engine>, sqlx::Error>;
+
+    async fn increment_downloads(&self, engine_id: Uuid) -> Result<(), sqlx::Error>;
+
+    async fn get_engine_role(
+        &self,
+        engine_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<EngineRole>, sqlx::Error>;
+
+    async fn get_engine_members(&self, engine_id: Uuid) -> Result<Vec<EngineMember>, sqlx::Error>;
+
+    /// Adds the member or changes their role, unless that demotes the last
+    /// owner.
+    async fn set_engine_member(
+        &self,
+        engine_id: Uuid,
+        user_id: Uuid,
+        role: EngineRole,
+    ) -> Result<MemberUpdate<EngineRole>, sqlx::Error>;
+
+    /// Removes the member unless they are the last owner.
+    async fn remove_engine_member(
+        &self,
+        engine_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<MemberUpdate<EngineRole>, sqlx::Error>;
+}
+
+/// Released versions of the engines.
+#[async_trait]
+pub trait EngineVersionRepository: Send + Sync {
+    /// Every uploaded version, yanked and unvalidated ones included, most
+    /// recent first.
+    async fn get_engine_versions(&self, engine_id: Uuid)
+    -> Result<Vec<EngineVersion>, sqlx::Error>;
+
+    async fn get_engine_version(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+    ) -> Result<Option<EngineVersion>, sqlx::Error>;
+
+    /// Returns `None` if the version doesn't exist, has no artifact or is not
+    /// validated.
+    async fn get_version_artifact(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+    ) -> Result<Option<VersionArtifact>, sqlx::Error>;
+
+    /// Records a release pending validation. Fails with a unique violation if
+    /// the version already exists.
+    async fn publish_version(
+        &self,
+        version: &NewEngineVersion<'_>,
+    ) -> Result<EngineVersion, sqlx::Error>;
+
+    /// Yanks the version unless it already is, returns it either way.
+    async fn yank_version(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+        reason: Option<&str>,
+        yanked_by: Uuid,
+    ) -> Result<Option<EngineVersion>, sqlx::Error>;
+
+    /// Makes a yanked version resolvable again. It only becomes the current
+    /// version if the engine has none.
+    async fn unyank_version(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+    ) -> Result<Option<EngineVersion>, sqlx::Error>;
+
+    /// Key a publisher signs the artifacts of new versions with, revoked
+    /// ones included.
+    async fn get_publisher_key(&self, id: Uuid) -> Result<Option<PublisherKey>, sqlx::Error>;
+}
+
+/// Accounts, their global role and their login sessions.
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    async fn get_user(&self, id: Uuid) -> Result<Option<User>, sqlx::Error>;
+
+    /// Returns the user owning the identity, creating both on first login.
+    async fn upsert_identity_user(&self, identity: &NewIdentity) -> Result<User, sqlx::Error>;
+
+    async fn get_user_identities(&self, user_id: Uuid) -> Result<Vec<UserIdentity>, sqlx::Error>;
+
+    /// Returns `None` if the identity belongs to another user, fails with a
+    /// unique violation if the user has another account of the same provider.
+    async fn link_identity(
+        &self,
+        user_id: Uuid,
+        identity: &NewIdentity,
+    ) -> Result<Option<UserIdentity>, sqlx::Error>;
+
+    /// Returns whether the user had an identity of this provider.
+    async fn unlink_identity(&self, user_id: Uuid, provider: &str) -> Result<bool, sqlx::Error>;
+
+    async fn get_user_role(&self, user_id: Uuid) -> Result<Option<GlobalRole>, sqlx::Error>;
+
+    /// Returns whether the user exists.
+    async fn set_user_role(&self, user_id: Uuid, role: GlobalRole) -> Result<bool, sqlx::Error>;
+
+    async fn create_session(
+        &self,
+        user_id: Uuid,
+        refresh_token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<Uuid, sqlx::Error>;
+
+    /// Swaps the refresh token of a live session, a refresh token can only
+    /// ever be used once. Returns the `(session_id, user_id)` pair, `None` if
+    /// the token is unknown, expired or revoked.
+    async fn rotate_session(
+        &self,
+        refresh_token_hash: &str,
+        new_refresh_token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<Option<(Uuid, Uuid)>, sqlx::Error>;
+
+    async fn revoke_session(&self, session_id: Uuid) -> Result<(), sqlx::Error>;
+
+    async fn revoke_user_sessions(&self, user_id: Uuid) -> Result<(), sqlx::Error>;
+
+    async fn is_session_active(&self, session_id: Uuid) -> Result<bool, sqlx::Error>;
+
+    /// Looks up a live personal access token by its hash and records its use.
+    /// Returns the `(token_id, user_id, scopes)` triple.
+    async fn use_personal_access_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<(Uuid, Uuid, Vec<TokenScope>)>, sqlx::Error>;
+
+    /// Lists the tokens of a user which have not been revoked, expired ones
+    /// included.
+    async fn get_personal_access_tokens(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<PersonalAccessToken>, sqlx::Error>;
+
+    async fn create_personal_access_token(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        token_hash: &str,
+        token_prefix: &str,
+        scopes: &[TokenScope],
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<PersonalAccessToken, sqlx::Error>;
+
+    /// Returns `None` if the token does not exist, does not belong to the user
+    /// or is already revoked.
+    async fn revoke_personal_access_token(
+        &self,
+        user_id: Uuid,
+        token_id: Uuid,
+    ) -> Result<Option<PersonalAccessToken>, sqlx::Error>;
+
+    /// Remembers an authorization until its code is exchanged.
+    async fn create_oauth_state(
+        &self,
+        state: &str,
+        oauth_state: &OauthState,
+        expires_at: OffsetDateTime,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Spends the state, `None` if it is unknown, expired or already used.
+    async fn take_oauth_state(&self, state: &str) -> Result<Option<OauthState>, sqlx::Error>;
+}
+
+/// Translation projects and their members.
+#[async_trait]
+pub trait ProjectRepository: Send + Sync {
+    /// Lists public projects, plus the private ones `user_id` is a member of.
+    /// Administrators see every project.
+    async fn get_projects(
+        &self,
+        pagination: ApiPagination,
+        filter: ProjectFilter,
+        user_id: Option<Uuid>,
+        is_admin: bool,
+    ) -> Result<Vec<Project>, sqlx::Error>;
+
+    async fn get_project(&self, id: Uuid) -> Result<Option<Project>, sqlx::Error>;
+
+    async fn get_project_access(
+        &self,
+        project_id: Uuid,
+        user_id: Option<Uuid>,
+    ) -> Result<Option<ProjectAccess>, sqlx::Error>;
+
+    /// Creates the project with `owner_id` as its owner.
+    async fn create_project(
+        &self,
+        request: &CreateProjectRequest,
+        source_language: &str,
+        owner_id: Uuid,
+    ) -> Result<Project, sqlx::Error>;
+
+    async fn update_project(
+        &self,
+        id: Uuid,
+        request: &UpdateProjectRequest,
+    ) -> Result<Option<Project>, sqlx::Error>;
+
+    async fn set_project_archived(
+        &self,
+        id: Uuid,
+        archived: bool,
+    ) -> Result<Option<Project>, sqlx::Error>;
+
+    async fn get_project_members(
+        &self,
+        project_id: Uuid,
+    ) -> Result<Vec<ProjectMember>, sqlx::Error>;
+
+    async fn get_project_role(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<ProjectRole>, sqlx::Error>;
+
+    /// Adds the member or changes their role, unless that demotes the last
+    /// owner.
+    async fn set_project_member(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+        role: ProjectRole,
+    ) -> Result<MemberUpdate<ProjectRole>, sqlx::Error>;
+
+    /// Removes the member unless they are the last owner.
+    async fn remove_project_member(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<MemberUpdate<ProjectRole>, sqlx::Error>;
+
+    /// Visual novel a new project translates.
+    async fn get_visual_novel(&self, id: Uuid) -> Result<Option<VisualNovel>, sqlx::Error>;
+}
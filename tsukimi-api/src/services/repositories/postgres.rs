@@ -0,0 +1,345 @@
+use async_trait::async_trait;
+use time::OffsetDateTime;
+use tsukimi_core::{
+    auth::{PersonalAccessToken, TokenScope, UserIdentity},
+    models::{
+        CreateProjectRequest, Engine, EngineMember, EngineRole, EngineVersion, GlobalRole, Project,
+        ProjectMember, ProjectRole, PublisherKey, UpdateProjectRequest, User, VisualNovel,
+    },
+};
+use uuid::Uuid;
+
+use super::{EngineRepository, EngineVersionRepository, ProjectRepository, UserRepository};
+use crate::services::database::{
+    ApiPagination, DatabaseService, EngineFilter, MemberUpdate, NewEngineVersion, NewIdentity,
+    OauthState, ProjectAccess, ProjectFilter, VersionArtifact,
+};
+
+#[async_trait]
+impl EngineRepository for DatabaseService {
+    async fn get_engines(
+        &self,
+        pagination: ApiPagination,
+        filter: EngineFilter,
+    ) -> Result<Vec<Engine>, sqlx::Error> {
+        DatabaseService::get_engines(self, pagination, filter).await
+    }
+
+    async fn get_engine(&self, id: Uuid) -> Result<Option<Engine>, sqlx::Error> {
+        DatabaseService::get_engine(self, id).await
+    }
+
+    async fn deprecate_engine(
+        &self,
+        id: Uuid,
+        message: &str,
+        replacement_engine_id: Option<Uuid>,
+    ) -> Result<Option<Engine>, sqlx::Error> {
+        DatabaseService::deprecate_engine(self, id, message, replacement_engine_id).await
+    }
+
+    async fn undeprecate_engine(&self, id: Uuid) -> Result<Option<Engine>, sqlx::Error> {
+        DatabaseService::undeprecate_engine(self, id).await
+    }
+
+    async fn increment_downloads(&self, engine_id: Uuid) -> Result<(), sqlx::Error> {
+        DatabaseService::increment_downloads(self, engine_id).await
+    }
+
+    async fn get_engine_role(
+        &self,
+        engine_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<EngineRole>, sqlx::Error> {
+        DatabaseService::get_engine_role(self, engine_id, user_id).await
+    }
+
+    async fn get_engine_members(&self, engine_id: Uuid) -> Result<Vec<EngineMember>, sqlx::Error> {
+        DatabaseService::get_engine_members(self, engine_id).await
+    }
+
+    async fn set_engine_member(
+        &self,
+        engine_id: Uuid,
+        user_id: Uuid,
+        role: EngineRole,
+    ) -> Result<MemberUpdate<EngineRole>, sqlx::Error> {
+        DatabaseService::set_engine_member(self, engine_id, user_id, role).await
+    }
+
+    async fn remove_engine_member(
+        &self,
+        engine_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<MemberUpdate<EngineRole>, sqlx::Error> {
+        DatabaseService::remove_engine_member(self, engine_id, user_id).await
+    }
+}
+
+#[async_trait]
+impl EngineVersionRepository for DatabaseService {
+    async fn get_engine_versions(
+        &self,
+        engine_id: Uuid,
+    ) -> Result<Vec<EngineVersion>, sqlx::Error> {
+        DatabaseService::get_engine_versions(self, engine_id).await
+    }
+
+    async fn get_engine_version(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+    ) -> Result<Option<EngineVersion>, sqlx::Error> {
+        DatabaseService::get_engine_version(self, engine_id, version).await
+    }
+
+    async fn get_version_artifact(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+    ) -> Result<Option<VersionArtifact>, sqlx::Error> {
+        DatabaseService::get_version_artifact(self, engine_id, version).await
+    }
+
+    async fn publish_version(
+        &self,
+        version: &NewEngineVersion<'_>,
+    ) -> Result<EngineVersion, sqlx::Error> {
+        DatabaseService::publish_version(self, version).await
+    }
+
+    async fn yank_version(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+        reason: Option<&str>,
+        yanked_by: Uuid,
+    ) -> Result<Option<EngineVersion>, sqlx::Error> {
+        DatabaseService::yank_version(self, engine_id, version, reason, yanked_by).await
+    }
+
+    async fn unyank_version(
+        &self,
+        engine_id: Uuid,
+        version: &str,
+    ) -> Result<Option<EngineVersion>, sqlx::Error> {
+        DatabaseService::unyank_version(self, engine_id, version).await
+    }
+
+    async fn get_publisher_key(&self, id: Uuid) -> Result<Option<PublisherKey>, sqlx::Error> {
+        DatabaseService::get_publisher_key(self, id).await
+    }
+}
+
+#[async_trait]
+impl UserRepository for DatabaseService {
+    async fn get_user(&self, id: Uuid) -> Result<Option<User>, sqlx::Error> {
+        DatabaseService::get_user(self, id).await
+    }
+
+    async fn upsert_identity_user(&self, identity: &NewIdentity) -> Result<User, sqlx::Error> {
+        DatabaseService::upsert_identity_user(self, identity).await
+    }
+
+    async fn get_user_identities(&self, user_id: Uuid) -> Result<Vec<UserIdentity>, sqlx::Error> {
+        DatabaseService::get_user_identities(self, user_id).await
+    }
+
+    async fn link_identity(
+        &self,
+        user_id: Uuid,
+        identity: &NewIdentity,
+    ) -> Result<Option<UserIdentity>, sqlx::Error> {
+        DatabaseService::link_identity(self, user_id, identity).await
+    }
+
+    async fn unlink_identity(&self, user_id: Uuid, provider: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::unlink_identity(self, user_id, provider).await
+    }
+
+    async fn get_user_role(&self, user_id: Uuid) -> Result<Option<GlobalRole>, sqlx::Error> {
+        DatabaseService::get_user_role(self, user_id).await
+    }
+
+    async fn set_user_role(&self, user_id: Uuid, role: GlobalRole) -> Result<bool, sqlx::Error> {
+        DatabaseService::set_user_role(self, user_id, role).await
+    }
+
+    async fn create_session(
+        &self,
+        user_id: Uuid,
+        refresh_token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<Uuid, sqlx::Error> {
+        DatabaseService::create_session(self, user_id, refresh_token_hash, expires_at).await
+    }
+
+    async fn rotate_session(
+        &self,
+        refresh_token_hash: &str,
+        new_refresh_token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<Option<(Uuid, Uuid)>, sqlx::Error> {
+        DatabaseService::rotate_session(
+            self,
+            refresh_token_hash,
+            new_refresh_token_hash,
+            expires_at,
+        )
+        .await
+    }
+
+    async fn revoke_session(&self, session_id: Uuid) -> Result<(), sqlx::Error> {
+        DatabaseService::revoke_session(self, session_id).await
+    }
+
+    async fn revoke_user_sessions(&self, user_id: Uuid) -> Result<(), sqlx::Error> {
+        DatabaseService::revoke_user_sessions(self, user_id).await
+    }
+
+    async fn is_session_active(&self, session_id: Uuid) -> Result<bool, sqlx::Error> {
+        DatabaseService::is_session_active(self, session_id).await
+    }
+
+    async fn use_personal_access_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<(Uuid, Uuid, Vec<TokenScope>)>, sqlx::Error> {
+        DatabaseService::use_personal_access_token(self, token_hash).await
+    }
+
+    async fn get_personal_access_tokens(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<PersonalAccessToken>, sqlx::Error> {
+        DatabaseService::get_personal_access_tokens(self, user_id).await
+    }
+
+    async fn create_personal_access_token(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        token_hash: &str,
+        token_prefix: &str,
+        scopes: &[TokenScope],
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<PersonalAccessToken, sqlx::Error> {
+        DatabaseService::create_personal_access_token(
+            self,
+            user_id,
+            name,
+            token_hash,
+            token_prefix,
+            scopes,
+            expires_at,
+        )
+        .await
+    }
+
+    async fn revoke_personal_access_token(
+        &self,
+        user_id: Uuid,
+        token_id: Uuid,
+    ) -> Result<Option<PersonalAccessToken>, sqlx::Error> {
+        DatabaseService::revoke_personal_access_token(self, user_id, token_id).await
+    }
+
+    async fn create_oauth_state(
+        &self,
+        state: &str,
+        oauth_state: &OauthState,
+        expires_at: OffsetDateTime,
+    ) -> Result<(), sqlx::Error> {
+        DatabaseService::create_oauth_state(self, state, oauth_state, expires_at).await
+    }
+
+    async fn take_oauth_state(&self, state: &str) -> Result<Option<OauthState>, sqlx::Error> {
+        DatabaseService::take_oauth_state(self, state).await
+    }
+}
+
+#[async_trait]
+impl ProjectRepository for DatabaseService {
+    async fn get_projects(
+        &self,
+        pagination: ApiPagination,
+        filter: ProjectFilter,
+        user_id: Option<Uuid>,
+        is_admin: bool,
+    ) -> Result<Vec<Project>, sqlx::Error> {
+        DatabaseService::get_projects(self, pagination, filter, user_id, is_admin).await
+    }
+
+    async fn get_project(&self, id: Uuid) -> Result<Option<Project>, sqlx::Error> {
+        DatabaseService::get_project(self, id).await
+    }
+
+    async fn get_project_access(
+        &self,
+        project_id: Uuid,
+        user_id: Option<Uuid>,
+    ) -> Result<Option<ProjectAccess>, sqlx::Error> {
+        DatabaseService::get_project_access(self, project_id, user_id).await
+    }
+
+    async fn create_project(
+        &self,
+        request: &CreateProjectRequest,
+        source_language: &str,
+        owner_id: Uuid,
+    ) -> Result<Project, sqlx::Error> {
+        DatabaseService::create_project(self, request, source_language, owner_id).await
+    }
+
+    async fn update_project(
+        &self,
+        id: Uuid,
+        request: &UpdateProjectRequest,
+    ) -> Result<Option<Project>, sqlx::Error> {
+        DatabaseService::update_project(self, id, request).await
+    }
+
+    async fn set_project_archived(
+        &self,
+        id: Uuid,
+        archived: bool,
+    ) -> Result<Option<Project>, sqlx::Error> {
+        DatabaseService::set_project_archived(self, id, archived).await
+    }
+
+    async fn get_project_members(
+        &self,
+        project_id: Uuid,
+    ) -> Result<Vec<ProjectMember>, sqlx::Error> {
+        DatabaseService::get_project_members(self, project_id).await
+    }
+
+    async fn get_project_role(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<ProjectRole>, sqlx::Error> {
+        DatabaseService::get_project_role(self, project_id, user_id).await
+    }
+
+    async fn set_project_member(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+        role: ProjectRole,
+    ) -> Result<MemberUpdate<ProjectRole>, sqlx::Error> {
+        DatabaseService::set_project_member(self, project_id, user_id, role).await
+    }
+
+    async fn remove_project_member(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<MemberUpdate<ProjectRole>, sqlx::Error> {
+        DatabaseService::remove_project_member(self, project_id, user_id).await
+    }
+
+    async fn get_visual_novel(&self, id: Uuid) -> Result<Option<VisualNovel>, sqlx::Error> {
+        DatabaseService::get_visual_novel(self, id).await
+    }
+}
@@ -0,0 +1,154 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use oauth2::{AccessToken, RefreshToken};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{config::SessionConfiguration, error::ApiError};
+
+static ISSUER: &str = "tsukimi-api";
+/// Marks personal access tokens so they can be told apart from signed session tokens.
+pub static PERSONAL_ACCESS_TOKEN_PREFIX: &str = "tsk_pat_";
+
+/// Claims carried by the access tokens minted by the API.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    /// Tsukimi user id
+    pub sub: Uuid,
+    /// Session the token was issued for, checked against the revocation list
+    pub sid: Uuid,
+    pub iss: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+struct SigningKey {
+    id: String,
+    encoding: EncodingKey,
+}
+
+#[derive(Clone)]
+pub struct SessionService {
+    signing_key: Arc<SigningKey>,
+    decoding_keys: Arc<HashMap<String, DecodingKey>>,
+    access_token_ttl: Duration,
+    refresh_token_ttl: Duration,
+}
+
+impl TryFrom<&SessionConfiguration> for SessionService {
+    type Error = String;
+
+    fn try_from(session_config: &SessionConfiguration) -> Result<Self, Self::Error> {
+        let first = session_config
+            .keys()
+            .first()
+            .ok_or_else(|| "At least one session signing key is required".to_string())?;
+
+        let mut decoding_keys = HashMap::new();
+        for key in session_config.keys() {
+            if key.secret().len() < 32 {
+                return Err(format!(
+                    "Session signing key `{}` must be at least 32 bytes long",
+                    key.id()
+                ));
+            }
+            if decoding_keys
+                .insert(
+                    key.id().to_string(),
+                    DecodingKey::from_secret(key.secret().as_bytes()),
+                )
+                .is_some()
+            {
+                return Err(format!("Duplicate session signing key id `{}`", key.id()));
+            }
+        }
+
+        Ok(SessionService {
+            signing_key: Arc::new(SigningKey {
+                id: first.id().to_string(),
+                encoding: EncodingKey::from_secret(first.secret().as_bytes()),
+            }),
+            decoding_keys: Arc::new(decoding_keys),
+            access_token_ttl: Duration::from_secs(session_config.access_token_ttl()),
+            refresh_token_ttl: Duration::from_secs(session_config.refresh_token_ttl()),
+        })
+    }
+}
+
+impl SessionService {
+    pub fn access_token_ttl(&self) -> Duration {
+        self.access_token_ttl
+    }
+
+    /// Expiration date of a refresh token issued now.
+    pub fn refresh_token_expiration(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc() + self.refresh_token_ttl
+    }
+
+    pub fn sign_access_token(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+    ) -> Result<AccessToken, ApiError> {
+        let now = OffsetDateTime::now_utc();
+        let claims = Claims {
+            sub: user_id,
+            sid: session_id,
+            iss: ISSUER.to_string(),
+            iat: now.unix_timestamp(),
+            exp: (now + self.access_token_ttl).unix_timestamp(),
+        };
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some(self.signing_key.id.clone());
+
+        jsonwebtoken::encode(&header, &claims, &self.signing_key.encoding)
+            .map(AccessToken::new)
+            .map_err(|e| ApiError::Internal(format!("Failed to sign access token: {}", e)))
+    }
+
+    /// Checks the signature and expiration of an access token.
+    /// Revocation is checked separately since it needs the database.
+    pub fn verify_access_token(&self, token: &str) -> Result<Claims, ApiError> {
+        let header = jsonwebtoken::decode_header(token)
+            .map_err(|_| ApiError::Unauthorized("Malformed access token".into()))?;
+        let key = header
+            .kid
+            .and_then(|kid| self.decoding_keys.get(&kid))
+            .ok_or_else(|| ApiError::Unauthorized("Unknown signing key".into()))?;
+
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_issuer(&[ISSUER]);
+
+        jsonwebtoken::decode::<Claims>(token, key, &validation)
+            .map(|data| data.claims)
+            .map_err(|_| ApiError::Unauthorized("Invalid or expired access token".into()))
+    }
+
+    pub fn generate_refresh_token() -> RefreshToken {
+        RefreshToken::new(random_secret())
+    }
+
+    pub fn generate_personal_access_token() -> AccessToken {
+        AccessToken::new(format!(
+            "{}{}",
+            PERSONAL_ACCESS_TOKEN_PREFIX,
+            random_secret()
+        ))
+    }
+
+    /// Refresh and personal access tokens are only stored hashed, a database
+    /// leak must not allow to use them.
+    pub fn hash_secret(secret: &str) -> String {
+        hex::encode(Sha256::digest(secret.as_bytes()))
+    }
+}
+
+fn random_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
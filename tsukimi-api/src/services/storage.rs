@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use bytes::Bytes;
+use object_store::{
+    ObjectStore, PutPayload, aws::AmazonS3Builder, local::LocalFileSystem, path::Path,
+};
+
+use crate::{config::StorageConfiguration, error::ApiError};
+
+/// Probe object written to check that the backend accepts writes
+static PROBE_KEY: &str = ".readyz";
+
+/// Engine artifacts, on the local disk or in an S3 compatible bucket.
+#[derive(Clone)]
+pub struct StorageService {
+    store: Arc<dyn ObjectStore>,
+}
+
+impl TryFrom<&StorageConfiguration> for StorageService {
+    type Error = String;
+
+    fn try_from(storage_config: &StorageConfiguration) -> Result<Self, Self::Error> {
+        let store: Arc<dyn ObjectStore> = match storage_config {
+            StorageConfiguration::Local { path } => {
+                std::fs::create_dir_all(path)
+                    .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+                Arc::new(LocalFileSystem::new_with_prefix(path).map_err(|e| e.to_string())?)
+            }
+            StorageConfiguration::S3 {
+                bucket,
+                region,
+                endpoint,
+            } => {
+                let mut builder = AmazonS3Builder::from_env()
+                    .with_bucket_name(bucket)
+                    .with_region(region);
+                if let Some(endpoint) = endpoint {
+                    builder = builder
+                        .with_endpoint(endpoint)
+                        .with_allow_http(endpoint.starts_with("http://"));
+                }
+                Arc::new(builder.build().map_err(|e| e.to_string())?)
+            }
+        };
+        Ok(StorageService { store })
+    }
+}
+
+impl StorageService {
+    /// Returns `None` if nothing is stored under `key`.
+    pub async fn get(&self, key: &str) -> Result<Option<Bytes>, ApiError> {
+        let result = match self.store.get(&Path::from(key)).await {
+            Ok(result) => result,
+            Err(object_store::Error::NotFound { .. }) => return Ok(None),
+            Err(e) => return Err(ApiError::Internal(format!("Failed to read {}: {}", key, e))),
+        };
+        result
+            .bytes()
+            .await
+            .map(Some)
+            .map_err(|e| ApiError::Internal(format!("Failed to read {}: {}", key, e)))
+    }
+
+    pub async fn put(&self, key: &str, bytes: Bytes) -> Result<(), ApiError> {
+        self.store
+            .put(&Path::from(key), PutPayload::from_bytes(bytes))
+            .await
+            .map(|_| ())
+            .map_err(|e| ApiError::Internal(format!("Failed to write {}: {}", key, e)))
+    }
+
+    /// Deleting a key which holds nothing is not an error.
+    pub async fn delete(&self, key: &str) -> Result<(), ApiError> {
+        match self.store.delete(&Path::from(key)).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(e) => Err(ApiError::Internal(format!(
+                "Failed to delete {}: {}",
+                key, e
+            ))),
+        }
+    }
+
+    pub async fn check_writable(&self) -> Result<(), String> {
+        let path = Path::from(PROBE_KEY);
+        self.store
+            .put(&path, PutPayload::from_static(b"ok"))
+            .await
+            .map_err(|e| format!("Storage is not writable: {}", e))?;
+        self.store
+            .delete(&path)
+            .await
+            .map_err(|e| format!("Storage is not writable: {}", e))
+    }
+}
@@ -0,0 +1,462 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use reqwest::{
+    Url,
+    dns::{Addrs, Name, Resolve, Resolving},
+    header::CONTENT_TYPE,
+};
+use sha2::Sha256;
+use time::OffsetDateTime;
+use tokio::task::JoinSet;
+use tracing::error;
+use tsukimi_core::models::DeliveryStatus;
+
+use crate::{
+    config::WebhookConfiguration,
+    services::{
+        database::{DatabaseService, DeliveryAttempt, PendingDelivery},
+        health::HealthService,
+        jobs, metrics,
+    },
+};
+
+/// `t=<unix timestamp>,v1=<hex HMAC-SHA256 of "<timestamp>.<body>">`, keyed
+/// with the webhook secret
+pub static SIGNATURE_HEADER: &str = "x-tsukimi-signature";
+pub static EVENT_HEADER: &str = "x-tsukimi-event";
+/// Id of the delivery, differs between a delivery and its redeliveries
+pub static DELIVERY_HEADER: &str = "x-tsukimi-delivery";
+static SECRET_PREFIX: &str = "whsec_";
+/// Deliveries claimed per poll
+static BATCH_SIZE: i64 = 20;
+/// Delay before the first retry, doubled after every failed attempt
+static RETRY_BASE: Duration = Duration::from_secs(30);
+static MAX_RETRY_DELAY: Duration = Duration::from_secs(6 * 60 * 60);
+/// Length of the response body kept in the delivery log
+static MAX_RESPONSE_BODY: usize = 1024;
+
+/// Sends the queued webhook deliveries. Every instance runs a dispatcher,
+/// deliveries are claimed with `SKIP LOCKED` so each is sent by one of them.
+#[derive(Clone)]
+pub struct WebhookService {
+    http_client: reqwest::Client,
+    lease: Duration,
+    max_attempts: u32,
+    poll_interval: Duration,
+    allow_http: bool,
+    allow_private_addresses: bool,
+}
+
+impl TryFrom<&WebhookConfiguration> for WebhookService {
+    type Error = String;
+
+    fn try_from(webhook_config: &WebhookConfiguration) -> Result<Self, Self::Error> {
+        let http_client = reqwest::Client::builder()
+            .timeout(webhook_config.timeout())
+            .user_agent(concat!(
+                env!("CARGO_PKG_NAME"),
+                "/",
+                env!("CARGO_PKG_VERSION")
+            ))
+            // A redirect would send the signed payload somewhere unchecked
+            .redirect(reqwest::redirect::Policy::none())
+            // A proxy would resolve the host itself
+            .no_proxy();
+        let http_client = match webhook_config.allow_private_addresses() {
+            true => http_client,
+            false => http_client.dns_resolver(Arc::new(PublicResolver)),
+        }
+        .build()
+        .map_err(|e| e.to_string())?;
+        Ok(WebhookService {
+            http_client,
+            // Leaves time to record the outcome once the request timed out
+            lease: webhook_config.timeout() * 2,
+            max_attempts: webhook_config.max_attempts(),
+            poll_interval: webhook_config.poll_interval(),
+            allow_http: webhook_config.allow_http(),
+            allow_private_addresses: webhook_config.allow_private_addresses(),
+        })
+    }
+}
+
+impl WebhookService {
+    pub fn generate_secret() -> String {
+        let mut bytes = [0u8; 32];
+        rand::rng().fill_bytes(&mut bytes);
+        format!("{}{}", SECRET_PREFIX, hex::encode(bytes))
+    }
+
+    /// Refuses URLs pointing at the network of the API, the response of a
+    /// delivery is readable by the owner of the webhook.
+    pub async fn validate_url(&self, url: &str) -> Result<(), String> {
+        let url = Url::parse(url).map_err(|e| format!("Invalid webhook URL: {}", e))?;
+        match url.scheme() {
+            "https" => {}
+            "http" if self.allow_http => {}
+            _ => return Err("Webhook URL must use https".into()),
+        }
+        let Some(host) = url.host_str() else {
+            return Err("Webhook URL must have a host".into());
+        };
+        if self.allow_private_addresses {
+            return Ok(());
+        }
+        match literal_ip(&url) {
+            Some(ip) => check_public(ip),
+            None => resolve_public(host).await.map(|_| ()),
+        }
+    }
+
+    /// Checked again on every delivery, the host may have changed since the
+    /// webhook was saved. Names are checked by the resolver of the client.
+    fn check_destination(&self, url: &str) -> Result<(), String> {
+        if self.allow_private_addresses {
+            return Ok(());
+        }
+        let url = Url::parse(url).map_err(|e| format!("Invalid webhook URL: {}", e))?;
+        literal_ip(&url).map_or(Ok(()), check_public)
+    }
+
+    pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("webhook secret as HMAC key");
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        format!(
+            "t={},v1={}",
+            timestamp,
+            hex::encode(mac.finalize().into_bytes())
+        )
+    }
+
+    /// Sends due deliveries until the instance starts shutting down.
+    pub fn spawn_dispatcher(self, database: DatabaseService, health: HealthService) {
+        tokio::spawn(async move {
+            while !health.is_shutting_down() {
+                let claimed = database
+                    .claim_webhook_deliveries(BATCH_SIZE, self.lease)
+                    .await
+                    .unwrap_or_else(|e| {
+                        error!("Failed to claim webhook deliveries: {}", e);
+                        Vec::new()
+                    });
+                let backlog = claimed.len() as i64 == BATCH_SIZE;
+
+                let mut sending = JoinSet::new();
+                for delivery in claimed {
+                    let service = self.clone();
+                    let database = database.clone();
+                    sending.spawn(async move { service.deliver(&database, delivery).await });
+                }
+                while sending.join_next().await.is_some() {}
+
+                if !backlog {
+                    tokio::time::sleep(self.poll_interval).await;
+                }
+            }
+        });
+    }
+
+    async fn deliver(&self, database: &DatabaseService, delivery: PendingDelivery) {
+        let body = delivery.payload.0.to_string().into_bytes();
+        let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        let signature = Self::sign(&delivery.secret, timestamp, &body);
+
+        let result = match self.check_destination(&delivery.url) {
+            Ok(()) => self
+                .http_client
+                .post(&delivery.url)
+                .header(CONTENT_TYPE, "application/json")
+                .header(EVENT_HEADER, delivery.event.as_str())
+                .header(DELIVERY_HEADER, delivery.id.to_string())
+                .header(SIGNATURE_HEADER, signature)
+                .body(body)
+                .send()
+                .await
+                .map_err(|e| describe(&e)),
+            Err(e) => Err(e),
+        };
+        let (response_status, response_body, error) = match result {
+            Ok(response) => {
+                let status = response.status();
+                let body = read_body(response, MAX_RESPONSE_BODY).await;
+                (Some(status), Some(body), None)
+            }
+            Err(e) => (None, None, Some(e)),
+        };
+
+        let succeeded = response_status.is_some_and(|status| status.is_success());
+        let attempts = delivery.attempts as u32 + 1;
+        let (status, next_attempt_at) = match succeeded {
+            true => (DeliveryStatus::Succeeded, None),
+            false if attempts >= self.max_attempts => (DeliveryStatus::Failed, None),
+            false => (
+                DeliveryStatus::Pending,
+                Some(
+                    OffsetDateTime::now_utc()
+                        + jobs::retry_delay(attempts, RETRY_BASE, MAX_RETRY_DELAY),
+                ),
+            ),
+        };
+        metrics::record_webhook_delivery(delivery.event.as_str(), succeeded);
+
+        let attempt = DeliveryAttempt {
+            status,
+            next_attempt_at,
+            response_status: response_status.map(|status| status.as_u16().into()),
+            response_body: response_body.as_deref(),
+            error: error.as_deref(),
+        };
+        if let Err(e) = database.record_webhook_attempt(delivery.id, attempt).await {
+            error!(
+                "Failed to record attempt of webhook delivery {}: {}",
+                delivery.id, e
+            );
+        }
+    }
+}
+
+/// Resolves the hosts of the deliveries, refusing internal addresses so a
+/// name can't be pointed at one once the webhook is validated.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addresses = resolve_public(name.as_str()).await?;
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Address of URLs whose host is not a name, IPv6 ones are in brackets.
+fn literal_ip(url: &Url) -> Option<IpAddr> {
+    url.host_str()?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .ok()
+}
+
+async fn resolve_public(host: &str) -> Result<Vec<SocketAddr>, String> {
+    // The port is replaced by the one of the URL
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, 0))
+        .await
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+        .collect();
+    if addresses.is_empty() {
+        return Err(format!("{} doesn't resolve to any address", host));
+    }
+    for address in &addresses {
+        check_public(address.ip())?;
+    }
+    Ok(addresses)
+}
+
+fn check_public(ip: IpAddr) -> Result<(), String> {
+    match is_public(ip) {
+        true => Ok(()),
+        false => Err(format!("Webhooks can't be sent to the address {}", ip)),
+    }
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                // 0.0.0.0/8, shared address space, benchmarking and reserved
+                || a == 0
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 198 && (18..20).contains(&b))
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => match embedded_ipv4(ip) {
+            Some(ip) => is_public(ip.into()),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local()
+                    // Documentation
+                    || first == 0x2001 && ip.segments()[1] == 0x0db8)
+            }
+        },
+    }
+}
+
+/// IPv4 address an IPv6 one routes to: IPv4-mapped `::ffff:a.b.c.d`,
+/// IPv4-compatible `::a.b.c.d`, NAT64 `64:ff9b::/96` and 6to4 `2002::/16`.
+/// `::` and `::1` come out as `0.0.0.0` and `0.0.0.1`, neither is public.
+fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let segments = ip.segments();
+    let from_segments =
+        |high: u16, low: u16| Ipv4Addr::from((u32::from(high) << 16) | u32::from(low));
+    match segments {
+        [0, 0, 0, 0, 0, 0xffff, high, low] | [0, 0, 0, 0, 0, 0, high, low] => {
+            Some(from_segments(high, low))
+        }
+        [0x64, 0xff9b, 0, 0, 0, 0, high, low] => Some(from_segments(high, low)),
+        [0x2002, high, low, ..] => Some(from_segments(high, low)),
+        _ => None,
+    }
+}
+
+/// Only the start of the response is kept, the rest is not read.
+async fn read_body(mut response: reqwest::Response, length: usize) -> String {
+    let mut body = Vec::new();
+    while body.len() < length {
+        match response.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            _ => break,
+        }
+    }
+    truncate(String::from_utf8_lossy(&body).into_owned(), length)
+}
+
+/// The error of reqwest alone doesn't say why the request failed.
+fn describe(error: &reqwest::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message = format!("{}: {}", message, cause);
+        source = cause.source();
+    }
+    message
+}
+
+fn truncate(mut text: String, length: usize) -> String {
+    if text.len() > length {
+        let mut end = length;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(ip: &str) -> bool {
+        is_public(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn private_ipv4_addresses_are_refused() {
+        for ip in [
+            "0.0.0.0",
+            "0.1.2.3",
+            "127.0.0.1",
+            "10.0.0.1",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "198.18.0.1",
+            "192.0.2.1",
+            "224.0.0.1",
+            "240.0.0.1",
+            "255.255.255.255",
+        ] {
+            assert!(!public(ip), "{} is not public", ip);
+        }
+        for ip in ["1.1.1.1", "93.184.215.14", "100.128.0.1", "198.20.0.1"] {
+            assert!(public(ip), "{} is public", ip);
+        }
+    }
+
+    #[test]
+    fn private_ipv6_addresses_are_refused() {
+        for ip in [
+            "::",
+            "::1",
+            "fc00::1",
+            "fd12:3456::1",
+            "fe80::1",
+            "ff02::1",
+            "2001:db8::1",
+        ] {
+            assert!(!public(ip), "{} is not public", ip);
+        }
+        assert!(public("2606:4700:4700::1111"));
+    }
+
+    /// IPv6 forms routing to an IPv4 address are as public as that address.
+    #[test]
+    fn embedded_ipv4_addresses_are_checked() {
+        for ip in [
+            // IPv4-mapped
+            "::ffff:127.0.0.1",
+            "::ffff:10.0.0.1",
+            // IPv4-compatible
+            "::127.0.0.1",
+            "::169.254.169.254",
+            // NAT64
+            "64:ff9b::7f00:1",
+            "64:ff9b::a9fe:a9fe",
+            // 6to4
+            "2002:7f00:1::",
+            "2002:a00:1:1::1",
+        ] {
+            assert!(!public(ip), "{} is not public", ip);
+        }
+        for ip in ["::ffff:1.1.1.1", "64:ff9b::101:101", "2002:101:101::1"] {
+            assert!(public(ip), "{} is public", ip);
+        }
+    }
+
+    #[test]
+    fn literal_hosts_are_parsed() {
+        let ip = |url: &str| literal_ip(&Url::parse(url).unwrap());
+        assert_eq!(ip("http://127.0.0.1:8080/hook"), "127.0.0.1".parse().ok());
+        assert_eq!(
+            ip("http://[64:ff9b::7f00:1]/hook"),
+            "64:ff9b::7f00:1".parse().ok()
+        );
+        assert_eq!(ip("https://hooks.example/hook"), None);
+    }
+
+    #[test]
+    fn payloads_are_signed_with_their_timestamp() {
+        let body = br#"{"event":"ping"}"#;
+        assert_eq!(
+            WebhookService::sign("whsec_test", 1_700_000_000, body),
+            "t=1700000000,v1=aa8efe37b751e71157c508c5ac4acb1e9fe5225db98355dfc00f4b680afbc447"
+        );
+        assert_eq!(
+            WebhookService::sign("", 0, b""),
+            "t=0,v1=b849d5a581847b281957065739df36df2463d1977ea8d6e1e4e6cf33fadc68c3"
+        );
+
+        let signature = WebhookService::sign("whsec_test", 1_700_000_000, body);
+        assert_ne!(
+            WebhookService::sign("whsec_other", 1_700_000_000, body),
+            signature
+        );
+        assert_ne!(
+            WebhookService::sign("whsec_test", 1_700_000_001, body),
+            signature
+        );
+        assert_ne!(
+            WebhookService::sign("whsec_test", 1_700_000_000, br#"{"event":"pong"}"#),
+            signature
+        );
+    }
+}
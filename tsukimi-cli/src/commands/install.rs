@@ -1,15 +1,25 @@
 use inquire::Text;
-use log::info;
+use log::{info, warn};
+use tsukimi_core::{models::Engine, signing};
 
 use crate::{
-    error::CliResult,
-    services::{api::ApiService, project_data::get_local_extension_state},
+    commands::list::PluginError,
+    error::{CliError, CliResult},
+    services::{
+        api::{ApiService, DownloadedArtifact},
+        project_data::{get_local_extension_state, get_project_data_folder},
+        trust_store::TrustStore,
+    },
 };
 
 #[derive(clap::Args)]
 pub struct InstallCommandParams {
     engine: Option<String>,
     force: Option<bool>,
+    /// Fingerprint of the publisher key to trust for this engine, required
+    /// when the engine is signed with a key not trusted yet
+    #[arg(long)]
+    trust: Option<String>,
 }
 
 pub async fn execute(params: InstallCommandParams) -> CliResult {
@@ -23,7 +33,24 @@ pub async fn execute(params: InstallCommandParams) -> CliResult {
     let extension_local = get_local_extension_state(&engine_name);
 
     // Fetch online the engine by name
-    let engine = ApiService::default().fetch_engine(&engine_name).await?;
+    let api = ApiService::default();
+    let engine = api.fetch_engine(&engine_name).await?;
+
+    if engine.deprecated_at.is_some() {
+        warn!(
+            "Engine `{}` is deprecated: {}",
+            engine_name,
+            engine.deprecation_message.as_deref().unwrap_or_default()
+        );
+    }
+    // Yanked versions are never resolved, only existing lockfiles keep them
+    let Some(current_version) = engine.current_version.clone() else {
+        info!(
+            "Engine `{}` has no installable version, every release was yanked.",
+            engine_name
+        );
+        return Ok(());
+    };
 
     // Compare the versions
     // If the online version is newer, download it
@@ -33,31 +60,31 @@ pub async fn execute(params: InstallCommandParams) -> CliResult {
             info!("Engine `{}` not found locally, downloading...", engine_name);
             true
         }
-        Some(value) if value.version > engine.current_version => {
+        Some(value) if value.version > current_version => {
             info!(
                 "Engine `{}` is outdated (local: {}, online: {}), updating...",
-                engine_name, value.version, engine.current_version
+                engine_name, value.version, current_version
             );
             true
         }
-        Some(value) if value.version == engine.current_version => {
+        Some(value) if value.version == current_version => {
             info!(
                 "Engine `{}` is up to date (version: {}). No action needed.",
                 engine_name, value.version
             );
             false
         }
-        Some(value) if value.version < engine.current_version => {
+        Some(value) if value.version < current_version => {
             if params.force.unwrap_or(false) {
                 info!(
                     "Engine `{}` is outdated (local: {}, online: {}). Forcing update...",
-                    engine_name, value.version, engine.current_version
+                    engine_name, value.version, current_version
                 );
                 true
             } else {
                 info!(
                     "Engine `{}` is outdated (local: {}, online: {}). Use --force to update.",
-                    engine_name, value.version, engine.current_version
+                    engine_name, value.version, current_version
                 );
                 false
             }
@@ -70,6 +97,113 @@ pub async fn execute(params: InstallCommandParams) -> CliResult {
             false
         }
     };
+    if !need_install {
+        return Ok(());
+    }
+
+    let artifact = api.download_version(&engine, &current_version).await?;
+    verify_artifact(
+        &api,
+        &engine,
+        &engine_name,
+        &artifact,
+        params.trust.as_deref(),
+    )
+    .await?;
+
+    let plugins_dir = get_project_data_folder().ok_or_else(|| {
+        PluginError::ActionFailed("Could not locate the plugins directory".into())
+    })?;
+    std::fs::create_dir_all(&plugins_dir).map_err(|e| {
+        PluginError::ActionFailed(format!("Failed to create plugins directory: {}", e))
+    })?;
+    let path = plugins_dir.join(format!("{}.wasm", engine_name));
+    std::fs::write(&path, &artifact.bytes).map_err(|e| {
+        PluginError::ActionFailed(format!("Failed to write {}: {}", path.display(), e))
+    })?;
+    info!(
+        "Engine `{}` {} installed at {}",
+        engine_name,
+        current_version,
+        path.display()
+    );
+
+    Ok(())
+}
+
+/// Only artifacts signed with a live key of a maintainer of the engine, and
+/// trusted on this machine, are installed. Nothing is written to disk before
+/// this passes.
+async fn verify_artifact(
+    api: &ApiService,
+    engine: &Engine,
+    engine_name: &str,
+    artifact: &DownloadedArtifact,
+    trust: Option<&str>,
+) -> CliResult {
+    let untrusted = |reason: String| CliError::UntrustedArtifact(engine_name.to_string(), reason);
+
+    let (Some(signature), Some(key_id)) = (&artifact.signature, &artifact.publisher_key_id) else {
+        return Err(untrusted("the release is not signed".into()));
+    };
+    let key = api.fetch_publisher_key(key_id).await?;
+    // Computed here, the one sent by the registry is not trusted
+    let fingerprint = signing::decode_public_key(&key.public_key)
+        .map(|public_key| signing::fingerprint(&public_key))
+        .map_err(|e| untrusted(e.to_string()))?;
+    if key.revoked_at.is_some() {
+        return Err(untrusted(format!(
+            "it is signed with the revoked key {}",
+            fingerprint
+        )));
+    }
+    let members = api.fetch_engine_members(engine).await?;
+    if !members.iter().any(|member| member.user_id == key.user_id) {
+        return Err(untrusted(format!(
+            "it is signed with the key {} of a user who is not a maintainer",
+            fingerprint
+        )));
+    }
+    signing::verify_artifact(&key.public_key, signature, &artifact.bytes)
+        .map_err(|e| untrusted(e.to_string()))?;
+
+    let mut trust_store = TrustStore::load().map_err(PluginError::ActionFailed)?;
+    let trusted = trust_store
+        .trusted_keys(engine_name)
+        .is_some_and(|keys| keys.contains(&fingerprint));
+    match trust.map(str::to_lowercase) {
+        Some(expected) if expected != fingerprint => {
+            return Err(untrusted(format!(
+                "it is signed with the key {}, not the one passed to --trust",
+                fingerprint
+            )));
+        }
+        Some(_) => info!(
+            "Trusting key `{}` ({}) for `{}`",
+            key.name, fingerprint, engine_name
+        ),
+        None if trusted => {}
+        None if trust_store.trusted_keys(engine_name).is_some() => {
+            return Err(untrusted(format!(
+                "it is signed with the key {}, which is not trusted for this engine. \
+                 Check it with the maintainers, then pass --trust {}",
+                fingerprint, fingerprint
+            )));
+        }
+        // First install, later releases must be signed with this key
+        None => warn!(
+            "Trusting key `{}` ({}) for `{}` on first use",
+            key.name, fingerprint, engine_name
+        ),
+    }
+    if !trusted {
+        trust_store.trust(engine_name, &fingerprint);
+        trust_store.save().map_err(PluginError::ActionFailed)?;
+    }
 
+    info!(
+        "Signature of `{}` verified with key `{}` ({})",
+        engine_name, key.name, fingerprint
+    );
     Ok(())
 }
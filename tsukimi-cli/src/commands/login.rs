@@ -1,17 +1,16 @@
 use crate::{
     api::ApiError,
     error::{CliError, CliResult},
-    services::credentials::{read_token, store_token},
+    services::{
+        api::ApiService,
+        credentials::{read_token, store_token},
+    },
 };
 use inquire::Select;
 use log::info;
-use oauth2::{
-    AccessToken, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge,
-    RedirectUrl, RefreshToken, Scope, TokenResponse, TokenUrl, basic::BasicClient,
-};
-use reqwest::{Url, header};
+use oauth2::{AccessToken, AuthorizationCode, CsrfToken, RefreshToken};
+use reqwest::{StatusCode, Url};
 use serde::{Deserialize, Serialize};
-use time::OffsetDateTime;
 use tokio::{
     io::AsyncBufReadExt,
     io::AsyncWriteExt,
@@ -19,8 +18,11 @@ use tokio::{
     net::TcpListener,
     time::{Duration, interval},
 };
+use tsukimi_core::{auth::SessionResponse, models::User};
 
-static CLIENT_ID: &str = "Iv23li6unikd56cFZ6zX";
+/// Identity provider of the API the CLI logs in with, the device flow below
+/// talks to GitHub directly
+pub static OAUTH_PROVIDER: &str = "github";
 static DEVICE_CODE_URL: &str = "https://github.com/login/device/code";
 static POLL_URL: &str = "https://github.com/login/oauth/access_token";
 
@@ -36,8 +38,6 @@ struct DeviceCodeResponse {
 #[derive(Debug, Deserialize)]
 struct AccessTokenResponse {
     access_token: AccessToken,
-    token_type: String,
-    scope: Scope,
 }
 
 #[derive(Debug, Deserialize)]
@@ -73,35 +73,52 @@ pub enum Provider {
     OAuth,
 }
 
+/// Tsukimi session of the CLI. The provider token used to log in is dropped
+/// once traded, only the API's own pair is stored.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthSession {
     pub provider: Provider,
     pub access_token: AccessToken,
-    pub refresh_token: Option<RefreshToken>,
-    pub expires_at: Option<OffsetDateTime>,
-    pub scopes: Option<Vec<Scope>>,
+    pub refresh_token: RefreshToken,
 }
 
 impl AuthSession {
-    pub async fn fetch_user(&self) -> Result<UserInfo, ApiError> {
-        let client = reqwest::Client::new();
-        let response = client
-            .get("https://api.github.com/user")
-            .header(
-                header::AUTHORIZATION,
-                format!("Bearer {}", self.access_token.secret()),
-            )
-            .header(header::USER_AGENT, "MyRustApp") // GitHub exige un User-Agent
-            .send()
-            .await
-            .map_err(|e| ApiError::NetworkError(e.to_string()))?;
+    fn new(provider: Provider, session: SessionResponse) -> Self {
+        AuthSession {
+            provider,
+            access_token: session.access_token,
+            refresh_token: session.refresh_token,
+        }
+    }
 
-        let user_info: UserInfo = response
-            .json()
-            .await
-            .map_err(|e| ApiError::ParseError(e.to_string()))?;
+    /// Trades the refresh token for a new pair and stores it.
+    async fn refresh(&mut self, api: &ApiService) -> Result<(), CliError> {
+        info!("Access token refused, refreshing the session...");
+        let session = api.refresh_session(self.refresh_token.clone()).await?;
+        *self = AuthSession::new(self.provider.clone(), session);
+        store_token(self)?;
+        Ok(())
+    }
+
+    /// Runs an authenticated request, refreshing the session once if the API
+    /// refuses the access token.
+    pub async fn authorized<T>(
+        &mut self,
+        request: impl AsyncFn(&ApiService, &AccessToken) -> Result<T, ApiError>,
+    ) -> Result<T, CliError> {
+        let api = ApiService::default();
+        match request(&api, &self.access_token).await {
+            Err(ApiError::RequestError(StatusCode::UNAUTHORIZED, _)) => {
+                self.refresh(&api).await?;
+                Ok(request(&api, &self.access_token).await?)
+            }
+            result => Ok(result?),
+        }
+    }
 
-        Ok(user_info)
+    pub async fn fetch_user(&mut self) -> Result<User, CliError> {
+        self.authorized(async |api, access_token| api.fetch_me(access_token).await)
+            .await
     }
 }
 
@@ -122,9 +139,9 @@ impl Provider {
 
 pub async fn execute() -> CliResult {
     // Check if the user is already logged in
-    if let Ok(session) = read_token() {
-        let user_info = session.fetch_user().await?;
-        return Err(CliError::AlreadyLoggedIn(user_info));
+    if let Ok(mut session) = read_token() {
+        let user = session.fetch_user().await?;
+        return Err(CliError::AlreadyLoggedIn(user));
     }
 
     // Select the authentication method (Device Code or OAuth)
@@ -137,49 +154,32 @@ pub async fn execute() -> CliResult {
     .prompt()
     .map_err(|_| ApiError::AuthenticationError("Failed to select authentication method".into()))?;
 
-    let session = match auth_method {
+    let mut session = match auth_method {
         Provider::DeviceCode => device_flow_get_access_token().await?,
         Provider::OAuth => oauth2_get_access_token().await?,
     };
     println!("Authentification complete.");
-    info!("Receive access token: {}", session.access_token.secret());
 
     store_token(&session)?;
 
-    let user_info = session.fetch_user().await?;
-    println!("Connected as: {}", user_info.format());
+    let user = session.fetch_user().await?;
+    println!("Connected as: {}", user.username);
 
     Ok(())
 }
 
 async fn oauth2_get_access_token() -> Result<AuthSession, ApiError> {
-    let client = BasicClient::new(ClientId::new(CLIENT_ID.to_string()))
-        .set_auth_uri(AuthUrl::new("https://github.com/login/oauth/authorize".to_string()).unwrap())
-        .set_token_uri(TokenUrl::new(POLL_URL.to_string()).unwrap())
-        .set_redirect_uri(RedirectUrl::new("http://localhost:7777/callback".to_string()).unwrap());
-
-    let http_client = reqwest::ClientBuilder::new()
-        // Following redirects opens the client up to SSRF vulnerabilities.
-        .redirect(reqwest::redirect::Policy::none())
-        .build()
-        .expect("Client should build");
-
-    let (pkce_code_challenge, pkce_code_verifier) = PkceCodeChallenge::new_random_sha256();
-
-    let (authorize_url, csrf_state) = client
-        .authorize_url(CsrfToken::new_random)
-        .set_pkce_challenge(pkce_code_challenge)
-        // This example is requesting access to the user's public repos and email.
-        .add_scope(Scope::new("public_repo".to_string()))
-        .add_scope(Scope::new("user:email".to_string()))
-        .url();
-
-    if let Err(_) = open::that(authorize_url.to_string()) {
+    let api = ApiService::default();
+    // The API builds the URL and keeps the PKCE verifier
+    let authorization = api.oauth_authorize_url(OAUTH_PROVIDER).await?;
+    let authorize_url = authorization.authorize_url;
+
+    if let Err(_) = open::that(&authorize_url) {
         println!("Failed to open the browser. Please open the following URL manually:");
         println!("{}", authorize_url);
     }
 
-    let (code, _state) = {
+    let (code, state) = {
         let listener = TcpListener::bind("127.0.0.1:7777")
             .await
             .map_err(|e| ApiError::NetworkError(e.to_string()))?;
@@ -223,49 +223,35 @@ async fn oauth2_get_access_token() -> Result<AuthSession, ApiError> {
 
     info!("Received code: {}", code.secret());
 
-    let response = http_client
-        .post("http://localhost:3000/oauth/github/exchange-code")
-        .json(&tsukimi_core::auth::OauthExchangeCodeRequest {
-            code,
-            pkce_code_verifier,
-        })
-        .send()
-        .await
-        .map_err(|e| ApiError::NetworkError(e.to_string()))?;
-
-    if !response.status().is_success() {
-        return Err(ApiError::RequestError(
-            response.status(),
-            response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string()),
+    if state.secret() != authorization.state.secret() {
+        return Err(ApiError::AuthenticationError(
+            "OAuth state mismatch, the callback was not for this login".into(),
         ));
     }
 
-    let token_response: tsukimi_core::auth::OauthExchangeCodeResponse = response
-        .json()
-        .await
-        .map_err(|e| ApiError::ParseError(e.to_string()))?;
-
-    Ok(AuthSession {
-        provider: Provider::OAuth,
-        access_token: token_response.access_token,
-        refresh_token: token_response.refresh_token,
-        expires_at: token_response
-            .expires_in
-            .map(|it| OffsetDateTime::now_utc() + it),
-        scopes: token_response.scopes,
-    })
+    // The API exchanges the code and keeps the GitHub token to itself
+    let session = api.create_session(code, state).await?;
+
+    Ok(AuthSession::new(Provider::OAuth, session))
 }
 
 async fn device_flow_get_access_token() -> Result<AuthSession, ApiError> {
+    let api = ApiService::default();
+    let client_id = api
+        .oauth_providers()
+        .await?
+        .into_iter()
+        .find(|provider| provider.id == OAUTH_PROVIDER)
+        .map(|provider| provider.client_id)
+        .ok_or_else(|| {
+            ApiError::AuthenticationError(format!("The API has no {} login", OAUTH_PROVIDER))
+        })?;
     let client = reqwest::Client::new();
 
     let response = client
         .post(DEVICE_CODE_URL)
         .form(&[
-            ("client_id", CLIENT_ID),
+            ("client_id", client_id.as_str()),
             // ("scope", "read:user")
         ])
         .header("Accept", "application/json")
@@ -299,7 +285,7 @@ async fn device_flow_get_access_token() -> Result<AuthSession, ApiError> {
         let response = client
             .post(POLL_URL)
             .form(&[
-                ("client_id", CLIENT_ID),
+                ("client_id", client_id.as_str()),
                 ("device_code", &device_code_response.device_code),
                 ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
             ])
@@ -331,20 +317,11 @@ async fn device_flow_get_access_token() -> Result<AuthSession, ApiError> {
 
         match github_response {
             DafPollResponse::Success(token_res) => {
-                println!("Access Token: {}", token_res.access_token.secret());
-                return Ok(AuthSession {
-                    provider: Provider::DeviceCode,
-                    access_token: token_res.access_token,
-                    refresh_token: None,
-                    expires_at: None,
-                    scopes: Some(
-                        token_res
-                            .scope
-                            .split(' ')
-                            .map(|it| Scope::new(it.to_string()))
-                            .collect(),
-                    ),
-                });
+                // Only used to prove the account to the API, then dropped
+                let session = api
+                    .create_device_session(OAUTH_PROVIDER, token_res.access_token)
+                    .await?;
+                return Ok(AuthSession::new(Provider::DeviceCode, session));
             }
             DafPollResponse::Error(error_res) => match error_res.error {
                 ErrorType::AuthorizationPending => {
@@ -376,27 +353,6 @@ async fn device_flow_get_access_token() -> Result<AuthSession, ApiError> {
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct UserInfo {
-    pub login: String,
-    pub id: u64,
-    pub avatar_url: String,
-    pub html_url: String,
-    pub name: Option<String>,
-    pub email: Option<String>,
-}
-
-impl UserInfo {
-    pub fn format(&self) -> String {
-        let name = match &self.name {
-            Some(name) => name.clone(),
-            None => self.login.clone(),
-        };
-        let email = self.email.clone().unwrap_or_else(|| "No email".to_string());
-        format!("{} <{}>", name, email)
-    }
-}
-
 // pub async fn get_access_token() -> Result<String, ApiError> {
 //     let device_auth_url = DeviceAuthorizationUrl::new(DEVICE_CODE_URL.to_string())
 //         .expect("Invalid device authorization URL");
@@ -1,6 +1,20 @@
-use crate::{error::CliResult, services::credentials::delete_token};
+use log::warn;
+
+use crate::{
+    error::CliResult,
+    services::credentials::{delete_token, read_token},
+};
 
 pub async fn execute() -> CliResult {
+    // Forgetting the tokens locally is enough to log out, revoking the
+    // session only makes sure a copy of them is useless
+    if let Ok(mut session) = read_token()
+        && let Err(e) = session
+            .authorized(async |api, access_token| api.revoke_session(access_token).await)
+            .await
+    {
+        warn!("Failed to revoke the session: {}", e);
+    }
     let _ = delete_token()?;
     Ok(())
 }
@@ -11,15 +11,23 @@ pub struct SearchCommandParams {
 #[derive(Tabled)]
 struct ListItem {
     name: String,
-    version: Version,
+    version: String,
     description: String,
 }
 
 impl From<tsukimi_core::models::Engine> for ListItem {
     fn from(engine: tsukimi_core::models::Engine) -> Self {
+        let name = match engine.deprecated_at {
+            Some(_) => format!("{} (deprecated)", engine.name),
+            None => engine.name,
+        };
         Self {
-            name: engine.name,
-            version: engine.current_version,
+            name,
+            // Every version of the engine may be yanked
+            version: engine
+                .current_version
+                .as_ref()
+                .map_or_else(|| "-".to_string(), Version::to_string),
             description: engine.description,
         }
     }
@@ -2,12 +2,12 @@ use crate::{error::CliResult, services::credentials::read_token};
 use log::{error, info};
 
 pub async fn execute() -> CliResult {
-    let session = read_token().map_err(|e| {
+    let mut session = read_token().map_err(|e| {
         error!("Failed to read access token: {}", e);
         e
     })?;
-    let user_info = session.fetch_user().await?;
-    info!("User info: {:?}", user_info);
-    println!("Connected as: {}", user_info.format());
+    let user = session.fetch_user().await?;
+    info!("User info: {:?}", user);
+    println!("Connected as: {}", user.username);
     Ok(())
 }
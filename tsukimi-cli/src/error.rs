@@ -1,6 +1,8 @@
 use thiserror::Error;
 
-use crate::{api::ApiError, commands::login::UserInfo};
+use tsukimi_core::models::User;
+
+use crate::api::ApiError;
 
 #[derive(Error, Debug)]
 pub enum CliError {
@@ -11,8 +13,11 @@ pub enum CliError {
     #[error(transparent)]
     PluginError(#[from] crate::commands::list::PluginError),
 
-    #[error("You are already logged in as {}", .0.format())]
-    AlreadyLoggedIn(UserInfo),
+    #[error("Refusing to install `{0}`: {1}")]
+    UntrustedArtifact(String, String),
+
+    #[error("You are already logged in as {}", .0.username)]
+    AlreadyLoggedIn(User),
 }
 
 pub type CliResult = Result<(), CliError>;
@@ -1,7 +1,26 @@
+use oauth2::{AccessToken, AuthorizationCode, CsrfToken, RefreshToken};
+use tsukimi_core::{
+    auth::{
+        DeviceSessionRequest, IdentityProviderInfo, OauthAuthorizeUrlResponse,
+        OauthExchangeCodeRequest, RefreshSessionRequest, SessionResponse,
+    },
+    models::{EngineMember, PublisherKey, User, Version},
+};
+
 use crate::api::ApiError;
 
 // #[if]
 static BASE_URL: &str = "http://localhost:3000";
+static SIGNATURE_HEADER: &str = "x-signature";
+static PUBLISHER_KEY_HEADER: &str = "x-publisher-key";
+
+/// A downloaded release, not verified yet.
+pub struct DownloadedArtifact {
+    pub bytes: Vec<u8>,
+    /// Base64 encoded Ed25519 signature, `None` for unsigned releases
+    pub signature: Option<String>,
+    pub publisher_key_id: Option<String>,
+}
 
 pub struct ApiService {
     base_url: String,
@@ -69,4 +88,233 @@ impl ApiService {
             ))
         }
     }
+
+    pub async fn download_version(
+        &self,
+        engine: &tsukimi_core::models::Engine,
+        version: &Version,
+    ) -> Result<DownloadedArtifact, ApiError> {
+        let url = self.build_url(&format!(
+            "engines/{}/versions/{}/download",
+            engine.id, version
+        ));
+        let client = self.get_client();
+
+        let response = client.get(&url).send().await?;
+
+        if response.status().is_success() {
+            let header = |name: &str| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string)
+            };
+            let signature = header(SIGNATURE_HEADER);
+            let publisher_key_id = header(PUBLISHER_KEY_HEADER);
+            let bytes = response.bytes().await?.to_vec();
+            Ok(DownloadedArtifact {
+                bytes,
+                signature,
+                publisher_key_id,
+            })
+        } else {
+            Err(ApiError::NetworkError(
+                response.text().await.unwrap_or_default(),
+            ))
+        }
+    }
+
+    pub async fn fetch_engine_members(
+        &self,
+        engine: &tsukimi_core::models::Engine,
+    ) -> Result<Vec<EngineMember>, ApiError> {
+        let url = self.build_url(&format!("engines/{}/members", engine.id));
+        let client = self.get_client();
+
+        let response = client.get(&url).send().await?;
+
+        if response.status().is_success() {
+            let members: Vec<EngineMember> = response.json().await?;
+            Ok(members)
+        } else {
+            Err(ApiError::NetworkError(
+                response.text().await.unwrap_or_default(),
+            ))
+        }
+    }
+
+    pub async fn fetch_publisher_key(&self, key_id: &str) -> Result<PublisherKey, ApiError> {
+        let url = self.build_url(&format!("publisher-keys/{}", key_id));
+        let client = self.get_client();
+
+        let response = client.get(&url).send().await?;
+
+        if response.status().is_success() {
+            let key: PublisherKey = response.json().await?;
+            Ok(key)
+        } else {
+            Err(ApiError::NetworkError(
+                response.text().await.unwrap_or_default(),
+            ))
+        }
+    }
+
+    /// Starts an authorization with an identity provider of the API, the API
+    /// keeps the PKCE verifier.
+    pub async fn oauth_authorize_url(
+        &self,
+        provider: &str,
+    ) -> Result<OauthAuthorizeUrlResponse, ApiError> {
+        let url = self.build_url(&format!("oauth/{}/authorize-url", provider));
+        let client = self.get_client();
+
+        let response = client.get(&url).send().await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            Err(ApiError::RequestError(
+                response.status(),
+                response.text().await.unwrap_or_default(),
+            ))
+        }
+    }
+
+    /// Identity providers of the API, with the client id the device flow
+    /// needs.
+    pub async fn oauth_providers(&self) -> Result<Vec<IdentityProviderInfo>, ApiError> {
+        let url = self.build_url("oauth/providers");
+        let client = self.get_client();
+
+        let response = client.get(&url).send().await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            Err(ApiError::RequestError(
+                response.status(),
+                response.text().await.unwrap_or_default(),
+            ))
+        }
+    }
+
+    /// Completes an authorization started with `oauth_authorize_url` and opens
+    /// a Tsukimi session.
+    pub async fn create_session(
+        &self,
+        code: AuthorizationCode,
+        state: CsrfToken,
+    ) -> Result<SessionResponse, ApiError> {
+        let url = self.build_url("auth/session");
+        let client = self.get_client();
+
+        let response = client
+            .post(&url)
+            .json(&OauthExchangeCodeRequest { code, state })
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            Err(ApiError::RequestError(
+                response.status(),
+                response.text().await.unwrap_or_default(),
+            ))
+        }
+    }
+
+    /// Trades a token obtained through the device flow of the provider for a
+    /// Tsukimi session.
+    pub async fn create_device_session(
+        &self,
+        provider: &str,
+        access_token: AccessToken,
+    ) -> Result<SessionResponse, ApiError> {
+        let url = self.build_url("auth/device-session");
+        let client = self.get_client();
+
+        let response = client
+            .post(&url)
+            .json(&DeviceSessionRequest {
+                provider: provider.to_string(),
+                access_token,
+            })
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            Err(ApiError::RequestError(
+                response.status(),
+                response.text().await.unwrap_or_default(),
+            ))
+        }
+    }
+
+    /// Trades the refresh token for a new pair, the old one is spent.
+    pub async fn refresh_session(
+        &self,
+        refresh_token: RefreshToken,
+    ) -> Result<SessionResponse, ApiError> {
+        let url = self.build_url("auth/refresh");
+        let client = self.get_client();
+
+        let response = client
+            .post(&url)
+            .json(&RefreshSessionRequest { refresh_token })
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            Err(ApiError::RequestError(
+                response.status(),
+                response.text().await.unwrap_or_default(),
+            ))
+        }
+    }
+
+    pub async fn revoke_session(&self, access_token: &AccessToken) -> Result<(), ApiError> {
+        let url = self.build_url("auth/session");
+        let client = self.get_client();
+
+        let response = client
+            .delete(&url)
+            .bearer_auth(access_token.secret())
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(ApiError::RequestError(
+                response.status(),
+                response.text().await.unwrap_or_default(),
+            ))
+        }
+    }
+
+    pub async fn fetch_me(&self, access_token: &AccessToken) -> Result<User, ApiError> {
+        let url = self.build_url("auth/me");
+        let client = self.get_client();
+
+        let response = client
+            .get(&url)
+            .bearer_auth(access_token.secret())
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            Err(ApiError::RequestError(
+                response.status(),
+                response.text().await.unwrap_or_default(),
+            ))
+        }
+    }
 }
@@ -3,7 +3,7 @@ use log::info;
 use crate::commands::login::AuthSession;
 
 static SERVICE_NAME: &str = "tsukimi";
-static USERNAME: &str = "github_access_token";
+static USERNAME: &str = "session";
 
 #[derive(Debug, thiserror::Error)]
 pub enum CredentialsError {
@@ -1,3 +1,4 @@
 pub mod api;
 pub mod credentials;
 pub mod project_data;
+pub mod trust_store;
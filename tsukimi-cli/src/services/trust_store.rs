@@ -0,0 +1,60 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::project_data::get_project_data_folder;
+
+static TRUST_STORE_FILE: &str = "trusted_keys.json";
+
+/// Fingerprints of the publisher keys trusted for each engine. The registry
+/// serves the artifact, its signature and the key, so the key is pinned here
+/// on first install and a registry can't swap it later.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct TrustStore {
+    engines: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl TrustStore {
+    fn path() -> Result<PathBuf, String> {
+        get_project_data_folder()
+            .map(|folder| folder.join(TRUST_STORE_FILE))
+            .ok_or_else(|| "Could not locate the data directory".to_string())
+    }
+
+    /// An empty store if nothing was pinned yet.
+    pub fn load() -> Result<Self, String> {
+        let path = Self::path()?;
+        match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path()?;
+        if let Some(folder) = path.parent() {
+            std::fs::create_dir_all(folder)
+                .map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+        }
+        let json = serde_json::to_vec_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// `None` until a key was trusted for the engine.
+    pub fn trusted_keys(&self, engine_name: &str) -> Option<&BTreeSet<String>> {
+        self.engines.get(engine_name)
+    }
+
+    pub fn trust(&mut self, engine_name: &str, fingerprint: &str) {
+        self.engines
+            .entry(engine_name.to_string())
+            .or_default()
+            .insert(fingerprint.to_string());
+    }
+}
@@ -1,18 +1,217 @@
-use std::time::Duration;
+use std::{str::FromStr, time::Duration};
 
-use oauth2::{AccessToken, AuthorizationCode, PkceCodeVerifier, RefreshToken, Scope};
+use oauth2::{AccessToken, AuthorizationCode, CsrfToken, RefreshToken, Scope};
 use serde::{Deserialize, Serialize};
+use sqlx::{
+    Decode, Postgres, Type,
+    postgres::{PgHasArrayType, PgTypeInfo},
+};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Identity provider users can log in with, as configured on the API.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct IdentityProviderInfo {
+    /// Used in the `/oauth/{provider}` paths, e.g. `github`
+    pub id: String,
+    pub name: String,
+    /// Public client id, for clients running the device flow with the
+    /// provider directly
+    pub client_id: String,
+}
+
+/// Account of an identity provider linked to a Tsukimi user. A user can log
+/// in with any of their identities.
+#[derive(sqlx::FromRow, Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UserIdentity {
+    pub provider: String,
+    /// Stable id of the account at the provider
+    pub subject: String,
+    pub username: String,
+    pub email: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_login_at: Option<OffsetDateTime>,
+}
+
+/// Authorization started by the API. The PKCE verifier stays on the server,
+/// bound to `state` until the code is exchanged or the record expires.
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OauthAuthorizeUrlResponse {
+    pub authorize_url: String,
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub state: CsrfToken,
+    pub client_id: String,
+}
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct OauthExchangeCodeRequest {
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
     pub code: AuthorizationCode,
-    pub pkce_code_verifier: PkceCodeVerifier,
+    /// `state` returned along the authorization URL
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub state: CsrfToken,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct OauthExchangeCodeResponse {
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
     pub access_token: AccessToken,
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>))]
     pub refresh_token: Option<RefreshToken>,
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<crate::openapi::DurationSchema>))]
     pub expires_in: Option<Duration>,
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<Vec<String>>))]
     pub scopes: Option<Vec<Scope>>,
 }
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OauthRefreshRequest {
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub refresh_token: RefreshToken,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OauthRevokeRequest {
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub access_token: AccessToken,
+}
+
+/// Tokens issued by the Tsukimi API once the identity provider login succeeded.
+/// The access token is a short-lived signed token, the refresh token is opaque
+/// and can be exchanged exactly once against a new pair.
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SessionResponse {
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub access_token: AccessToken,
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub refresh_token: RefreshToken,
+    #[cfg_attr(feature = "openapi", schema(value_type = crate::openapi::DurationSchema))]
+    pub expires_in: Duration,
+}
+
+/// Access token a client obtained from the provider itself, through the
+/// device flow. The API only reads the account with it.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeviceSessionRequest {
+    /// Identity provider id
+    pub provider: String,
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub access_token: AccessToken,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RefreshSessionRequest {
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub refresh_token: RefreshToken,
+}
+
+/// Permission granted to a personal access token.
+/// Sessions opened through the login flow implicitly hold every scope.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum TokenScope {
+    #[serde(rename = "engines:publish")]
+    EnginesPublish,
+    #[serde(rename = "projects:write")]
+    ProjectsWrite,
+}
+
+impl TokenScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenScope::EnginesPublish => "engines:publish",
+            TokenScope::ProjectsWrite => "projects:write",
+        }
+    }
+}
+
+impl std::fmt::Display for TokenScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for TokenScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "engines:publish" => Ok(TokenScope::EnginesPublish),
+            "projects:write" => Ok(TokenScope::ProjectsWrite),
+            _ => Err(format!("Unknown token scope: {}", s)),
+        }
+    }
+}
+
+impl Type<Postgres> for TokenScope {
+    fn type_info() -> PgTypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <String as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl PgHasArrayType for TokenScope {
+    fn array_type_info() -> PgTypeInfo {
+        <String as PgHasArrayType>::array_type_info()
+    }
+}
+
+impl Decode<'_, Postgres> for TokenScope {
+    fn decode(
+        value: <Postgres as sqlx::Database>::ValueRef<'_>,
+    ) -> Result<Self, sqlx::error::BoxDynError> {
+        let s = <&str as Decode<Postgres>>::decode(value)?;
+        Ok(s.parse()?)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreatePersonalAccessTokenRequest {
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    /// Number of days the token stays valid, at most 365, `None` for a token
+    /// without expiry
+    pub expires_in_days: Option<u32>,
+}
+
+#[derive(sqlx::FromRow, Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PersonalAccessToken {
+    pub id: Uuid,
+    pub name: String,
+    /// First characters of the token, enough to recognise it in a list
+    pub token_prefix: String,
+    pub scopes: Vec<TokenScope>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_used_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Returned once at creation, the secret cannot be retrieved afterwards.
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreatedPersonalAccessToken {
+    #[serde(flatten)]
+    pub token: PersonalAccessToken,
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub secret: AccessToken,
+}
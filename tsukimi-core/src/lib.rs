@@ -1,2 +1,5 @@
 pub mod auth;
 pub mod models;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+pub mod signing;
@@ -2,14 +2,29 @@ use std::path::Display;
 
 use serde::{Deserialize, Serialize};
 use sqlx::{Decode, Postgres, Type};
+use time::{Date, OffsetDateTime};
 use uuid::Uuid;
 
 #[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Engine {
     pub id: Uuid,
     pub name: String,
     pub description: String,
-    pub current_version: Version,
+    /// Latest version that is not yanked, `None` once every version is
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>, example = "1.2.0"))]
+    pub current_version: Option<Version>,
+    pub tags: Vec<String>,
+    /// Source languages the extension can extract, BCP 47 tags
+    pub supported_languages: Vec<String>,
+    /// Version of the `tsukimi:extension` world the engine implements
+    pub wit_version: Option<String>,
+    pub downloads: i64,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub deprecated_at: Option<OffsetDateTime>,
+    pub deprecation_message: Option<String>,
+    /// Engine to use instead of a deprecated one
+    pub replacement_engine_id: Option<Uuid>,
     // pub created_at: String,
     // pub updated_at: String,
 }
@@ -21,9 +36,14 @@ pub struct Version {
     patch: u32,
 }
 
+/// Stored as text, e.g. `1.2.0`.
 impl Type<Postgres> for Version {
     fn type_info() -> sqlx::postgres::PgTypeInfo {
-        sqlx::postgres::PgTypeInfo::with_name("version")
+        <String as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        <String as Type<Postgres>>::compatible(ty)
     }
 }
 
@@ -83,33 +103,774 @@ impl<'de> Deserialize<'de> for Version {
         D: serde::Deserializer<'de>,
     {
         let version_str = String::deserialize(deserializer)?;
-        let parts: Vec<&str> = version_str.split('.').collect();
+        version_str.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl std::str::FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('.').collect();
         if parts.len() != 3 {
-            return Err(serde::de::Error::custom("Invalid version format"));
+            return Err("Invalid version format".into());
         }
-        let major = parts[0].parse().map_err(serde::de::Error::custom)?;
-        let minor = parts[1].parse().map_err(serde::de::Error::custom)?;
-        let patch = parts[2].parse().map_err(serde::de::Error::custom)?;
+        let parse = |part: &str| part.parse().map_err(|e| format!("Invalid version: {}", e));
         Ok(Version {
-            major,
-            minor,
-            patch,
+            major: parse(parts[0])?,
+            minor: parse(parts[1])?,
+            patch: parse(parts[2])?,
         })
     }
 }
 
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct EngineVersion {
-    pub id: String,
-    pub engine_id: String,
-    pub version: String,
-    pub description: String,
-    pub created_at: String,
+    pub id: Uuid,
+    pub engine_id: Uuid,
+    #[cfg_attr(feature = "openapi", schema(value_type = String, example = "1.2.0"))]
+    pub version: Version,
+    pub description: Option<String>,
+    /// Whether this is the engine's current version
+    pub is_active: bool,
+    pub artifact_size: Option<i64>,
+    pub artifact_sha256: Option<String>,
+    /// Yanked versions stay downloadable but are never resolved anew
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub yanked_at: Option<OffsetDateTime>,
+    pub yank_reason: Option<String>,
+    /// Base64 encoded Ed25519 signature of the artifact
+    pub signature: Option<String>,
+    /// Publisher key the artifact was signed with
+    pub publisher_key_id: Option<Uuid>,
+    /// Version of the `tsukimi:extension` world the artifact implements
+    pub wit_version: Option<String>,
+    /// Functions the artifact exports, e.g. `iadd.decode`
+    pub capabilities: Vec<String>,
+    /// Only valid versions are downloadable or become current
+    pub validation_status: ValidationStatus,
+    /// Why an invalid artifact was rejected
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<ComponentReport>))]
+    pub validation_report: Option<sqlx::types::Json<ComponentReport>>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Uploaded artifacts are checked by a background job.
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[sqlx(type_name = "validation_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ValidationStatus {
+    Pending,
+    Valid,
+    Invalid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct YankVersionRequest {
+    /// Shown to the users of the version, e.g. what is broken
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeprecateEngineRequest {
+    pub message: String,
+    pub replacement_engine_id: Option<Uuid>,
+}
+
+/// Outcome of checking an uploaded artifact against the `tsukimi:extension` world.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ComponentReport {
+    pub valid: bool,
+    pub wit_version: Option<String>,
+    /// Functions the component exports, e.g. `iadd.decode`
+    pub capabilities: Vec<String>,
+    /// Everything the component imports, allowed or not
+    pub imports: Vec<String>,
+    /// Why the component was rejected, empty when it is valid
+    pub errors: Vec<String>,
+}
+
+/// Ed25519 public key a user signs the artifacts they publish with.
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PublisherKey {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    /// Base64 encoded raw 32 bytes key
+    pub public_key: String,
+    /// Hex encoded SHA-256 of the raw key
+    pub fingerprint: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    /// Artifacts signed with a revoked key are refused by the CLI
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub revoked_at: Option<OffsetDateTime>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RegisterPublisherKeyRequest {
+    pub name: String,
+    /// Base64 encoded raw 32 bytes Ed25519 key
+    pub public_key: String,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct User {
-    pub id: String,
+    pub id: Uuid,
+    pub username: String,
+    pub email: Option<String>,
+    pub role: GlobalRole,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// Role of a user across the whole registry.
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[sqlx(type_name = "global_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum GlobalRole {
+    Admin,
+    User,
+}
+
+/// Role of a user on a single engine.
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[sqlx(type_name = "engine_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum EngineRole {
+    /// Publishes versions and manages the members
+    Owner,
+    /// Publishes versions
+    Maintainer,
+}
+
+/// Role of a user on a single translation project.
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[sqlx(type_name = "project_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectRole {
+    /// Manages the project settings and its members
+    Owner,
+    /// Submits translations
+    Translator,
+    /// Submits translations and approves the ones of others
+    Reviewer,
+    /// Read-only access, useful on private projects
+    Viewer,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct EngineMember {
+    pub user_id: Uuid,
     pub username: String,
-    pub email: String,
-    pub created_at: String,
-    pub updated_at: String,
+    pub role: EngineRole,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpdateEngineMemberRequest {
+    pub role: EngineRole,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpdateUserRoleRequest {
+    pub role: GlobalRole,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VisualNovel {
+    pub id: Uuid,
+    pub title: String,
+    pub original_title: Option<String>,
+    pub developer: Option<String>,
+    pub release_date: Option<Date>,
+    /// BCP 47 tag of the language the game was released in, e.g. `ja`
+    pub original_language: String,
+    pub engine_id: Option<Uuid>,
+    pub cover_image_url: Option<String>,
+    /// Identifier on vndb.org, e.g. `v17`
+    pub vndb_id: Option<String>,
+    /// Identifiers on other databases (Steam, ErogameScape...) keyed by site
+    #[cfg_attr(
+        feature = "openapi",
+        schema(value_type = std::collections::HashMap<String, String>)
+    )]
+    pub external_ids: sqlx::types::Json<std::collections::HashMap<String, String>>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// Payload used to create or replace a visual novel.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VisualNovelRequest {
+    pub title: String,
+    pub original_title: Option<String>,
+    pub developer: Option<String>,
+    pub release_date: Option<Date>,
+    pub original_language: String,
+    pub engine_id: Option<Uuid>,
+    pub cover_image_url: Option<String>,
+    pub vndb_id: Option<String>,
+    #[serde(default)]
+    pub external_ids: std::collections::HashMap<String, String>,
+}
+
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[sqlx(type_name = "project_visibility", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectVisibility {
+    /// Listed and readable by everyone, only members can contribute
+    Public,
+    /// Only visible to its members
+    Private,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Project {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub visual_novel_id: Uuid,
+    pub engine_version_id: Option<Uuid>,
+    pub source_language: String,
+    pub target_languages: Vec<String>,
+    pub visibility: ProjectVisibility,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub archived_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateProjectRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub visual_novel_id: Uuid,
+    pub engine_version_id: Option<Uuid>,
+    /// Defaults to the original language of the visual novel
+    pub source_language: Option<String>,
+    pub target_languages: Vec<String>,
+    pub visibility: ProjectVisibility,
+}
+
+/// Partial update, `None` fields are left untouched.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpdateProjectRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub engine_version_id: Option<Uuid>,
+    pub target_languages: Option<Vec<String>>,
+    pub visibility: Option<ProjectVisibility>,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ProjectMember {
+    pub user_id: Uuid,
+    pub username: String,
+    pub role: ProjectRole,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpdateProjectMemberRequest {
+    pub role: ProjectRole,
+}
+
+/// A unit of source text extracted from a game file, with its translation in
+/// the requested language if any.
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TranslationUnit {
+    pub id: Uuid,
+    pub file: String,
+    /// Identifies the unit inside its file, stable across extractions
+    pub key: String,
+    pub position: i32,
+    pub source_text: String,
+    pub context: Option<String>,
+    /// The unit disappeared from the latest extraction
+    pub obsolete: bool,
+    pub translation: Option<String>,
+    /// `0` when the unit has not been translated yet
+    pub revision: i32,
+    /// The source text changed since the translation was written
+    pub fuzzy: bool,
+    /// `None` when the unit has not been translated yet
+    pub review_state: Option<ReviewState>,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ProjectFile {
+    pub file: String,
+    pub units: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ExtractedUnit {
+    pub file: String,
+    pub key: String,
+    pub source_text: String,
+    pub context: Option<String>,
+}
+
+/// Result of an extraction run. Every file present in `units` is replaced:
+/// units missing from the upload are marked obsolete.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UploadExtractionRequest {
+    pub units: Vec<ExtractedUnit>,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UploadExtractionResponse {
+    pub inserted: i64,
+    pub updated: i64,
+    pub obsolete: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpdateTranslationRequest {
+    pub text: String,
+    /// Revision the edit is based on, `0` for a first translation.
+    /// The update is refused if someone saved another revision meanwhile.
+    pub revision: i32,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Translation {
+    pub unit_id: Uuid,
+    pub language: String,
+    pub text: String,
+    pub revision: i32,
+    pub fuzzy: bool,
+    pub review_state: ReviewState,
+    pub updated_by: Option<Uuid>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TranslationRevision {
+    pub revision: i32,
+    pub text: String,
+    pub author_id: Option<Uuid>,
+    pub author: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Review state of the current translation of a unit.
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[sqlx(type_name = "review_state", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewState {
+    /// Saved by a translator, not reviewed yet
+    Translated,
+    /// A reviewer asked for changes
+    NeedsWork,
+    Approved,
+}
+
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[sqlx(type_name = "suggestion_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Suggestion {
+    pub id: Uuid,
+    pub unit_id: Uuid,
+    pub language: String,
+    pub text: String,
+    /// Revision of the translation the suggestion was written against
+    pub base_revision: i32,
+    pub author_id: Option<Uuid>,
+    pub status: SuggestionStatus,
+    pub reviewer_id: Option<Uuid>,
+    pub review_comment: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub reviewed_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateSuggestionRequest {
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ReviewDecisionRequest {
+    pub comment: Option<String>,
+}
+
+/// Reviews the current translation directly, without a suggestion.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ReviewTranslationRequest {
+    pub state: ReviewState,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ReviewQueueItem {
+    pub unit_id: Uuid,
+    pub file: String,
+    pub key: String,
+    pub source_text: String,
+    pub translation: Option<String>,
+    pub revision: i32,
+    pub review_state: Option<ReviewState>,
+    pub pending_suggestions: i64,
+}
+
+/// Reason a thread was opened on a unit, lets translators find open questions.
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[sqlx(type_name = "issue_flag", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum IssueFlag {
+    NeedsContext,
+    SourceTypo,
+    GlossaryQuestion,
+    Other,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CommentThread {
+    pub id: Uuid,
+    pub unit_id: Uuid,
+    /// `None` when the thread is about the source text
+    pub language: Option<String>,
+    pub flag: Option<IssueFlag>,
+    pub created_by: Option<Uuid>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub resolved_by: Option<Uuid>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub resolved_at: Option<OffsetDateTime>,
+    #[sqlx(skip)]
+    pub comments: Vec<Comment>,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Comment {
+    pub id: Uuid,
+    pub thread_id: Uuid,
+    pub author_id: Option<Uuid>,
+    pub author: Option<String>,
+    pub body: String,
+    /// Usernames mentioned with `@username` that matched a user
+    pub mentions: Vec<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateThreadRequest {
+    pub body: String,
+    pub language: Option<String>,
+    pub flag: Option<IssueFlag>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateCommentRequest {
+    pub body: String,
+}
+
+/// Unit and word counts of a slice of a project in one language.
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Progress {
+    pub units: i64,
+    pub words: i64,
+    /// Translated and up to date with the source text
+    pub translated_units: i64,
+    pub translated_words: i64,
+    /// Approved by a reviewer and up to date with the source text
+    pub reviewed_units: i64,
+    pub reviewed_words: i64,
+    /// Translated but the source text changed since
+    pub fuzzy_units: i64,
+    pub fuzzy_words: i64,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LanguageProgress {
+    pub language: String,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub progress: Progress,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FileProgress {
+    pub file: String,
+    pub language: String,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub progress: Progress,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ContributorStats {
+    pub user_id: Uuid,
+    pub username: String,
+    pub language: String,
+    /// Translation revisions authored, approved suggestions included
+    pub translations: i64,
+    pub translated_words: i64,
+    /// Translations approved as a reviewer
+    pub approvals: i64,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ActivityPoint {
+    pub day: Date,
+    pub language: String,
+    pub translations: i64,
+    pub approvals: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ProjectStats {
+    pub languages: Vec<LanguageProgress>,
+    pub files: Vec<FileProgress>,
+    pub contributors: Vec<ContributorStats>,
+    pub activity: Vec<ActivityPoint>,
+}
+
+/// Events a webhook can subscribe to.
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[sqlx(type_name = "webhook_event", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    #[serde(rename = "engine.version_published")]
+    EngineVersionPublished,
+    #[serde(rename = "engine.version_yanked")]
+    EngineVersionYanked,
+    /// A target language crossed 25, 50, 75 or 100% of translated units
+    #[serde(rename = "project.milestone_reached")]
+    ProjectMilestoneReached,
+    #[serde(rename = "suggestion.approved")]
+    SuggestionApproved,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::EngineVersionPublished => "engine.version_published",
+            WebhookEvent::EngineVersionYanked => "engine.version_yanked",
+            WebhookEvent::ProjectMilestoneReached => "project.milestone_reached",
+            WebhookEvent::SuggestionApproved => "suggestion.approved",
+        }
+    }
+
+    /// Whether the event is raised by engines, the others by projects.
+    pub fn is_engine_event(&self) -> bool {
+        matches!(
+            self,
+            WebhookEvent::EngineVersionPublished | WebhookEvent::EngineVersionYanked
+        )
+    }
+}
+
+/// A webhook belongs to either an engine or a project and only receives the
+/// events of its owner.
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Webhook {
+    pub id: Uuid,
+    pub engine_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub active: bool,
+    pub created_by: Option<Uuid>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateWebhookRequest {
+    /// Exactly one of `engine_id` and `project_id` must be given
+    pub engine_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub events: Option<Vec<WebhookEvent>>,
+    pub active: Option<bool>,
+}
+
+/// Returned once at creation, the secret cannot be retrieved afterwards.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    /// Key of the HMAC-SHA256 signature sent with every delivery
+    pub secret: String,
+}
+
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[sqlx(type_name = "delivery_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    /// Waiting for its first attempt or a retry
+    Pending,
+    Succeeded,
+    /// Every attempt failed
+    Failed,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: WebhookEvent,
+    /// The body sent to the webhook
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub payload: sqlx::types::Json<serde_json::Value>,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub next_attempt_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_attempt_at: Option<OffsetDateTime>,
+    /// HTTP status of the last attempt, `None` if it got no response
+    pub response_status: Option<i32>,
+    /// Start of the last response body
+    pub response_body: Option<String>,
+    pub error: Option<String>,
+    /// The delivery this one was redelivered from
+    pub redelivery_of: Option<Uuid>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// One mutating request, as recorded in the append-only audit log.
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AuditEntry {
+    pub id: Uuid,
+    /// `None` for anonymous requests, such as logging in
+    pub actor_id: Option<Uuid>,
+    /// Personal access token the request was made with
+    pub token_id: Option<Uuid>,
+    /// Method and route template, e.g. `PATCH /projects/{id}`
+    pub action: String,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    /// Fields changed by the request, before and after it
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<Object>))]
+    pub before: Option<sqlx::types::Json<serde_json::Value>>,
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<Object>))]
+    pub after: Option<sqlx::types::Json<serde_json::Value>>,
+    pub ip: Option<String>,
+    pub status: i16,
+    #[serde(with = "time::serde::rfc3339")]
+    pub occurred_at: OffsetDateTime,
+}
+
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[sqlx(type_name = "job_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// Waiting for its first attempt, a retry or its scheduled time
+    Queued,
+    Running,
+    Succeeded,
+    /// Every attempt failed, kept until retried by an admin
+    Dead,
+}
+
+/// A job of the background queue.
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BackgroundJob {
+    pub id: Uuid,
+    /// e.g. `artifact.validate`
+    pub kind: String,
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub payload: sqlx::types::Json<serde_json::Value>,
+    pub status: JobStatus,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    /// Not run before this time
+    #[serde(with = "time::serde::rfc3339")]
+    pub run_at: OffsetDateTime,
+    /// Worker running the job
+    pub locked_by: Option<String>,
+    pub last_error: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub finished_at: Option<OffsetDateTime>,
 }
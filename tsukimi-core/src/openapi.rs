@@ -0,0 +1,29 @@
+//! OpenAPI schemas of the types serialized by hand.
+
+use utoipa::{
+    PartialSchema, ToSchema,
+    openapi::{ObjectBuilder, RefOr, Schema, Type},
+};
+
+use crate::models::Version;
+
+/// Serialized form of a `std::time::Duration`.
+#[allow(dead_code)]
+#[derive(ToSchema)]
+#[schema(as = Duration)]
+pub struct DurationSchema {
+    secs: u64,
+    nanos: u32,
+}
+
+impl PartialSchema for Version {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .pattern(Some(r"^\d+\.\d+\.\d+$"))
+            .examples([serde_json::json!("1.0.0")])
+            .into()
+    }
+}
+
+impl ToSchema for Version {}
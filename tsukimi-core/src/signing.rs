@@ -0,0 +1,176 @@
+//! Detached Ed25519 signatures of engine artifacts. The API checks them when a
+//! version is published and the CLI checks them again before installing it.
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use ring::{digest, signature};
+
+/// Length of a raw Ed25519 public key
+static PUBLIC_KEY_LENGTH: usize = 32;
+static SIGNATURE_LENGTH: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureError {
+    /// Not the base64 encoding of a raw 32 bytes Ed25519 key
+    InvalidPublicKey,
+    /// Not the base64 encoding of a 64 bytes Ed25519 signature
+    InvalidSignature,
+    /// Well formed, but not made over these bytes with this key
+    Mismatch,
+}
+
+impl std::fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureError::InvalidPublicKey => {
+                write!(
+                    f,
+                    "public key must be a base64 encoded 32 bytes Ed25519 key"
+                )
+            }
+            SignatureError::InvalidSignature => {
+                write!(
+                    f,
+                    "signature must be a base64 encoded 64 bytes Ed25519 signature"
+                )
+            }
+            SignatureError::Mismatch => write!(f, "signature does not match the artifact"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// Decodes a base64 public key into its raw bytes.
+pub fn decode_public_key(public_key: &str) -> Result<Vec<u8>, SignatureError> {
+    STANDARD
+        .decode(public_key.trim())
+        .ok()
+        .filter(|bytes| bytes.len() == PUBLIC_KEY_LENGTH)
+        .ok_or(SignatureError::InvalidPublicKey)
+}
+
+/// Hex encoded SHA-256 of the raw public key, how keys are told apart.
+pub fn fingerprint(public_key: &[u8]) -> String {
+    digest::digest(&digest::SHA256, public_key)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Checks that `signature` was made over the whole artifact with the private
+/// half of `public_key`, both base64 encoded.
+pub fn verify_artifact(
+    public_key: &str,
+    signature: &str,
+    artifact: &[u8],
+) -> Result<(), SignatureError> {
+    let public_key = decode_public_key(public_key)?;
+    let signature = STANDARD
+        .decode(signature.trim())
+        .ok()
+        .filter(|bytes| bytes.len() == SIGNATURE_LENGTH)
+        .ok_or(SignatureError::InvalidSignature)?;
+    signature::UnparsedPublicKey::new(&signature::ED25519, &public_key)
+        .verify(artifact, &signature)
+        .map_err(|_| SignatureError::Mismatch)
+}
+
+#[cfg(test)]
+mod tests {
+    use ring::{
+        rand::SystemRandom,
+        signature::{Ed25519KeyPair, KeyPair},
+    };
+
+    use super::*;
+
+    static ARTIFACT: &[u8] = b"\0asm\x01\0\0\0 engine";
+
+    /// A fresh key pair, its base64 public key and its signature of `ARTIFACT`.
+    fn signed() -> (Ed25519KeyPair, String, String) {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let public_key = STANDARD.encode(key_pair.public_key().as_ref());
+        let signature = STANDARD.encode(key_pair.sign(ARTIFACT).as_ref());
+        (key_pair, public_key, signature)
+    }
+
+    #[test]
+    fn signed_artifacts_are_accepted() {
+        let (_, public_key, signature) = signed();
+        assert_eq!(verify_artifact(&public_key, &signature, ARTIFACT), Ok(()));
+        // Pasted keys and signatures often end with a newline
+        let public_key = format!("{}\n", public_key);
+        let signature = format!(" {}\n", signature);
+        assert_eq!(verify_artifact(&public_key, &signature, ARTIFACT), Ok(()));
+    }
+
+    #[test]
+    fn tampered_artifacts_are_refused() {
+        let (_, public_key, signature) = signed();
+        let mut artifact = ARTIFACT.to_vec();
+        artifact[0] ^= 1;
+        assert_eq!(
+            verify_artifact(&public_key, &signature, &artifact),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify_artifact(&public_key, &signature, &ARTIFACT[..ARTIFACT.len() - 1]),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn tampered_signatures_are_refused() {
+        let (_, public_key, signature) = signed();
+        let mut bytes = STANDARD.decode(&signature).unwrap();
+        bytes[10] ^= 0x80;
+        assert_eq!(
+            verify_artifact(&public_key, &STANDARD.encode(&bytes), ARTIFACT),
+            Err(SignatureError::Mismatch)
+        );
+
+        let (_, _, other_signature) = signed();
+        assert_eq!(
+            verify_artifact(&public_key, &other_signature, ARTIFACT),
+            Err(SignatureError::Mismatch)
+        );
+
+        bytes.pop();
+        for signature in [
+            STANDARD.encode(&bytes),
+            "not base64!".to_string(),
+            String::new(),
+        ] {
+            assert_eq!(
+                verify_artifact(&public_key, &signature, ARTIFACT),
+                Err(SignatureError::InvalidSignature)
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_public_keys_are_refused() {
+        let (key_pair, _, signature) = signed();
+        let public_key = key_pair.public_key().as_ref();
+        for key in [
+            STANDARD.encode(&public_key[..31]),
+            STANDARD.encode([public_key, &[0]].concat()),
+            "not base64!".to_string(),
+        ] {
+            assert_eq!(
+                verify_artifact(&key, &signature, ARTIFACT),
+                Err(SignatureError::InvalidPublicKey)
+            );
+        }
+    }
+
+    #[test]
+    fn fingerprints_are_the_hex_sha256_of_the_key() {
+        assert_eq!(
+            fingerprint(&[0; 32]),
+            "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925"
+        );
+    }
+}
@@ -0,0 +1,130 @@
+// Generates `src/lib/types/api.d.ts` from the `openapi.json` the API tests
+// keep up to date. `--check` fails instead of writing when the file is stale.
+import { readFileSync, writeFileSync } from 'node:fs';
+
+const documentPath = new URL('../openapi.json', import.meta.url);
+const typesPath = new URL('../src/lib/types/api.d.ts', import.meta.url);
+
+const document = JSON.parse(readFileSync(documentPath, 'utf8'));
+
+const indent = (depth) => '\t'.repeat(depth);
+
+const comment = (description, depth) => {
+	if (!description) return '';
+	const lines = description.split('\n').map((line) => `${indent(depth)} * ${line}`.trimEnd());
+	return `${indent(depth)}/**\n${lines.join('\n')}\n${indent(depth)} */\n`;
+};
+
+const union = (types) => [...new Set(types)].join(' | ');
+
+function type(schema, depth) {
+	if (schema.$ref) {
+		return `components['schemas']['${schema.$ref.split('/').pop()}']`;
+	}
+	if (schema.enum) {
+		return union(schema.enum.map((value) => JSON.stringify(value)));
+	}
+	if (schema.oneOf || schema.anyOf) {
+		return union((schema.oneOf ?? schema.anyOf).map((variant) => type(variant, depth)));
+	}
+	if (schema.allOf) {
+		return schema.allOf.map((part) => type(part, depth)).join(' & ');
+	}
+	if (Array.isArray(schema.type)) {
+		return union(schema.type.map((single) => type({ ...schema, type: single }, depth)));
+	}
+	switch (schema.type) {
+		case 'string':
+			return 'string';
+		case 'integer':
+		case 'number':
+			return 'number';
+		case 'boolean':
+			return 'boolean';
+		case 'null':
+			return 'null';
+		case 'array': {
+			const items = type(schema.items ?? {}, depth);
+			return /[ |&]/.test(items) ? `(${items})[]` : `${items}[]`;
+		}
+		case 'object':
+			return object(schema, depth);
+		default:
+			return 'unknown';
+	}
+}
+
+function object(schema, depth) {
+	const required = new Set(schema.required ?? []);
+	const members = Object.entries(schema.properties ?? {}).map(
+		([name, property]) =>
+			comment(property.description, depth + 1) +
+			`${indent(depth + 1)}${name}${required.has(name) ? '' : '?'}: ${type(property, depth + 1)};`
+	);
+	if (schema.additionalProperties) {
+		const values =
+			schema.additionalProperties === true
+				? 'unknown'
+				: type(schema.additionalProperties, depth + 1);
+		members.push(`${indent(depth + 1)}[key: string]: ${values};`);
+	}
+	if (members.length === 0) return 'Record<string, unknown>';
+	return `{\n${members.join('\n')}\n${indent(depth)}}`;
+}
+
+// Query parameters of each operation, e.g. the filters of `GET /engines`
+function operations() {
+	const members = [];
+	for (const methods of Object.values(document.paths)) {
+		for (const operation of Object.values(methods)) {
+			const query = (operation.parameters ?? []).filter((parameter) => parameter.in === 'query');
+			if (!operation.operationId || query.length === 0) continue;
+			const properties = Object.fromEntries(
+				query.map((parameter) => [
+					parameter.name,
+					{ description: parameter.description, ...parameter.schema }
+				])
+			);
+			const required = query.filter((parameter) => parameter.required).map(({ name }) => name);
+			members.push(
+				comment(operation.summary, 1) +
+					`${indent(1)}${operation.operationId}: {\n` +
+					`${indent(2)}query: ${object({ properties, required }, 2)};\n` +
+					`${indent(1)}};`
+			);
+		}
+	}
+	return members.join('\n');
+}
+
+const schemas = Object.entries(document.components.schemas).map(
+	([name, schema]) => comment(schema.description, 2) + `${indent(2)}${name}: ${type(schema, 2)};`
+);
+
+const output = `// Generated by \`npm run types:api\` from openapi.json, do not edit.
+
+export interface components {
+\tschemas: {
+${schemas.join('\n')}
+\t};
+}
+
+export interface operations {
+${operations()}
+}
+`;
+
+if (process.argv.includes('--check')) {
+	let current = '';
+	try {
+		current = readFileSync(typesPath, 'utf8');
+	} catch {
+		// Reported as stale below
+	}
+	if (current !== output) {
+		console.error('src/lib/types/api.d.ts is stale, run `npm run types:api`');
+		process.exit(1);
+	}
+} else {
+	writeFileSync(typesPath, output);
+}
@@ -0,0 +1,1002 @@
+// Generated by `npm run types:api` from openapi.json, do not edit.
+
+export interface components {
+	schemas: {
+		ActivityPoint: {
+			approvals: number;
+			day: string;
+			language: string;
+			translations: number;
+		};
+		/**
+		 * One mutating request, as recorded in the append-only audit log.
+		 */
+		AuditEntry: {
+			/**
+			 * Method and route template, e.g. `PATCH /projects/{id}`
+			 */
+			action: string;
+			/**
+			 * `None` for anonymous requests, such as logging in
+			 */
+			actor_id?: string | null;
+			after?: Record<string, unknown> | null;
+			/**
+			 * Fields changed by the request, before and after it
+			 */
+			before?: Record<string, unknown> | null;
+			id: string;
+			ip?: string | null;
+			occurred_at: string;
+			status: number;
+			target_id?: string | null;
+			target_type?: string | null;
+			/**
+			 * Personal access token the request was made with
+			 */
+			token_id?: string | null;
+		};
+		/**
+		 * A job of the background queue.
+		 */
+		BackgroundJob: {
+			attempts: number;
+			created_at: string;
+			finished_at?: string | null;
+			id: string;
+			/**
+			 * e.g. `artifact.validate`
+			 */
+			kind: string;
+			last_error?: string | null;
+			/**
+			 * Worker running the job
+			 */
+			locked_by?: string | null;
+			max_attempts: number;
+			payload: Record<string, unknown>;
+			/**
+			 * Not run before this time
+			 */
+			run_at: string;
+			status: components['schemas']['JobStatus'];
+		};
+		Check: {
+			error?: string | null;
+			ok: boolean;
+		};
+		Comment: {
+			author?: string | null;
+			author_id?: string | null;
+			body: string;
+			created_at: string;
+			id: string;
+			/**
+			 * Usernames mentioned with `@username` that matched a user
+			 */
+			mentions: string[];
+			thread_id: string;
+		};
+		CommentThread: {
+			comments: components['schemas']['Comment'][];
+			created_at: string;
+			created_by?: string | null;
+			flag?: null | components['schemas']['IssueFlag'];
+			id: string;
+			/**
+			 * `None` when the thread is about the source text
+			 */
+			language?: string | null;
+			resolved_at?: string | null;
+			resolved_by?: string | null;
+			unit_id: string;
+		};
+		/**
+		 * Outcome of checking an uploaded artifact against the `tsukimi:extension` world.
+		 */
+		ComponentReport: {
+			/**
+			 * Functions the component exports, e.g. `iadd.decode`
+			 */
+			capabilities: string[];
+			/**
+			 * Why the component was rejected, empty when it is valid
+			 */
+			errors: string[];
+			/**
+			 * Everything the component imports, allowed or not
+			 */
+			imports: string[];
+			valid: boolean;
+			wit_version?: string | null;
+		};
+		ContributorStats: {
+			/**
+			 * Translations approved as a reviewer
+			 */
+			approvals: number;
+			language: string;
+			translated_words: number;
+			/**
+			 * Translation revisions authored, approved suggestions included
+			 */
+			translations: number;
+			user_id: string;
+			username: string;
+		};
+		CreateCommentRequest: {
+			body: string;
+		};
+		CreatePersonalAccessTokenRequest: {
+			/**
+			 * Number of days the token stays valid, at most 365, `None` for a token
+			 * without expiry
+			 */
+			expires_in_days?: number | null;
+			name: string;
+			scopes: components['schemas']['TokenScope'][];
+		};
+		CreateProjectRequest: {
+			description?: string | null;
+			engine_version_id?: string | null;
+			name: string;
+			/**
+			 * Defaults to the original language of the visual novel
+			 */
+			source_language?: string | null;
+			target_languages: string[];
+			visibility: components['schemas']['ProjectVisibility'];
+			visual_novel_id: string;
+		};
+		CreateSuggestionRequest: {
+			text: string;
+		};
+		CreateThreadRequest: {
+			body: string;
+			flag?: null | components['schemas']['IssueFlag'];
+			language?: string | null;
+		};
+		CreateWebhookRequest: {
+			/**
+			 * Exactly one of `engine_id` and `project_id` must be given
+			 */
+			engine_id?: string | null;
+			events: components['schemas']['WebhookEvent'][];
+			project_id?: string | null;
+			url: string;
+		};
+		/**
+		 * Returned once at creation, the secret cannot be retrieved afterwards.
+		 */
+		CreatedPersonalAccessToken: components['schemas']['PersonalAccessToken'] & {
+			secret: string;
+		};
+		/**
+		 * Returned once at creation, the secret cannot be retrieved afterwards.
+		 */
+		CreatedWebhook: components['schemas']['Webhook'] & {
+			/**
+			 * Key of the HMAC-SHA256 signature sent with every delivery
+			 */
+			secret: string;
+		};
+		DeliveryStatus: "pending" | "succeeded" | "failed";
+		DeprecateEngineRequest: {
+			message: string;
+			replacement_engine_id?: string | null;
+		};
+		/**
+		 * Access token a client obtained from the provider itself, through the
+		 * device flow. The API only reads the account with it.
+		 */
+		DeviceSessionRequest: {
+			access_token: string;
+			/**
+			 * Identity provider id
+			 */
+			provider: string;
+		};
+		/**
+		 * Serialized form of a `std::time::Duration`.
+		 */
+		Duration: {
+			nanos: number;
+			secs: number;
+		};
+		Engine: {
+			/**
+			 * Latest version that is not yanked, `None` once every version is
+			 */
+			current_version?: string | null;
+			deprecated_at?: string | null;
+			deprecation_message?: string | null;
+			description: string;
+			downloads: number;
+			id: string;
+			name: string;
+			/**
+			 * Engine to use instead of a deprecated one
+			 */
+			replacement_engine_id?: string | null;
+			/**
+			 * Source languages the extension can extract, BCP 47 tags
+			 */
+			supported_languages: string[];
+			tags: string[];
+			/**
+			 * Version of the `tsukimi:extension` world the engine implements
+			 */
+			wit_version?: string | null;
+		};
+		EngineMember: {
+			created_at: string;
+			role: components['schemas']['EngineRole'];
+			user_id: string;
+			username: string;
+		};
+		/**
+		 * Role of a user on a single engine.
+		 */
+		EngineRole: "owner" | "maintainer";
+		EngineSort: "relevance" | "name" | "downloads" | "recently_updated";
+		EngineVersion: {
+			artifact_sha256?: string | null;
+			artifact_size?: number | null;
+			/**
+			 * Functions the artifact exports, e.g. `iadd.decode`
+			 */
+			capabilities: string[];
+			created_at: string;
+			description?: string | null;
+			engine_id: string;
+			id: string;
+			/**
+			 * Whether this is the engine's current version
+			 */
+			is_active: boolean;
+			/**
+			 * Publisher key the artifact was signed with
+			 */
+			publisher_key_id?: string | null;
+			/**
+			 * Base64 encoded Ed25519 signature of the artifact
+			 */
+			signature?: string | null;
+			validation_report?: null | components['schemas']['ComponentReport'];
+			/**
+			 * Only valid versions are downloadable or become current
+			 */
+			validation_status: components['schemas']['ValidationStatus'];
+			version: string;
+			/**
+			 * Version of the `tsukimi:extension` world the artifact implements
+			 */
+			wit_version?: string | null;
+			yank_reason?: string | null;
+			/**
+			 * Yanked versions stay downloadable but are never resolved anew
+			 */
+			yanked_at?: string | null;
+		};
+		/**
+		 * Body of every error response.
+		 */
+		ErrorBody: {
+			error: string;
+		};
+		ExtractedUnit: {
+			context?: string | null;
+			file: string;
+			key: string;
+			source_text: string;
+		};
+		FileProgress: components['schemas']['Progress'] & {
+			file: string;
+			language: string;
+		};
+		/**
+		 * Role of a user across the whole registry.
+		 */
+		GlobalRole: "admin" | "user";
+		/**
+		 * Identity provider users can log in with, as configured on the API.
+		 */
+		IdentityProviderInfo: {
+			/**
+			 * Public client id, for clients running the device flow with the
+			 * provider directly
+			 */
+			client_id: string;
+			/**
+			 * Used in the `/oauth/{provider}` paths, e.g. `github`
+			 */
+			id: string;
+			name: string;
+		};
+		/**
+		 * Reason a thread was opened on a unit, lets translators find open questions.
+		 */
+		IssueFlag: "needs_context" | "source_typo" | "glossary_question" | "other";
+		JobStatus: "queued" | "running" | "succeeded" | "dead";
+		LanguageProgress: components['schemas']['Progress'] & {
+			language: string;
+		};
+		/**
+		 * Authorization started by the API. The PKCE verifier stays on the server,
+		 * bound to `state` until the code is exchanged or the record expires.
+		 */
+		OauthAuthorizeUrlResponse: {
+			authorize_url: string;
+			client_id: string;
+			state: string;
+		};
+		OauthExchangeCodeRequest: {
+			code: string;
+			/**
+			 * `state` returned along the authorization URL
+			 */
+			state: string;
+		};
+		OauthExchangeCodeResponse: {
+			access_token: string;
+			expires_in?: null | components['schemas']['Duration'];
+			refresh_token?: string | null;
+			scopes?: string[] | null;
+		};
+		OauthRefreshRequest: {
+			refresh_token: string;
+		};
+		OauthRevokeRequest: {
+			access_token: string;
+		};
+		PersonalAccessToken: {
+			created_at: string;
+			expires_at?: string | null;
+			id: string;
+			last_used_at?: string | null;
+			name: string;
+			scopes: components['schemas']['TokenScope'][];
+			/**
+			 * First characters of the token, enough to recognise it in a list
+			 */
+			token_prefix: string;
+		};
+		/**
+		 * Unit and word counts of a slice of a project in one language.
+		 */
+		Progress: {
+			/**
+			 * Translated but the source text changed since
+			 */
+			fuzzy_units: number;
+			fuzzy_words: number;
+			/**
+			 * Approved by a reviewer and up to date with the source text
+			 */
+			reviewed_units: number;
+			reviewed_words: number;
+			/**
+			 * Translated and up to date with the source text
+			 */
+			translated_units: number;
+			translated_words: number;
+			units: number;
+			words: number;
+		};
+		Project: {
+			archived_at?: string | null;
+			created_at: string;
+			description?: string | null;
+			engine_version_id?: string | null;
+			id: string;
+			name: string;
+			source_language: string;
+			target_languages: string[];
+			updated_at: string;
+			visibility: components['schemas']['ProjectVisibility'];
+			visual_novel_id: string;
+		};
+		ProjectFile: {
+			file: string;
+			units: number;
+		};
+		ProjectMember: {
+			created_at: string;
+			role: components['schemas']['ProjectRole'];
+			user_id: string;
+			username: string;
+		};
+		/**
+		 * Role of a user on a single translation project.
+		 */
+		ProjectRole: "owner" | "translator" | "reviewer" | "viewer";
+		ProjectStats: {
+			activity: components['schemas']['ActivityPoint'][];
+			contributors: components['schemas']['ContributorStats'][];
+			files: components['schemas']['FileProgress'][];
+			languages: components['schemas']['LanguageProgress'][];
+		};
+		ProjectVisibility: "public" | "private";
+		/**
+		 * Ed25519 public key a user signs the artifacts they publish with.
+		 */
+		PublisherKey: {
+			created_at: string;
+			/**
+			 * Hex encoded SHA-256 of the raw key
+			 */
+			fingerprint: string;
+			id: string;
+			name: string;
+			/**
+			 * Base64 encoded raw 32 bytes key
+			 */
+			public_key: string;
+			/**
+			 * Artifacts signed with a revoked key are refused by the CLI
+			 */
+			revoked_at?: string | null;
+			user_id: string;
+		};
+		Readiness: {
+			checks: {
+				[key: string]: components['schemas']['Check'];
+			};
+			ready: boolean;
+		};
+		RefreshSessionRequest: {
+			refresh_token: string;
+		};
+		RegisterPublisherKeyRequest: {
+			name: string;
+			/**
+			 * Base64 encoded raw 32 bytes Ed25519 key
+			 */
+			public_key: string;
+		};
+		ReviewDecisionRequest: {
+			comment?: string | null;
+		};
+		ReviewQueueItem: {
+			file: string;
+			key: string;
+			pending_suggestions: number;
+			review_state?: null | components['schemas']['ReviewState'];
+			revision: number;
+			source_text: string;
+			translation?: string | null;
+			unit_id: string;
+		};
+		/**
+		 * Review state of the current translation of a unit.
+		 */
+		ReviewState: "translated" | "needs_work" | "approved";
+		/**
+		 * Reviews the current translation directly, without a suggestion.
+		 */
+		ReviewTranslationRequest: {
+			state: components['schemas']['ReviewState'];
+		};
+		/**
+		 * Tokens issued by the Tsukimi API once the identity provider login succeeded.
+		 * The access token is a short-lived signed token, the refresh token is opaque
+		 * and can be exchanged exactly once against a new pair.
+		 */
+		SessionResponse: {
+			access_token: string;
+			expires_in: components['schemas']['Duration'];
+			refresh_token: string;
+		};
+		Suggestion: {
+			author_id?: string | null;
+			/**
+			 * Revision of the translation the suggestion was written against
+			 */
+			base_revision: number;
+			created_at: string;
+			id: string;
+			language: string;
+			review_comment?: string | null;
+			reviewed_at?: string | null;
+			reviewer_id?: string | null;
+			status: components['schemas']['SuggestionStatus'];
+			text: string;
+			unit_id: string;
+		};
+		SuggestionStatus: "pending" | "approved" | "rejected";
+		/**
+		 * Permission granted to a personal access token.
+		 * Sessions opened through the login flow implicitly hold every scope.
+		 */
+		TokenScope: "engines:publish" | "projects:write";
+		Translation: {
+			fuzzy: boolean;
+			language: string;
+			review_state: components['schemas']['ReviewState'];
+			revision: number;
+			text: string;
+			unit_id: string;
+			updated_at: string;
+			updated_by?: string | null;
+		};
+		TranslationRevision: {
+			author?: string | null;
+			author_id?: string | null;
+			created_at: string;
+			revision: number;
+			text: string;
+		};
+		/**
+		 * A unit of source text extracted from a game file, with its translation in
+		 * the requested language if any.
+		 */
+		TranslationUnit: {
+			context?: string | null;
+			file: string;
+			/**
+			 * The source text changed since the translation was written
+			 */
+			fuzzy: boolean;
+			id: string;
+			/**
+			 * Identifies the unit inside its file, stable across extractions
+			 */
+			key: string;
+			/**
+			 * The unit disappeared from the latest extraction
+			 */
+			obsolete: boolean;
+			position: number;
+			review_state?: null | components['schemas']['ReviewState'];
+			/**
+			 * `0` when the unit has not been translated yet
+			 */
+			revision: number;
+			source_text: string;
+			translation?: string | null;
+		};
+		UpdateEngineMemberRequest: {
+			role: components['schemas']['EngineRole'];
+		};
+		UpdateProjectMemberRequest: {
+			role: components['schemas']['ProjectRole'];
+		};
+		/**
+		 * Partial update, `None` fields are left untouched.
+		 */
+		UpdateProjectRequest: {
+			description?: string | null;
+			engine_version_id?: string | null;
+			name?: string | null;
+			target_languages?: string[] | null;
+			visibility?: null | components['schemas']['ProjectVisibility'];
+		};
+		UpdateTranslationRequest: {
+			/**
+			 * Revision the edit is based on, `0` for a first translation.
+			 * The update is refused if someone saved another revision meanwhile.
+			 */
+			revision: number;
+			text: string;
+		};
+		UpdateUserRoleRequest: {
+			role: components['schemas']['GlobalRole'];
+		};
+		UpdateWebhookRequest: {
+			active?: boolean | null;
+			events?: components['schemas']['WebhookEvent'][] | null;
+			url?: string | null;
+		};
+		/**
+		 * Result of an extraction run. Every file present in `units` is replaced:
+		 * units missing from the upload are marked obsolete.
+		 */
+		UploadExtractionRequest: {
+			units: components['schemas']['ExtractedUnit'][];
+		};
+		UploadExtractionResponse: {
+			inserted: number;
+			obsolete: number;
+			updated: number;
+		};
+		User: {
+			created_at: string;
+			email?: string | null;
+			id: string;
+			role: components['schemas']['GlobalRole'];
+			updated_at: string;
+			username: string;
+		};
+		/**
+		 * Account of an identity provider linked to a Tsukimi user. A user can log
+		 * in with any of their identities.
+		 */
+		UserIdentity: {
+			created_at: string;
+			email?: string | null;
+			last_login_at?: string | null;
+			provider: string;
+			/**
+			 * Stable id of the account at the provider
+			 */
+			subject: string;
+			username: string;
+		};
+		/**
+		 * Uploaded artifacts are checked by a background job.
+		 */
+		ValidationStatus: "pending" | "valid" | "invalid";
+		Version: {
+			built_at?: string | null;
+			commit: string;
+			features: string[];
+			name: string;
+			profile: string;
+			version: string;
+		};
+		VisualNovel: {
+			cover_image_url?: string | null;
+			created_at: string;
+			developer?: string | null;
+			engine_id?: string | null;
+			/**
+			 * Identifiers on other databases (Steam, ErogameScape...) keyed by site
+			 */
+			external_ids: {
+				[key: string]: string;
+			};
+			id: string;
+			/**
+			 * BCP 47 tag of the language the game was released in, e.g. `ja`
+			 */
+			original_language: string;
+			original_title?: string | null;
+			release_date?: string | null;
+			title: string;
+			updated_at: string;
+			/**
+			 * Identifier on vndb.org, e.g. `v17`
+			 */
+			vndb_id?: string | null;
+		};
+		/**
+		 * Payload used to create or replace a visual novel.
+		 */
+		VisualNovelRequest: {
+			cover_image_url?: string | null;
+			developer?: string | null;
+			engine_id?: string | null;
+			external_ids?: {
+				[key: string]: string;
+			};
+			original_language: string;
+			original_title?: string | null;
+			release_date?: string | null;
+			title: string;
+			vndb_id?: string | null;
+		};
+		/**
+		 * A webhook belongs to either an engine or a project and only receives the
+		 * events of its owner.
+		 */
+		Webhook: {
+			active: boolean;
+			created_at: string;
+			created_by?: string | null;
+			engine_id?: string | null;
+			events: components['schemas']['WebhookEvent'][];
+			id: string;
+			project_id?: string | null;
+			updated_at: string;
+			url: string;
+		};
+		WebhookDelivery: {
+			attempts: number;
+			created_at: string;
+			error?: string | null;
+			event: components['schemas']['WebhookEvent'];
+			id: string;
+			last_attempt_at?: string | null;
+			next_attempt_at?: string | null;
+			/**
+			 * The body sent to the webhook
+			 */
+			payload: Record<string, unknown>;
+			/**
+			 * The delivery this one was redelivered from
+			 */
+			redelivery_of?: string | null;
+			/**
+			 * Start of the last response body
+			 */
+			response_body?: string | null;
+			/**
+			 * HTTP status of the last attempt, `None` if it got no response
+			 */
+			response_status?: number | null;
+			status: components['schemas']['DeliveryStatus'];
+			webhook_id: string;
+		};
+		/**
+		 * Events a webhook can subscribe to.
+		 */
+		WebhookEvent: "engine.version_published" | "engine.version_yanked" | "project.milestone_reached" | "suggestion.approved";
+		YankVersionRequest: {
+			/**
+			 * Shown to the users of the version, e.g. what is broken
+			 */
+			reason?: string | null;
+		};
+	};
+}
+
+export interface operations {
+	/**
+	 * Mutating requests that succeeded, most recent first. `query` is ignored.
+	 */
+	get_audit_log: {
+		query: {
+			/**
+			 * Full-text search, everything when empty
+			 */
+			query?: string;
+			/**
+			 * Starts at 1
+			 */
+			page?: number;
+			/**
+			 * At most 100, larger values are clamped
+			 */
+			per_page?: number;
+			actor_id?: string;
+			/**
+			 * e.g. `projects`, `engines`, `users`
+			 */
+			target_type?: string;
+			target_id?: string;
+			/**
+			 * Entries at or after this time
+			 */
+			since?: string;
+			/**
+			 * Entries before this time
+			 */
+			until?: string;
+		};
+	};
+	/**
+	 * Jobs of the background queue, most recent first.
+	 */
+	get_jobs: {
+		query: {
+			/**
+			 * Full-text search, everything when empty
+			 */
+			query?: string;
+			/**
+			 * Starts at 1
+			 */
+			page?: number;
+			/**
+			 * At most 100, larger values are clamped
+			 */
+			per_page?: number;
+			status?: components['schemas']['JobStatus'];
+			/**
+			 * e.g. `artifact.validate`
+			 */
+			kind?: string;
+		};
+	};
+	get_engines: {
+		query: {
+			/**
+			 * Full-text search, everything when empty
+			 */
+			query?: string;
+			/**
+			 * Starts at 1
+			 */
+			page?: number;
+			/**
+			 * At most 100, larger values are clamped
+			 */
+			per_page?: number;
+			sort?: components['schemas']['EngineSort'];
+			/**
+			 * Only engines supporting this source language
+			 */
+			language?: string;
+			wit_version?: string;
+		};
+	};
+	/**
+	 * Uploads a release. The body is the WebAssembly component, signed with a
+	 * key the caller registered. The release stays pending until a background
+	 * job checks it implements the `tsukimi:extension` world, it then becomes
+	 * the current version unless a greater one already is.
+	 */
+	publish_version: {
+		query: {
+			/**
+			 * Release notes
+			 */
+			description?: string;
+		};
+	};
+	/**
+	 * Starts an authorization. The PKCE verifier is kept server side, the client
+	 * only sends back the `state` along the code the provider redirected it with.
+	 * An authorization to link an account is bound to the caller, only they can
+	 * complete it and it can't open a session.
+	 */
+	authorize_url_handler: {
+		query: {
+			/**
+			 * `link` to link the account to the caller instead of logging in
+			 */
+			purpose?: "login" | "link";
+		};
+	};
+	/**
+	 * Public projects, plus the private ones the caller is a member of.
+	 */
+	get_projects: {
+		query: {
+			/**
+			 * Full-text search, everything when empty
+			 */
+			query?: string;
+			/**
+			 * Starts at 1
+			 */
+			page?: number;
+			/**
+			 * At most 100, larger values are clamped
+			 */
+			per_page?: number;
+			visual_novel_id?: string;
+			include_archived?: boolean;
+		};
+	};
+	get_review_queue: {
+		query: {
+			/**
+			 * Full-text search, everything when empty
+			 */
+			query?: string;
+			/**
+			 * Starts at 1
+			 */
+			page?: number;
+			/**
+			 * At most 100, larger values are clamped
+			 */
+			per_page?: number;
+			language: string;
+			file?: string;
+			/**
+			 * Without a state, lists what needs a reviewer: unreviewed translations
+			 * and units with pending suggestions
+			 */
+			state?: components['schemas']['ReviewState'];
+		};
+	};
+	get_project_stats: {
+		query: {
+			language?: string;
+			/**
+			 * Number of days of activity to return
+			 */
+			days?: number;
+		};
+	};
+	get_threads: {
+		query: {
+			/**
+			 * Full-text search, everything when empty
+			 */
+			query?: string;
+			/**
+			 * Starts at 1
+			 */
+			page?: number;
+			/**
+			 * At most 100, larger values are clamped
+			 */
+			per_page?: number;
+			unit_id?: string;
+			language?: string;
+			flag?: components['schemas']['IssueFlag'];
+			resolved?: boolean;
+		};
+	};
+	get_units: {
+		query: {
+			/**
+			 * Full-text search, everything when empty
+			 */
+			query?: string;
+			/**
+			 * Starts at 1
+			 */
+			page?: number;
+			/**
+			 * At most 100, larger values are clamped
+			 */
+			per_page?: number;
+			file?: string;
+			/**
+			 * Joins the translations of this language
+			 */
+			language?: string;
+			include_obsolete?: boolean;
+		};
+	};
+	get_unit_threads: {
+		query: {
+			/**
+			 * Full-text search, everything when empty
+			 */
+			query?: string;
+			/**
+			 * Starts at 1
+			 */
+			page?: number;
+			/**
+			 * At most 100, larger values are clamped
+			 */
+			per_page?: number;
+			unit_id?: string;
+			language?: string;
+			flag?: components['schemas']['IssueFlag'];
+			resolved?: boolean;
+		};
+	};
+	get_visual_novels: {
+		query: {
+			/**
+			 * Full-text search, everything when empty
+			 */
+			query?: string;
+			/**
+			 * Starts at 1
+			 */
+			page?: number;
+			/**
+			 * At most 100, larger values are clamped
+			 */
+			per_page?: number;
+			engine_id?: string;
+			original_language?: string;
+		};
+	};
+	get_webhooks: {
+		query: {
+			/**
+			 * Exactly one of `engine_id` and `project_id` must be given
+			 */
+			engine_id?: string;
+			project_id?: string;
+		};
+	};
+	/**
+	 * Delivery log, most recent first.
+	 */
+	get_deliveries: {
+		query: {
+			/**
+			 * Full-text search, everything when empty
+			 */
+			query?: string;
+			/**
+			 * Starts at 1
+			 */
+			page?: number;
+			/**
+			 * At most 100, larger values are clamped
+			 */
+			per_page?: number;
+		};
+	};
+}
@@ -1,12 +1,6 @@
-export type Extension = {
-	id: string;
-	name: string;
-	description: string;
-	current_version: string;
-};
+// Generated from the API's `openapi.json` by `npm run types:api`.
+import type { components, operations } from './api';
 
-export type Pagination = {
-	query: string;
-	page: number;
-	per_page: number;
-};
+export type Extension = components['schemas']['Engine'];
+
+export type Pagination = Pick<operations['get_engines']['query'], 'query' | 'page' | 'per_page'>;